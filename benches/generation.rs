@@ -7,7 +7,10 @@ mod util;
 use std::hint::black_box;
 
 use diol::prelude::*;
-use oxeylyzer_core::{corpus_cleaner::CorpusCleaner, data::Data, layout::PosPair};
+use oxeylyzer_core::{
+    analyzer_data::AnalyzerData, corpus_cleaner::CorpusCleaner, data::Data, layout::PosPair,
+    weights::Config,
+};
 
 use crate::util::oxeylyzer;
 
@@ -130,6 +133,7 @@ fn shuffle_pins(bencher: Bencher, pin_count: usize) {
 
 fn load_data(bencher: Bencher, language: &str) {
     let path = format!("./static/language_data/{language}.json");
+    let weights = Config::with_loaded_weights("config.toml").unwrap().weights;
 
-    bencher.bench(|| Data::load(&path).unwrap().name)
+    bencher.bench(|| AnalyzerData::load(&path, &weights).unwrap().len())
 }
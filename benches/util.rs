@@ -3,11 +3,14 @@ use oxeylyzer_core::generate::Oxeylyzer;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn oxeylyzer(corpus: &str) -> Oxeylyzer {
-    use oxeylyzer_core::{data::Data, weights::Config};
+    use oxeylyzer_core::{analyzer_data::AnalyzerData, weights::Config};
 
     let config = Config::with_loaded_weights("config.toml").expect("Failed to load config");
-    let data =
-        Data::load(format!("./static/language_data/{corpus}.json")).expect("this should exist");
+    let data = AnalyzerData::load(
+        format!("./static/language_data/{corpus}.json"),
+        &config.weights,
+    )
+    .expect("this should exist");
 
-    Oxeylyzer::new(data, config)
+    Oxeylyzer::with_analyzer_data(data, config).expect("the config should be valid")
 }
@@ -1,20 +1,24 @@
 use std::sync::Arc;
 
+use itertools::Itertools;
+
 use crate::{char_mapping::CharMapping, data::Data, weights::Weights};
 
 /// Optimized data structure for layout analysis, containing frequency information for various n-grams.
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct AnalyzerData {
-    name: String,
-    chars: Box<[i64]>,
-    bigrams: Box<[i64]>,
-    skipgrams: Box<[i64]>,
-    skipgrams2: Box<[i64]>,
-    skipgrams3: Box<[i64]>,
-    trigrams: Box<[i64]>,
-    gen_trigrams: Box<[([u8; 3], i64)]>,
-    same_finger_weighted_bigrams: Box<[i64]>,
-    stretch_weighted_bigrams: Box<[i64]>,
+    pub(crate) name: String,
+    pub(crate) chars: Box<[i64]>,
+    pub(crate) bigrams: Box<[i64]>,
+    pub(crate) skipgrams: Box<[i64]>,
+    pub(crate) skipgrams2: Box<[i64]>,
+    pub(crate) skipgrams3: Box<[i64]>,
+    pub(crate) trigrams: Box<[i64]>,
+    pub(crate) gen_trigrams: Box<[([u8; 3], i64)]>,
+    pub(crate) gen_quadgrams: Box<[([u8; 4], i64)]>,
+    pub(crate) words: Box<[(Box<[u8]>, i64)]>,
+    pub(crate) same_finger_weighted_bigrams: Box<[i64]>,
+    pub(crate) stretch_weighted_bigrams: Box<[i64]>,
     /// Total number of characters in the corpus.
     pub char_total: i64,
     /// Total number of bigrams in the corpus.
@@ -27,6 +31,10 @@ pub struct AnalyzerData {
     pub skipgram3_total: i64,
     /// Total number of trigrams in the corpus.
     pub trigram_total: i64,
+    /// Total number of quadgrams in the corpus, or 0 if they weren't collected.
+    pub quadgram_total: i64,
+    /// Total number of words in the corpus, or 0 if they weren't collected.
+    pub word_total: i64,
     /// Mapping between characters and their internal byte representations.
     pub mapping: Arc<CharMapping>,
 }
@@ -55,6 +63,8 @@ impl AnalyzerData {
         let skipgram2_total = data.skipgram2_total;
         let skipgram3_total = data.skipgram3_total;
         let trigram_total = data.trigram_total;
+        let quadgram_total = data.quadgram_total;
+        let word_total = data.word_total;
 
         let mut chars = vec![0; data.chars.len() + 3];
         let mut mapping = CharMapping::new();
@@ -139,57 +149,29 @@ impl AnalyzerData {
             })
             .collect::<Box<_>>();
 
-        let dsfb_ratio = weights.sfs / weights.sfbs;
-
-        let sfwb = bigrams
-            .iter()
-            .zip(&skipgrams)
-            .zip(&skipgrams2)
-            .zip(&skipgrams3)
-            .map(|(((&b, &s), &s2), &s3)| {
-                let sfb = b as f64;
-                let sfs = (s as f64) * dsfb_ratio;
-                let sfs2 = (s2 as f64) * dsfb_ratio.powi(2);
-                let sfs3 = (s3 as f64) * dsfb_ratio.powi(3);
-                ((sfb + sfs + sfs2 + sfs3) * weights.sfbs) as i64
-            })
-            .collect::<Vec<_>>();
-
-        let swb = bigrams
-            .iter()
-            .zip(&skipgrams)
-            .zip(&skipgrams2)
-            .zip(&skipgrams3)
-            .map(|(((&b, &s), &s2), &s3)| {
-                let sfb = b as f64;
-                let sfs = (s as f64) * dsfb_ratio;
-                let sfs2 = (s2 as f64) * dsfb_ratio.powi(2);
-                let sfs3 = (s3 as f64) * dsfb_ratio.powi(3);
-                ((sfb + sfs + sfs2 + sfs3) * weights.stretches) as i64
-            })
-            .collect::<Vec<_>>();
-
-        let same_finger_weighted_bigrams = (0..sfwb.len())
-            .map(|i| {
-                let u1 = i / len;
-                let u2 = i % len;
-                let j = u2 * len + u1;
-                sfwb[i] + sfwb[j]
+        let gen_quadgrams = data
+            .quadgrams
+            .into_iter()
+            .map(|(q, f)| {
+                let u = q.map(|c| mapping.get_u(c));
+                (u, (convert_f(f) * quadgram_total as f64) as i64)
             })
+            .sorted_by(|(q1, f1), (q2, f2)| f2.cmp(f1).then_with(|| q1.cmp(q2)))
             .collect::<Box<_>>();
 
-        let stretch_weighted_bigrams = (0..swb.len())
-            .map(|i| {
-                let u1 = i / len;
-                let u2 = i % len;
-                let j = u2 * len + u1;
-                swb[i] + swb[j]
+        let words = data
+            .words
+            .into_iter()
+            .map(|(w, f)| {
+                let u = w.chars().map(|c| mapping.get_u(c)).collect::<Box<_>>();
+                (u, (convert_f(f) * word_total as f64) as i64)
             })
+            .sorted_by(|(w1, f1), (w2, f2)| f2.cmp(f1).then_with(|| w1.cmp(w2)))
             .collect::<Box<_>>();
 
         let mapping = Arc::new(mapping);
 
-        Self {
+        let mut data = Self {
             name: data.name,
             chars: chars.into(),
             bigrams: bigrams.into(),
@@ -198,8 +180,10 @@ impl AnalyzerData {
             skipgrams3: skipgrams3.into(),
             trigrams: trigrams.into(),
             gen_trigrams,
-            same_finger_weighted_bigrams,
-            stretch_weighted_bigrams,
+            gen_quadgrams,
+            words,
+            same_finger_weighted_bigrams: Default::default(),
+            stretch_weighted_bigrams: Default::default(),
 
             char_total,
             bigram_total,
@@ -207,9 +191,48 @@ impl AnalyzerData {
             skipgram2_total,
             skipgram3_total,
             trigram_total,
+            quadgram_total,
+            word_total,
 
             mapping,
-        }
+        };
+        data.weigh_bigrams(weights);
+
+        data
+    }
+
+    /// Computes the same finger and stretch bigrams weighted by `weights`, which are the only
+    /// parts that depend on them. Both count bigrams in either order.
+    pub(crate) fn weigh_bigrams(&mut self, weights: &Weights) {
+        let len = self.len();
+        let weighted = |[r1, r2, r3]: [f64; 3], weight: f64| {
+            let weighted = self
+                .bigrams
+                .iter()
+                .zip(&self.skipgrams)
+                .zip(&self.skipgrams2)
+                .zip(&self.skipgrams3)
+                .map(|(((&b, &s), &s2), &s3)| {
+                    let sfb = b as f64;
+                    let sfs = (s as f64) * r1;
+                    let sfs2 = (s2 as f64) * r2;
+                    let sfs3 = (s3 as f64) * r3;
+                    ((sfb + sfs + sfs2 + sfs3) * weight) as i64
+                })
+                .collect::<Vec<_>>();
+
+            (0..weighted.len())
+                .map(|i| {
+                    let u1 = i / len;
+                    let u2 = i % len;
+                    let j = u2 * len + u1;
+                    weighted[i] + weighted[j]
+                })
+                .collect::<Box<_>>()
+        };
+
+        self.same_finger_weighted_bigrams = weighted(weights.dsfb_ratios(), weights.sfbs);
+        self.stretch_weighted_bigrams = weighted(weights.stretch_dsfb_ratios(), weights.stretches);
     }
 
     /// Returns the number of unique characters in the mapping.
@@ -342,6 +365,34 @@ impl AnalyzerData {
         &self.gen_trigrams
     }
 
+    /// Returns the quadgram data formatted for generation, sorted from most to least frequent.
+    /// This is empty if the corpus was created without collecting quadgrams.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::analyzer_data::AnalyzerData;
+    ///
+    /// let analyzer_data = AnalyzerData::default();
+    /// assert!(analyzer_data.gen_quadgrams().is_empty());
+    /// ```
+    pub fn gen_quadgrams(&self) -> &[([u8; 4], i64)] {
+        &self.gen_quadgrams
+    }
+
+    /// Returns the word data, sorted from most to least frequent. This is empty if the corpus
+    /// was created without collecting words.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::analyzer_data::AnalyzerData;
+    ///
+    /// let analyzer_data = AnalyzerData::default();
+    /// assert!(analyzer_data.words().is_empty());
+    /// ```
+    pub fn words(&self) -> &[(Box<[u8]>, i64)] {
+        &self.words
+    }
+
     /// Returns the frequency of a specific character.
     ///
     /// # Examples:
@@ -0,0 +1,773 @@
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use fxhash::FxHashMap as HashMap;
+use itertools::Itertools;
+
+use crate::{
+    OxeylyzerError, OxeylyzerResultExt, REPLACEMENT_CHAR, Result, analyzer_data::AnalyzerData,
+    char_mapping::CharMapping, data::Data, weights::Weights,
+};
+
+/// Bytes every binary data file starts with.
+pub const MAGIC: [u8; 4] = *b"OXYD";
+/// Version of the binary format. Files of any other version are not read.
+pub const VERSION: u16 = 2;
+
+const CHECKSUM_LEN: usize = 4;
+
+/// Size and modification time of the JSON file a binary file was written next to, used to tell
+/// whether the binary file is still up to date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceStamp {
+    len: u64,
+    modified: Duration,
+}
+
+impl SourceStamp {
+    /// Reads the stamp of a file, or returns `None` if its metadata isn't available.
+    pub fn of<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        Some(Self {
+            len: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// Encodes corpus data in the binary format. All numbers are little endian and of a fixed width.
+/// Characters are stored once in a table that n-grams refer to by index, using as few bytes per
+/// index as the size of the table allows, and the file ends in a CRC32 checksum of everything
+/// before it.
+///
+/// The table starts with the characters in the order [`AnalyzerData`] maps them, followed by
+/// characters that only occur in n-grams or words, so an index is also the position in its
+/// flat arrays. Quadgrams and words are stored in the order [`AnalyzerData`] sorts them, which
+/// lets [`analyzer_from_bytes`] fill it without any lookups or sorting.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{binary_data, data::Data};
+/// let data = Data::from("the quick brown fox");
+/// let bytes = binary_data::to_bytes(&data, None);
+///
+/// assert_eq!(&bytes[..4], b"OXYD");
+/// assert_eq!(binary_data::from_bytes(&bytes).unwrap().0, data);
+/// ```
+pub fn to_bytes(data: &Data, source: Option<SourceStamp>) -> Vec<u8> {
+    let mut w = Writer::default();
+
+    w.bytes(&MAGIC);
+    w.u16(VERSION);
+
+    match source {
+        Some(stamp) => {
+            w.u8(1);
+            w.u64(stamp.len);
+            w.u64(stamp.modified.as_secs());
+            w.u32(stamp.modified.subsec_nanos());
+        }
+        None => w.u8(0),
+    }
+
+    w.str(&data.name);
+
+    for total in [
+        data.char_total,
+        data.bigram_total,
+        data.skipgram_total,
+        data.skipgram2_total,
+        data.skipgram3_total,
+        data.trigram_total,
+        data.quadgram_total,
+        data.word_total,
+    ] {
+        w.i64(total);
+    }
+
+    let mut mapping = CharMapping::new();
+    data.chars.keys().for_each(|&c| mapping.push(c));
+    let mapped = mapping.len();
+
+    let unmapped = data
+        .bigrams
+        .keys()
+        .flatten()
+        .chain(data.skipgrams.keys().flatten())
+        .chain(data.skipgrams2.keys().flatten())
+        .chain(data.skipgrams3.keys().flatten())
+        .chain(data.trigrams.keys().flatten())
+        .chain(data.quadgrams.keys().flatten())
+        .copied()
+        .chain(data.words.keys().flat_map(|w| w.chars()))
+        .filter(|&c| mapping.get_u(c) == 0 && c != REPLACEMENT_CHAR)
+        .unique()
+        .sorted();
+    let table = (0..mapped)
+        .map(|u| mapping.get_c(u as u8))
+        .chain(unmapped)
+        .collect::<Vec<_>>();
+    let index = table
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (c, i as u32))
+        .collect::<HashMap<_, _>>();
+
+    w.u32(table.len() as u32);
+    w.u32(mapped as u32);
+    table.iter().for_each(|&c| w.u32(c as u32));
+
+    w.index_width = match table.len() {
+        0..=0x100 => 1,
+        0x101..=0x10000 => 2,
+        _ => 4,
+    };
+    w.u8(w.index_width);
+
+    w.ngrams(data.chars.iter().map(|(c, f)| ([*c], *f)), &index);
+    w.ngrams(data.bigrams.iter().map(|(b, f)| (*b, *f)), &index);
+    w.ngrams(data.skipgrams.iter().map(|(s, f)| (*s, *f)), &index);
+    w.ngrams(data.skipgrams2.iter().map(|(s, f)| (*s, *f)), &index);
+    w.ngrams(data.skipgrams3.iter().map(|(s, f)| (*s, *f)), &index);
+    w.ngrams(data.trigrams.iter().map(|(t, f)| (*t, *f)), &index);
+
+    // sorted like `AnalyzerData::new` sorts them
+    let quadgrams = data
+        .quadgrams
+        .iter()
+        .map(|(q, &f)| {
+            (
+                q.map(|c| mapping.get_u(c)),
+                count(f, data.quadgram_total),
+                *q,
+                f,
+            )
+        })
+        .sorted_by(|(u1, c1, ..), (u2, c2, ..)| c2.cmp(c1).then_with(|| u1.cmp(u2)))
+        .map(|(.., q, f)| (q, f));
+    w.ngrams(quadgrams, &index);
+
+    let words = data
+        .words
+        .iter()
+        .map(|(word, &f)| {
+            let u = word.chars().map(|c| mapping.get_u(c)).collect::<Vec<_>>();
+            (u, count(f, data.word_total), word, f)
+        })
+        .sorted_by(|(u1, c1, ..), (u2, c2, ..)| c2.cmp(c1).then_with(|| u1.cmp(u2)))
+        .collect::<Vec<_>>();
+
+    w.u32(words.len() as u32);
+    for (.., word, f) in words {
+        w.u32(word.chars().count() as u32);
+        word.chars().for_each(|c| w.index(index[&c]));
+        w.f64(f);
+    }
+
+    let checksum = crc32fast::hash(&w.buf);
+    w.u32(checksum);
+
+    w.buf
+}
+
+/// Counts in [`AnalyzerData`] are percentages of the total, rounded down.
+fn count(f: f64, total: i64) -> i64 {
+    (f / 100.0 * total as f64) as i64
+}
+
+/// Everything before the n-grams, and the reader positioned at them.
+struct Header<'a> {
+    reader: Reader<'a>,
+    source: Option<SourceStamp>,
+    name: String,
+    totals: [i64; 8],
+    table: Vec<char>,
+    mapped: usize,
+}
+
+fn read_header(bytes: &[u8]) -> Result<Header<'_>> {
+    if bytes.len() < MAGIC.len() + 2 + CHECKSUM_LEN || bytes[..MAGIC.len()] != MAGIC {
+        return Err(OxeylyzerError::InvalidBinaryData(
+            "not an oxeylyzer data file",
+        ));
+    }
+
+    let (content, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    let mut r = Reader {
+        bytes: content,
+        index_width: 4,
+    };
+    r.take(MAGIC.len())?;
+
+    let version = r.u16()?;
+    if version != VERSION {
+        return Err(OxeylyzerError::UnsupportedBinaryVersion(version, VERSION));
+    }
+    if crc32fast::hash(content) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err(OxeylyzerError::InvalidBinaryData("checksum mismatch"));
+    }
+
+    let source = match r.u8()? {
+        0 => None,
+        _ => Some(SourceStamp {
+            len: r.u64()?,
+            modified: Duration::new(r.u64()?, r.u32()?),
+        }),
+    };
+
+    let name = r.str()?;
+    let mut totals = [0; 8];
+    for total in totals.iter_mut() {
+        *total = r.i64()?;
+    }
+
+    let len = r.u32()? as usize;
+    let mapped = r.u32()? as usize;
+    if mapped > len {
+        return Err(OxeylyzerError::InvalidBinaryData("invalid character table"));
+    }
+
+    let table = (0..len)
+        .map(|_| {
+            r.u32().and_then(|c| {
+                char::from_u32(c).ok_or(OxeylyzerError::InvalidBinaryData("invalid character"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    r.index_width = match r.u8()? {
+        width @ (1 | 2 | 4) => width,
+        _ => return Err(OxeylyzerError::InvalidBinaryData("invalid index width")),
+    };
+
+    Ok(Header {
+        reader: r,
+        source,
+        name,
+        totals,
+        table,
+        mapped,
+    })
+}
+
+/// Decodes corpus data in the binary format, together with the stamp of the JSON file it was
+/// written next to, if any. Fails if the file has a different version, is cut off or its
+/// checksum doesn't match.
+pub fn from_bytes(bytes: &[u8]) -> Result<(Data, Option<SourceStamp>)> {
+    let Header {
+        reader: mut r,
+        source,
+        name,
+        totals,
+        table,
+        ..
+    } = read_header(bytes)?;
+    let [
+        char_total,
+        bigram_total,
+        skipgram_total,
+        skipgram2_total,
+        skipgram3_total,
+        trigram_total,
+        quadgram_total,
+        word_total,
+    ] = totals;
+
+    fn chars<const N: usize>(
+        ngrams: impl Iterator<Item = ([usize; N], f64)>,
+        table: &[char],
+    ) -> HashMap<[char; N], f64> {
+        ngrams
+            .map(|(ngram, f)| (ngram.map(|i| table[i]), f))
+            .collect()
+    }
+
+    let len = table.len();
+    let data = Data {
+        name,
+        chars: r.ngrams::<1>(len)?.map(|([c], f)| (table[c], f)).collect(),
+        bigrams: chars(r.ngrams(len)?, &table),
+        skipgrams: chars(r.ngrams(len)?, &table),
+        skipgrams2: chars(r.ngrams(len)?, &table),
+        skipgrams3: chars(r.ngrams(len)?, &table),
+        trigrams: chars(r.ngrams(len)?, &table),
+        quadgrams: chars(r.ngrams(len)?, &table),
+        words: r
+            .words(len)?
+            .map(|(w, f)| (w.into_iter().map(|i| table[i]).collect(), f))
+            .collect(),
+        char_total,
+        bigram_total,
+        skipgram_total,
+        skipgram2_total,
+        skipgram3_total,
+        trigram_total,
+        quadgram_total,
+        word_total,
+    };
+
+    r.finish()?;
+
+    Ok((data, source))
+}
+
+/// Decodes corpus data in the binary format straight into [`AnalyzerData`] for the given
+/// weights, giving the same result as converting the output of [`from_bytes`] but without
+/// building its maps first. Fails like [`from_bytes`], or if the data has more characters
+/// than [`AnalyzerData`] can map.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{analyzer_data::AnalyzerData, binary_data, data::Data};
+/// # use oxeylyzer_core::weights::Weights;
+/// let data = Data::from("the quick brown fox");
+/// let bytes = binary_data::to_bytes(&data, None);
+///
+/// let weights = Weights::default();
+/// let (analyzer_data, _) = binary_data::analyzer_from_bytes(&bytes, &weights).unwrap();
+///
+/// assert_eq!(analyzer_data, AnalyzerData::new(data, &weights));
+/// ```
+pub fn analyzer_from_bytes(
+    bytes: &[u8],
+    weights: &Weights,
+) -> Result<(AnalyzerData, Option<SourceStamp>)> {
+    let Header {
+        reader: mut r,
+        source,
+        name,
+        totals,
+        table,
+        mapped,
+    } = read_header(bytes)?;
+    let [
+        char_total,
+        bigram_total,
+        skipgram_total,
+        skipgram2_total,
+        skipgram3_total,
+        trigram_total,
+        quadgram_total,
+        word_total,
+    ] = totals;
+
+    if mapped > u8::MAX as usize + 1 {
+        return Err(OxeylyzerError::InvalidBinaryData("too many characters"));
+    }
+
+    // characters that aren't mapped count as the replacement character, which is mapped to 0
+    let u = |i: usize| if i < mapped { i } else { 0 };
+    let len = mapped;
+
+    let mut chars = vec![0; len];
+    for ([c], f) in r.ngrams::<1>(table.len())? {
+        chars[u(c)] = count(f, char_total);
+    }
+
+    let mut read_bigrams = |total| -> Result<Box<[i64]>> {
+        let mut bigrams = vec![0; len.pow(2)];
+        for ([c1, c2], f) in r.ngrams::<2>(table.len())? {
+            bigrams[u(c1) * len + u(c2)] = count(f, total);
+        }
+        Ok(bigrams.into())
+    };
+    let bigrams = read_bigrams(bigram_total)?;
+    let skipgrams = read_bigrams(skipgram_total)?;
+    let skipgrams2 = read_bigrams(skipgram2_total)?;
+    let skipgrams3 = read_bigrams(skipgram3_total)?;
+
+    let mut trigrams = vec![0; len.pow(3)];
+    let gen_trigrams = r
+        .ngrams::<3>(table.len())?
+        .map(|(t, f)| {
+            let [u1, u2, u3] = t.map(u);
+            let count = count(f, trigram_total);
+
+            trigrams[u1 * len.pow(2) + u2 * len + u3] = count;
+            ([u1 as u8, u2 as u8, u3 as u8], count)
+        })
+        .collect();
+
+    let gen_quadgrams = r
+        .ngrams::<4>(table.len())?
+        .map(|(q, f)| (q.map(|i| u(i) as u8), count(f, quadgram_total)))
+        .collect();
+
+    let words = r
+        .words(table.len())?
+        .map(|(w, f)| {
+            let w = w.into_iter().map(|i| u(i) as u8).collect();
+            (w, count(f, word_total))
+        })
+        .collect();
+
+    r.finish()?;
+
+    let mut mapping = CharMapping::new();
+    table[..mapped].iter().for_each(|&c| mapping.push(c));
+
+    let mut data = AnalyzerData {
+        name,
+        chars: chars.into(),
+        bigrams,
+        skipgrams,
+        skipgrams2,
+        skipgrams3,
+        trigrams: trigrams.into(),
+        gen_trigrams,
+        gen_quadgrams,
+        words,
+        char_total,
+        bigram_total,
+        skipgram_total,
+        skipgram2_total,
+        skipgram3_total,
+        trigram_total,
+        quadgram_total,
+        word_total,
+        mapping: Arc::new(mapping),
+        ..Default::default()
+    };
+    data.weigh_bigrams(weights);
+
+    Ok((data, source))
+}
+
+impl Data {
+    /// Saves the frequency data to a file in the binary format, stamped with the JSON file it
+    /// belongs to so it can be recognized as outdated when that file changes.
+    pub fn save_binary<P: AsRef<Path>, J: AsRef<Path>>(&self, path: P, json: J) -> Result<()> {
+        let bytes = to_bytes(self, SourceStamp::of(json));
+
+        std::fs::write(&path, bytes).path_context(path)
+    }
+
+    /// Loads frequency data from a file in the binary format.
+    pub fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(&path).path_context(&path)?;
+
+        from_bytes(&bytes).map(|(data, _)| data)
+    }
+
+    /// Loads the binary file next to a JSON file, if it exists, is valid and was written for
+    /// the current version of the JSON file.
+    pub(crate) fn load_binary_for(json: &Path) -> Option<Self> {
+        let bytes = std::fs::read(json.with_extension("bin")).ok()?;
+        let (data, source) = from_bytes(&bytes).ok()?;
+
+        up_to_date(json, source).then_some(data)
+    }
+}
+
+impl AnalyzerData {
+    /// Loads the data of a corpus for the given weights. Like [`Data::load`], an up to date
+    /// binary file next to a JSON file is preferred, which is converted directly with
+    /// [`analyzer_from_bytes`]. A `.bin` path is always loaded as binary.
+    pub fn load<P: AsRef<Path>>(path: P, weights: &Weights) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.extension().is_some_and(|ext| ext == "bin") {
+            let bytes = std::fs::read(path).path_context(path)?;
+            return analyzer_from_bytes(&bytes, weights).map(|(data, _)| data);
+        }
+
+        let binary = std::fs::read(path.with_extension("bin"))
+            .ok()
+            .and_then(|bytes| analyzer_from_bytes(&bytes, weights).ok())
+            .filter(|(_, source)| up_to_date(path, *source));
+
+        match binary {
+            Some((data, _)) => Ok(data),
+            None => Data::load(path).map(|data| AnalyzerData::new(data, weights)),
+        }
+    }
+}
+
+/// Whether a binary file with the given stamp was written for the current version of `json`.
+fn up_to_date(json: &Path, source: Option<SourceStamp>) -> bool {
+    match SourceStamp::of(json) {
+        Some(stamp) => source == Some(stamp),
+        None => true,
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+    index_width: u8,
+}
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, n: u8) {
+        self.buf.push(n);
+    }
+
+    fn u16(&mut self, n: u16) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn u64(&mut self, n: u64) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn i64(&mut self, n: i64) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn f64(&mut self, n: f64) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.u32(s.len() as u32);
+        self.bytes(s.as_bytes());
+    }
+
+    fn index(&mut self, i: u32) {
+        let i = i.to_le_bytes();
+        self.bytes(&i[..self.index_width as usize]);
+    }
+
+    fn ngrams<const N: usize>(
+        &mut self,
+        ngrams: impl Iterator<Item = ([char; N], f64)>,
+        index: &HashMap<char, u32>,
+    ) {
+        let ngrams = ngrams.collect::<Vec<_>>();
+
+        self.u32(ngrams.len() as u32);
+        for (ngram, f) in ngrams {
+            ngram.iter().for_each(|c| self.index(index[c]));
+            self.f64(f);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    index_width: u8,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < n {
+            return Err(OxeylyzerError::InvalidBinaryData("unexpected end of file"));
+        }
+
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.take(N).map(|bytes| bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        self.array().map(u8::from_le_bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        self.array().map(i64::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        self.array().map(f64::from_le_bytes)
+    }
+
+    fn index(&mut self, len: usize) -> Result<usize> {
+        let mut bytes = [0; 4];
+        bytes[..self.index_width as usize].copy_from_slice(self.take(self.index_width as usize)?);
+
+        match u32::from_le_bytes(bytes) as usize {
+            i if i < len => Ok(i),
+            _ => Err(OxeylyzerError::InvalidBinaryData("character out of range")),
+        }
+    }
+
+    fn str(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| OxeylyzerError::InvalidBinaryData("invalid string"))
+    }
+
+    /// Reads n-grams as indices into a character table of length `len`.
+    fn ngrams<const N: usize>(
+        &mut self,
+        len: usize,
+    ) -> Result<impl Iterator<Item = ([usize; N], f64)> + use<N>> {
+        let count = self.u32()? as usize;
+        let mut ngrams = Vec::with_capacity(count.min(self.bytes.len()));
+
+        for _ in 0..count {
+            let mut ngram = [0; N];
+
+            for i in ngram.iter_mut() {
+                *i = self.index(len)?;
+            }
+
+            ngrams.push((ngram, self.f64()?));
+        }
+
+        Ok(ngrams.into_iter())
+    }
+
+    /// Reads words as indices into a character table of length `len`.
+    fn words(&mut self, len: usize) -> Result<impl Iterator<Item = (Vec<usize>, f64)> + use<>> {
+        let count = self.u32()? as usize;
+        let mut words = Vec::with_capacity(count.min(self.bytes.len()));
+
+        for _ in 0..count {
+            let word = (0..self.u32()?)
+                .map(|_| self.index(len))
+                .collect::<Result<Vec<_>>>()?;
+
+            words.push((word, self.f64()?));
+        }
+
+        Ok(words.into_iter())
+    }
+
+    fn finish(&self) -> Result<()> {
+        match self.bytes.is_empty() {
+            true => Ok(()),
+            false => Err(OxeylyzerError::InvalidBinaryData("trailing bytes")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weights::Config;
+
+    fn english() -> Data {
+        let path = concat!(
+            std::env!("CARGO_MANIFEST_DIR"),
+            "/../static/language_data/english.json"
+        );
+
+        Data::load(path).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = english();
+        let bytes = to_bytes(&data, None);
+        let (decoded, source) = from_bytes(&bytes).unwrap();
+
+        assert_eq!(source, None);
+        assert_eq!(decoded, data);
+        assert_eq!(
+            AnalyzerData::new(decoded, &Weights::default()),
+            AnalyzerData::new(data, &Weights::default())
+        );
+    }
+
+    #[test]
+    fn analyzer_round_trip() {
+        let mut data = english();
+        // characters that aren't in `chars` are mapped to the replacement character
+        data.bigrams.insert(['€', 'e'], 0.01);
+        data.quadgrams.insert(['t', 'h', 'e', '€'], 0.01);
+        data.words.insert("5€".into(), 0.01);
+
+        let weights = Config::with_loaded_weights(concat!(
+            std::env!("CARGO_MANIFEST_DIR"),
+            "/../config.toml"
+        ))
+        .unwrap()
+        .weights;
+        let bytes = to_bytes(&data, None);
+        let (decoded, _) = analyzer_from_bytes(&bytes, &weights).unwrap();
+
+        assert_eq!(decoded, AnalyzerData::new(data, &weights));
+        assert!(analyzer_from_bytes(&bytes[..bytes.len() - 1], &weights).is_err());
+    }
+
+    #[test]
+    fn invalid_files() {
+        let bytes = to_bytes(&english(), None);
+
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 1;
+        assert!(matches!(
+            from_bytes(&corrupted),
+            Err(OxeylyzerError::InvalidBinaryData("checksum mismatch"))
+        ));
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            from_bytes(&newer),
+            Err(OxeylyzerError::UnsupportedBinaryVersion(_, VERSION))
+        ));
+
+        assert!(from_bytes(&bytes[..bytes.len() / 2]).is_err());
+        assert!(from_bytes(b"{\"name\": \"english\"}").is_err());
+    }
+
+    #[test]
+    fn prefer_up_to_date_binary() {
+        let folder = std::env::temp_dir().join(format!("oxeylyzer-binary-{}", std::process::id()));
+        let json = folder.join("english.json");
+        let binary = folder.join("english.bin");
+
+        let mut data = english();
+        data.name = "english".into();
+        data.save(&folder).unwrap();
+        assert!(binary.exists());
+        assert_eq!(Data::load(&json).unwrap(), data);
+        assert_eq!(Data::load(&binary).unwrap(), data);
+
+        let weights = Weights::default();
+        let analyzer_data = AnalyzerData::new(data.clone(), &weights);
+        assert_eq!(AnalyzerData::load(&json, &weights).unwrap(), analyzer_data);
+        assert_eq!(
+            AnalyzerData::load(&binary, &weights).unwrap(),
+            analyzer_data
+        );
+
+        // A binary written for another version of the json file is ignored
+        let mut other = data.clone();
+        other.name = "other".into();
+        std::fs::write(&binary, to_bytes(&other, None)).unwrap();
+        assert_eq!(Data::load(&json).unwrap().name, "english");
+        assert_eq!(
+            AnalyzerData::load(&json, &weights).unwrap().name(),
+            "english"
+        );
+
+        other.save_binary(&binary, &json).unwrap();
+        assert_eq!(Data::load(&json).unwrap().name, "other");
+
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use glob::Pattern;
+use serde::Deserialize;
+
+use crate::{BACKSPACE_CHAR, OxeylyzerError, OxeylyzerResultExt, Result, SHIFT_CHAR};
+
+/// Lines with fewer characters than this, not counting whitespace, are never removed as
+/// repeated. Short lines like a closing brace are typed over and over for real.
+const MIN_REPEATED_LINE_LEN: usize = 16;
+
+/// Width of an indentation level in files that don't indent with spaces.
+const DEFAULT_INDENT_WIDTH: usize = 4;
+
+/// How leading indentation of source code is counted.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Indentation {
+    /// Indentation is counted exactly as it is in the source.
+    #[default]
+    Keep,
+    /// Indentation is removed, as if the editor takes care of it entirely.
+    Collapse,
+    /// Every level of indentation is typed as a single tab.
+    Tab,
+    /// The editor keeps the indentation of the previous line, so only changes are typed: a tab
+    /// for every level deeper and a backspace, counted as [`BACKSPACE_CHAR`], for every level
+    /// shallower.
+    Auto,
+}
+
+/// Options for corpora made from source code, found in the `[code]` table of a corpus config.
+/// Every file is processed as a whole, so large files should be skipped with
+/// [`max_file_size`](Self::max_file_size). Key logs already contain indentation as it was
+/// typed, so they don't use these options.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{SHIFT_CHAR, code_corpus::{CodeOptions, Indentation}};
+/// let code: CodeOptions = toml::from_str(r#"
+///     indentation = "tab"
+///     fold_identifier_case = true
+/// "#).unwrap();
+///
+/// let text = "fn main() {\n    let camelCase = 1;\n}\n";
+/// assert_eq!(code.indentation, Indentation::Tab);
+/// assert_eq!(
+///     code.process(text),
+///     format!("fn main() {{\n\tlet camel{SHIFT_CHAR}case = 1;\n}}\n")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodeOptions {
+    /// How leading indentation is counted.
+    pub indentation: Indentation,
+    /// Glob patterns of files to skip, matched against their path relative to the source
+    /// directory, like `*.min.js` or `vendor/*`.
+    pub skip: Vec<String>,
+    /// Files larger than this amount of bytes are skipped, which are often generated.
+    pub max_file_size: Option<u64>,
+    /// Lines that are repeated more than this many times across the corpus are removed after
+    /// that, like license headers and other boilerplate. Only lines of at least 16 characters
+    /// besides whitespace are removed.
+    pub max_line_repeats: Option<usize>,
+    /// Whether uppercase letters inside identifiers, like the humps of camelCase, are counted
+    /// as [`SHIFT_CHAR`] followed by the lowercase letter, even if the cleaner doesn't insert
+    /// shift presses for uppercase letters. Underscores are kept as they are.
+    pub fold_identifier_case: bool,
+}
+
+impl CodeOptions {
+    /// Parses the glob patterns of files to skip.
+    pub fn skip_patterns(&self) -> Result<Vec<Pattern>> {
+        self.skip
+            .iter()
+            .map(|p| Pattern::new(p).map_err(|_| OxeylyzerError::InvalidSkipPattern(p.clone())))
+            .collect()
+    }
+
+    /// Lists the files of a source in alphabetical order, including the files in every
+    /// subdirectory, leaving out files that are skipped.
+    pub fn source_files<P: AsRef<Path>>(&self, source: P) -> Result<Vec<PathBuf>> {
+        let source = source.as_ref();
+        let patterns = self.skip_patterns()?;
+
+        let mut files = Vec::new();
+        let mut dirs = vec![source.to_path_buf()];
+
+        if source.is_file() {
+            files.push(source.to_path_buf());
+            dirs.clear();
+        } else if !source.exists() {
+            return Err(OxeylyzerError::PathDoesNotExist(source.to_path_buf()));
+        }
+
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(&dir).path_context(&dir)?.flatten() {
+                match entry.path() {
+                    path if path.is_dir() => dirs.push(path),
+                    path => files.push(path),
+                }
+            }
+        }
+
+        files.retain(|file| {
+            let relative = match file.strip_prefix(source) {
+                Ok(relative) if !relative.as_os_str().is_empty() => relative,
+                _ => file.file_name().map(Path::new).unwrap_or(file),
+            };
+            let too_large = self
+                .max_file_size
+                .is_some_and(|max| file.metadata().is_ok_and(|m| m.len() > max));
+
+            !too_large && !patterns.iter().any(|p| p.matches_path(relative))
+        });
+        files.sort();
+
+        Ok(files)
+    }
+
+    /// Processes the text of a single source file, changing its indentation and folding
+    /// identifiers as specified. Repeated lines are removed separately with [`RepeatedLines`].
+    pub fn process(&self, text: &str) -> String {
+        let text = match self.indentation {
+            Indentation::Keep => text.to_string(),
+            indentation => reindent(text, indentation),
+        };
+
+        match self.fold_identifier_case {
+            true => fold_identifier_case(&text),
+            false => text,
+        }
+    }
+
+    /// Processes a text as if it were the only file of a corpus, removing repeated lines as
+    /// well.
+    pub fn process_text(&self, text: String) -> String {
+        let text = RepeatedLines::new(self.max_line_repeats).remove(text);
+
+        self.process(&text)
+    }
+}
+
+/// Keeps track of how often lines were seen in the files of a corpus, removing them once they
+/// were seen more than a maximum amount of times. Files should be passed in a fixed order to
+/// get the same result every time.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::code_corpus::RepeatedLines;
+/// let header = "// Copyright (c) the authors\n";
+/// let mut repeated = RepeatedLines::new(Some(1));
+///
+/// assert_eq!(repeated.remove(format!("{header}a\n")), format!("{header}a\n"));
+/// assert_eq!(repeated.remove(format!("{header}b\n")), "b\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RepeatedLines {
+    max: Option<usize>,
+    seen: HashMap<String, usize>,
+}
+
+impl RepeatedLines {
+    /// Creates a tracker that keeps lines until they were seen more than `max` times, or
+    /// keeps every line if `max` is `None`.
+    pub fn new(max: Option<usize>) -> Self {
+        Self {
+            max,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Removes the lines of a file that were already seen too often.
+    pub fn remove(&mut self, text: String) -> String {
+        let Some(max) = self.max else {
+            return text;
+        };
+
+        text.split_inclusive('\n')
+            .filter(|line| {
+                let line = line.trim();
+                if line.chars().filter(|c| !c.is_whitespace()).count() < MIN_REPEATED_LINE_LEN {
+                    return true;
+                }
+
+                let count = self.seen.entry(line.to_string()).or_default();
+                *count += 1;
+                *count <= max
+            })
+            .collect()
+    }
+}
+
+/// Width of an indentation level in spaces, which is the most common increase in indentation
+/// between two lines that are indented with spaces. Alignment like the ` * ` of block comments
+/// and continuation lines changes the indentation as well, but less often than a new block.
+fn indent_width(text: &str) -> usize {
+    let mut deltas = HashMap::<usize, usize>::new();
+    let mut previous = None;
+
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        let spaces = (!indent.contains('\t')).then_some(indent.len());
+
+        if let (Some(previous), Some(spaces)) = (previous, spaces)
+            && spaces > previous
+        {
+            *deltas.entry(spaces - previous).or_default() += 1;
+        }
+        previous = spaces;
+    }
+
+    // ties go to the smaller width
+    deltas
+        .into_iter()
+        .max_by_key(|&(delta, count)| (count, std::cmp::Reverse(delta)))
+        .map(|(delta, _)| delta)
+        .unwrap_or(DEFAULT_INDENT_WIDTH)
+}
+
+fn reindent(text: &str, indentation: Indentation) -> String {
+    let width = indent_width(text);
+    let mut result = String::with_capacity(text.len());
+    let mut previous = 0;
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_start_matches([' ', '\t']);
+
+        // blank lines don't change the indentation of the lines around them
+        if content.trim().is_empty() {
+            result.push_str(content);
+            continue;
+        }
+
+        let indent = &line[..line.len() - content.len()];
+        let tabs = indent.chars().filter(|&c| c == '\t').count();
+        let level = tabs + (indent.len() - tabs) / width;
+
+        match indentation {
+            Indentation::Keep => result.push_str(indent),
+            Indentation::Collapse => {}
+            Indentation::Tab => result.extend(std::iter::repeat_n('\t', level)),
+            Indentation::Auto if level > previous => {
+                result.extend(std::iter::repeat_n('\t', level - previous))
+            }
+            Indentation::Auto => {
+                result.extend(std::iter::repeat_n(BACKSPACE_CHAR, previous - level))
+            }
+        }
+
+        result.push_str(content);
+        previous = level;
+    }
+
+    result
+}
+
+/// Turns uppercase letters that follow a lowercase letter or digit in an identifier into
+/// [`SHIFT_CHAR`] and the lowercase version of that letter, so the humps of `camelCase` are
+/// typed as `camel⇑case`. The first letter of an identifier and uppercase letters after other
+/// uppercase letters, where shift is held, are left to the cleaner.
+fn fold_identifier_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut after_lowercase = false;
+
+    for c in text.chars() {
+        if after_lowercase && c.is_uppercase() {
+            result.push(SHIFT_CHAR);
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+
+        after_lowercase = c.is_lowercase() || c.is_numeric();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CODE: &str = "\
+def main():
+    if True:
+        print(\"hi\")
+
+    return 1
+";
+
+    #[test]
+    fn indentation() {
+        let code = |indentation| CodeOptions {
+            indentation,
+            ..Default::default()
+        };
+
+        assert_eq!(code(Indentation::Keep).process(CODE), CODE);
+        assert_eq!(
+            code(Indentation::Collapse).process(CODE),
+            "def main():\nif True:\nprint(\"hi\")\n\nreturn 1\n"
+        );
+        assert_eq!(
+            code(Indentation::Tab).process(CODE),
+            "def main():\n\tif True:\n\t\tprint(\"hi\")\n\n\treturn 1\n"
+        );
+        assert_eq!(
+            code(Indentation::Auto).process(CODE),
+            format!("def main():\n\tif True:\n\tprint(\"hi\")\n\n{BACKSPACE_CHAR}return 1\n")
+        );
+
+        let tabs = "a\n\tb\n\t\tc\n";
+        assert_eq!(code(Indentation::Tab).process(tabs), tabs);
+    }
+
+    #[test]
+    fn indentation_with_alignment() {
+        let java = "\
+class A {
+    /**
+     * Adds two numbers.
+     */
+    int add(int a, int b) {
+        if (a > 0) {
+            return a
+              + b;
+        }
+        return b;
+    }
+}
+";
+        assert_eq!(indent_width(java), 4);
+        assert_eq!(indent_width("a\n  b\n    c\n     * d\n"), 2);
+        assert_eq!(indent_width("a\n\tb\n"), DEFAULT_INDENT_WIDTH);
+
+        let code = CodeOptions {
+            indentation: Indentation::Tab,
+            ..Default::default()
+        };
+        assert_eq!(
+            code.process(java),
+            "class A {\n\t/**\n\t* Adds two numbers.\n\t*/\n\tint add(int a, int b) {\n\t\t\
+            if (a > 0) {\n\t\t\treturn a\n\t\t\t+ b;\n\t\t}\n\t\treturn b;\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn identifier_case() {
+        assert_eq!(
+            fold_identifier_case("let parseHttp2Request = snake_case + Self::MAX_SIZE;"),
+            format!("let parse{SHIFT_CHAR}http2{SHIFT_CHAR}request = snake_case + Self::MAX_SIZE;")
+        );
+    }
+
+    #[test]
+    fn repeated_lines() {
+        let header = "# Licensed under the Apache License\n";
+        let file = |body: &str| format!("{header}}}\n{body}\n");
+
+        let mut repeated = RepeatedLines::new(Some(2));
+        let files = ["a", "b", "c"].map(|body| repeated.remove(file(body)));
+
+        assert_eq!(files[0], file("a"));
+        assert_eq!(files[1], file("b"));
+        assert_eq!(files[2], "}\nc\n");
+    }
+
+    #[test]
+    fn source_files() {
+        let dir = std::env::temp_dir().join(format!("oxeylyzer-code-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src/generated")).unwrap();
+        std::fs::write(dir.join("src/main.js"), "main()").unwrap();
+        std::fs::write(dir.join("src/lib.min.js"), "lib()").unwrap();
+        std::fs::write(dir.join("src/generated/large.js"), "x".repeat(1000)).unwrap();
+        std::fs::write(dir.join("readme.md"), "# readme").unwrap();
+
+        let code = CodeOptions {
+            skip: vec!["*.min.js".into()],
+            max_file_size: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(
+            code.source_files(&dir).unwrap(),
+            [dir.join("readme.md"), dir.join("src/main.js")]
+        );
+
+        let code = CodeOptions {
+            skip: vec!["src/*".into()],
+            ..Default::default()
+        };
+        assert_eq!(code.source_files(&dir).unwrap(), [dir.join("readme.md")]);
+
+        let invalid = CodeOptions {
+            skip: vec!["[".into()],
+            ..Default::default()
+        };
+        assert!(invalid.source_files(&dir).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
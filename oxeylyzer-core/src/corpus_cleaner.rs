@@ -2,9 +2,33 @@ use ahash::{AHashMap as HashMap, AHashSet as HashSet};
 
 use sliding_window_alt::SlidingWindow;
 use thiserror::Error;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
 
 use crate::*;
 
+/// Compatibility jamo of the leading consonants of Hangul syllables, `U+1100` to `U+1112`.
+const CHOSEONG: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ',
+    'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+/// Compatibility jamo of the trailing consonants of Hangul syllables, `U+11A8` to `U+11C2`.
+const JONGSEONG: [char; 27] = [
+    'ㄱ', 'ㄲ', 'ㄳ', 'ㄴ', 'ㄵ', 'ㄶ', 'ㄷ', 'ㄹ', 'ㄺ', 'ㄻ', 'ㄼ', 'ㄽ', 'ㄾ', 'ㄿ', 'ㅀ', 'ㅁ',
+    'ㅂ', 'ㅄ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅊ', 'ㅋ', 'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+/// Converts conjoining Hangul jamo, which is what syllables decompose into, to the compatibility
+/// jamo that are on Korean keyboards.
+fn compatibility_jamo(c: char) -> char {
+    match c as u32 {
+        i @ 0x1100..=0x1112 => CHOSEONG[(i - 0x1100) as usize],
+        i @ 0x1161..=0x1175 => char::from_u32(0x314F + i - 0x1161).unwrap_or(c),
+        i @ 0x11A8..=0x11C2 => JONGSEONG[(i - 0x11A8) as usize],
+        _ => c,
+    }
+}
+
 /// Errors that can occur during corpus cleaning.
 #[derive(Debug, Clone, Error)]
 pub enum CorpusError {}
@@ -17,6 +41,8 @@ pub struct CorpusCleaner {
     map: HashMap<char, Vec<char>>,
     shift_key: Option<char>,
     repeat_key: bool,
+    decompose: bool,
+    combining_marks: HashMap<char, Vec<char>>,
     raw: bool,
 }
 
@@ -26,6 +52,8 @@ impl Default for CorpusCleaner {
             map: HashMap::default(),
             shift_key: Some(SHIFT_CHAR),
             repeat_key: false,
+            decompose: false,
+            combining_marks: HashMap::default(),
             raw: true,
         }
     }
@@ -45,8 +73,11 @@ impl CorpusCleaner {
             shift_char: Some(SHIFT_CHAR),
             chars: HashSet::default(),
             shifted_chars: HashMap::default(),
+            shifted_dead_keys: HashMap::default(),
             mappings: HashMap::default(),
             repeat_key: false,
+            decompose: false,
+            combining_marks: HashMap::default(),
         }
     }
 
@@ -89,6 +120,55 @@ impl CorpusCleaner {
         self.repeat_key
     }
 
+    /// Returns true if characters without a mapping are decomposed.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::corpus_cleaner::CorpusCleaner;
+    ///
+    /// let cleaner = CorpusCleaner::builder().decompose(true).build();
+    /// assert!(cleaner.decomposes());
+    /// ```
+    pub fn decomposes(&self) -> bool {
+        self.decompose
+    }
+
+    /// Canonically decomposes a character (NFD), and returns the keys for its combining marks
+    /// followed by its base characters, like typing a dead key before a letter. Combining marks
+    /// without a configured mapping are left out. Hangul syllables are decomposed into the
+    /// compatibility jamo found on Korean keyboards.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::corpus_cleaner::CorpusCleaner;
+    ///
+    /// let cleaner = CorpusCleaner::builder()
+    ///     .decompose(true)
+    ///     .with_combining_marks([('\u{301}', vec!['´'])])
+    ///     .build();
+    ///
+    /// assert_eq!(cleaner.decomposition('é'), vec!['´', 'e']);
+    /// assert_eq!(cleaner.decomposition('ç'), vec!['c']);
+    /// assert_eq!(cleaner.decomposition('한'), vec!['ㅎ', 'ㅏ', 'ㄴ']);
+    /// ```
+    pub fn decomposition(&self, c: char) -> Vec<char> {
+        let mut marks = Vec::new();
+        let mut base = Vec::new();
+
+        decompose_canonical(c, |d| {
+            if is_combining_mark(d) {
+                if let Some(keys) = self.combining_marks.get(&d) {
+                    marks.extend(keys);
+                }
+            } else {
+                base.push(compatibility_jamo(d));
+            }
+        });
+
+        marks.extend(base);
+        marks
+    }
+
     /// Returns true if this is a raw cleaner.
     ///
     /// # Examples:
@@ -109,8 +189,11 @@ pub struct CorpusCleanerBuilder {
     shift_char: Option<char>,
     chars: HashSet<char>,
     shifted_chars: HashMap<char, char>,
+    shifted_dead_keys: HashMap<char, (char, char)>,
     mappings: HashMap<char, Vec<char>>,
     repeat_key: bool,
+    decompose: bool,
+    combining_marks: HashMap<char, Vec<char>>,
 }
 
 impl CorpusCleanerBuilder {
@@ -190,6 +273,51 @@ impl CorpusCleanerBuilder {
         self
     }
 
+    /// Enables or disables canonical decomposition of characters that don't have a mapping.
+    /// See [`CorpusCleaner::decomposition`] for how characters are decomposed.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::corpus_cleaner::{CleanCorpus, CorpusCleaner};
+    ///
+    /// let cleaner = CorpusCleaner::builder()
+    ///     .with_chars("aeu".chars())
+    ///     .decompose(true)
+    ///     .build();
+    ///
+    /// let cleaned = "äéü".chars().clean_corpus(&cleaner).flatten().collect::<String>();
+    /// assert_eq!(cleaned, "aeu");
+    /// ```
+    pub fn decompose(&mut self, enable: bool) -> &mut Self {
+        self.decompose = enable;
+
+        self
+    }
+
+    /// Maps combining marks to the keys that are pressed before the base character when a
+    /// character is decomposed. An empty list of keys leaves the mark out, which is also what
+    /// happens to marks without a mapping.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::corpus_cleaner::CorpusCleaner;
+    ///
+    /// let cleaner = CorpusCleaner::builder()
+    ///     .decompose(true)
+    ///     .with_combining_marks([('\u{308}', vec!['¨'])])
+    ///     .build();
+    ///
+    /// assert_eq!(cleaner.decomposition('ü'), vec!['¨', 'u']);
+    /// ```
+    pub fn with_combining_marks(
+        &mut self,
+        marks: impl IntoIterator<Item = (char, Vec<char>)>,
+    ) -> &mut Self {
+        self.combining_marks.extend(marks);
+
+        self
+    }
+
     /// Sets the character used to represent a shift key press.
     ///
     /// # Examples:
@@ -292,6 +420,35 @@ impl CorpusCleanerBuilder {
         self
     }
 
+    /// Adds mappings for uppercase characters that are typed with a dead key followed by a
+    /// shifted character. Like [`with_uppercase_mappings`](Self::with_uppercase_mappings),
+    /// mappings are given as `(lowercase, uppercase)`.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::corpus_cleaner::{CleanCorpus, CorpusCleaner};
+    ///
+    /// let cleaner = CorpusCleaner::builder()
+    ///     .with_chars(['a'])
+    ///     .with_dead_key([('á', 'a')], '´')
+    ///     .with_uppercase_dead_key([('a', 'Á')], '´')
+    ///     .build();
+    ///
+    /// let cleaned = "Áá".chars().clean_corpus(&cleaner).flatten().collect::<String>();
+    /// assert_eq!(cleaned, "´⇑a´a");
+    /// ```
+    pub fn with_uppercase_dead_key(
+        &mut self,
+        mappings: impl IntoIterator<Item = (char, char)>,
+        dead_key: char,
+    ) -> &mut Self {
+        mappings.into_iter().for_each(|(lower, upper)| {
+            self.shifted_dead_keys.insert(upper, (dead_key, lower));
+        });
+
+        self
+    }
+
     /// Adds standard QWERTY punctuation mappings (e.g., '!' maps to Shift + '1').
     ///
     /// # Examples:
@@ -373,39 +530,97 @@ impl CorpusCleanerBuilder {
 
         let mut chars = take(&mut self.chars);
         let shifted_chars = take(&mut self.shifted_chars);
+        let shifted_dead_keys = take(&mut self.shifted_dead_keys);
         let mut mappings = take(&mut self.mappings);
 
         if chars.remove(&' ') {
             mappings.insert(' ', vec![SPACE_CHAR]);
         }
 
-        let map = chars
-            .into_iter()
-            .map(|c| (c, vec![c]))
-            .chain(
-                shifted_chars
-                    .into_iter()
-                    .map(|(from, to)| match self.shift_char {
-                        Some(sc) => (from, vec![sc, to]),
-                        None => (from, vec![to]),
-                    }),
-            )
-            .chain(mappings)
-            .collect();
+        let combining_marks = take(&mut self.combining_marks);
+
+        let mut map: HashMap<_, _> =
+            chars
+                .into_iter()
+                .map(|c| (c, vec![c]))
+                .chain(
+                    shifted_chars
+                        .into_iter()
+                        .map(|(from, to)| match self.shift_char {
+                            Some(sc) => (from, vec![sc, to]),
+                            None => (from, vec![to]),
+                        }),
+                )
+                .chain(shifted_dead_keys.into_iter().map(
+                    |(from, (dk, to))| match self.shift_char {
+                        Some(sc) => (from, vec![dk, sc, to]),
+                        None => (from, vec![dk, to]),
+                    },
+                ))
+                .chain(mappings)
+                .collect();
+
+        // keys of combining marks are typed as they are, unless they have a mapping themselves
+        for &k in combining_marks.values().flatten() {
+            map.entry(k).or_insert_with(|| vec![k]);
+        }
 
         let shift_key = self.shift_char;
         let repeat_key = self.repeat_key;
+        let decompose = self.decompose;
         let raw = false;
 
         CorpusCleaner {
             map,
             shift_key,
             repeat_key,
+            decompose,
+            combining_marks,
             raw,
         }
     }
 }
 
+/// The state a [`CorpusCleanerIterator`] carries from one character to the next: the last
+/// character it saw, used for the repeat key, and whether shift is being held. Resuming from the
+/// state at the end of one piece of text with
+/// [`clean_corpus_from`](CleanCorpus::clean_corpus_from) cleans the next piece exactly like
+/// cleaning both in one go would.
+///
+/// # Examples:
+/// ```
+/// use oxeylyzer_core::corpus_cleaner::{CleanCorpus, CleanerState, CorpusCleaner};
+///
+/// let cleaner = CorpusCleaner::builder()
+///     .with_chars("abc".chars())
+///     .repeat_key(true)
+///     .build();
+///
+/// let mut first = "ABCa".chars().clean_corpus(&cleaner);
+/// let mut cleaned = first.by_ref().flatten().collect::<String>();
+/// let state = first.state();
+/// assert_ne!(state, CleanerState::default());
+///
+/// cleaned.extend("aBC".chars().clean_corpus_from(&cleaner, state).flatten());
+///
+/// let whole = "ABCaaBC".chars().clean_corpus(&cleaner).flatten().collect::<String>();
+/// assert_eq!(cleaned, whole);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CleanerState {
+    last: char,
+    shift_pressed: bool,
+}
+
+impl Default for CleanerState {
+    fn default() -> Self {
+        Self {
+            last: REPLACEMENT_CHAR,
+            shift_pressed: false,
+        }
+    }
+}
+
 /// An iterator that cleans corpus text using a [`CorpusCleaner`].
 #[must_use = "iterators are lazy and do nothing unless consumed"]
 #[derive(Debug)]
@@ -415,6 +630,68 @@ pub struct CorpusCleanerIterator<'a, I> {
     window: SlidingWindow<char>,
     shift_pressed: bool,
     use_window: bool,
+    decomposed: Vec<char>,
+    unmapped: HashMap<char, i64>,
+}
+
+impl<I> CorpusCleanerIterator<'_, I>
+where
+    I: Iterator<Item = char>,
+{
+    /// Returns the state the iterator is in after the characters it has cleaned so far.
+    pub fn state(&self) -> CleanerState {
+        CleanerState {
+            last: match self.use_window {
+                true => self.window[0],
+                false => REPLACEMENT_CHAR,
+            },
+            shift_pressed: self.shift_pressed,
+        }
+    }
+
+    /// Returns how often each character that couldn't be mapped was replaced with
+    /// [`REPLACEMENT_CHAR`] so far.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::corpus_cleaner::{CleanCorpus, CorpusCleaner};
+    ///
+    /// let cleaner = CorpusCleaner::builder().with_chars("abc".chars()).build();
+    ///
+    /// let mut iter = "abxcyx".chars().clean_corpus(&cleaner);
+    /// iter.by_ref().for_each(drop);
+    ///
+    /// let mut unmapped = iter.unmapped().collect::<Vec<_>>();
+    /// unmapped.sort();
+    /// assert_eq!(unmapped, [('x', 2), ('y', 1)]);
+    /// ```
+    pub fn unmapped(&self) -> impl Iterator<Item = (char, i64)> + '_ {
+        self.unmapped.iter().map(|(&c, &count)| (c, count))
+    }
+
+    fn replace(&mut self, c: char) -> Option<Vec<char>> {
+        *self.unmapped.entry(c).or_insert(0) += 1;
+        Some(vec![REPLACEMENT_CHAR])
+    }
+
+    /// Returns the next character, decomposing it first if the cleaner decomposes characters
+    /// without a mapping.
+    fn next_char(&mut self) -> Option<char> {
+        loop {
+            if let Some(c) = self.decomposed.pop() {
+                return Some(c);
+            }
+
+            let c = self.iter.next()?;
+
+            if !self.cleaner.decompose || self.cleaner.map.contains_key(&c) {
+                return Some(c);
+            }
+
+            self.decomposed = self.cleaner.decomposition(c);
+            self.decomposed.reverse();
+        }
+    }
 }
 
 impl<I> Iterator for CorpusCleanerIterator<'_, I>
@@ -424,7 +701,7 @@ where
     type Item = Vec<char>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let c = self.iter.next()?;
+        let c = self.next_char()?;
         if self.cleaner.raw {
             return Some(vec![c]);
         }
@@ -442,7 +719,8 @@ where
 
         if let Some(sk) = self.cleaner.shift_key {
             match self.cleaner.map.get(&c).map(|v| v.as_slice()) {
-                Some(&[f]) if f == sk => Some(vec![REPLACEMENT_CHAR]),
+                // the shift key itself is only kept when it's typed literally
+                Some(&[f]) if f == sk && c != sk => self.replace(c),
                 Some(&[REPEAT_KEY]) => Some(vec![REPEAT_KEY]),
                 Some(&[f]) if self.shift_pressed => {
                     self.shift_pressed = false;
@@ -469,11 +747,11 @@ where
                     Some(s.to_vec())
                 }
                 Some(s) => Some(s.to_vec()),
-                _ => Some(vec![REPLACEMENT_CHAR]),
+                _ => self.replace(c),
             }
         } else {
             match self.cleaner.map.get(&c) {
-                None => Some(vec![REPLACEMENT_CHAR]),
+                None => self.replace(c),
                 some => some.cloned(),
             }
         }
@@ -500,18 +778,38 @@ pub trait CleanCorpus: Iterator {
         Self: Iterator<Item = char>,
         Self: Sized,
     {
-        let window = match cleaner.repeat_key {
+        self.clean_corpus_from(cleaner, CleanerState::default())
+    }
+
+    /// Wraps the iterator with a [`CorpusCleanerIterator`] that resumes from `state`, which is
+    /// usually the [`state`](CorpusCleanerIterator::state) of an iterator that cleaned the text
+    /// right before this one.
+    fn clean_corpus_from(
+        self,
+        cleaner: &CorpusCleaner,
+        state: CleanerState,
+    ) -> CorpusCleanerIterator<'_, impl Iterator<Item = char>>
+    where
+        Self: Iterator<Item = char>,
+        Self: Sized,
+    {
+        let mut window = match cleaner.repeat_key {
             true => SlidingWindow::new(2, REPLACEMENT_CHAR),
             false => SlidingWindow::new(1, REPLACEMENT_CHAR),
         };
+        if state.last != REPLACEMENT_CHAR {
+            window.push(state.last);
+        }
         let iter = self;
 
         CorpusCleanerIterator {
             cleaner,
             iter,
             window,
-            shift_pressed: false,
+            shift_pressed: state.shift_pressed,
             use_window: cleaner.repeat_key,
+            decomposed: Vec::new(),
+            unmapped: HashMap::default(),
         }
     }
 }
@@ -592,6 +890,8 @@ mod tests {
             map: vanilla_cleaner.map.clone(),
             shift_key: vanilla_cleaner.shift_key,
             repeat_key: true,
+            decompose: false,
+            combining_marks: HashMap::default(),
             raw: false,
         };
 
@@ -0,0 +1,1113 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, serde_conv};
+
+use crate::{
+    BACKSPACE_CHAR, OxeylyzerError, OxeylyzerResultExt, Result, SHIFT_CHAR,
+    code_corpus::{CodeOptions, Indentation},
+    corpus_cleaner::CorpusCleaner,
+};
+
+serde_conv!(
+    StringAsCharArray,
+    Vec<char>,
+    |chars: &[char]| String::from_iter(chars),
+    |string: String| -> std::result::Result<_, Infallible> {
+        Ok(string.chars().collect::<Vec<_>>())
+    }
+);
+
+serde_conv!(
+    MultipleAsVec,
+    Vec<(char, String)>,
+    |_: &[(char, String)]| {
+        unimplemented!("Serializing a Multiple struct is currently unsupported");
+        #[allow(unused)]
+        Multiple::default()
+    },
+    |multiple: Multiple| -> std::result::Result<_, Infallible> {
+        let vec = multiple
+            .list
+            .into_iter()
+            .flat_map(|(from, to)| {
+                if multiple.uppercase_versions && from.to_uppercase().count() == 1 {
+                    let upper = from.to_uppercase().next().unwrap();
+                    vec![(from, to.clone()), (upper, to)]
+                } else {
+                    vec![(from, to)]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(vec)
+    }
+);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Multiple {
+    #[serde(default)]
+    uppercase_versions: bool,
+    list: Vec<(char, String)>,
+}
+
+/// Pairs of characters where every character in `from` is mapped to the character at the same
+/// index in `to`.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Default, PartialEq)]
+pub struct OneToOne {
+    /// Characters that are mapped.
+    #[serde_as(as = "StringAsCharArray")]
+    pub from: Vec<char>,
+    /// Characters they are mapped to.
+    #[serde_as(as = "StringAsCharArray")]
+    pub to: Vec<char>,
+}
+
+impl OneToOne {
+    fn validate(&self, name: &'static str) -> Result<()> {
+        match self.from.len() == self.to.len() {
+            true => Ok(()),
+            false => Err(OxeylyzerError::UnevenCorpusMapping(
+                name,
+                self.from.len(),
+                self.to.len(),
+            )),
+        }
+    }
+}
+
+impl std::ops::Add for OneToOne {
+    type Output = OneToOne;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let from = self.from.into_iter().chain(rhs.from).collect();
+        let to = self.to.into_iter().chain(rhs.to).collect();
+
+        Self { from, to }
+    }
+}
+
+/// Characters that are typed by pressing a dead key followed by another key. Every character in
+/// `from` is typed as the dead key followed by the character at the same index in `to`.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::corpus_config::DeadKey;
+/// let dead_key: DeadKey = toml::from_str(r#"
+///     from = "áé"
+///     to =   "ae"
+///     uppercase_versions = true
+/// "#).unwrap();
+///
+/// assert_eq!(dead_key.compositions().collect::<Vec<_>>(), [('á', 'a'), ('é', 'e')]);
+/// assert_eq!(dead_key.uppercase_compositions().collect::<Vec<_>>(), [('a', 'Á'), ('e', 'É')]);
+/// ```
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Default, PartialEq)]
+pub struct DeadKey {
+    /// Characters that are typed with the dead key.
+    #[serde_as(as = "StringAsCharArray")]
+    pub from: Vec<char>,
+    /// Characters that are pressed after the dead key.
+    #[serde_as(as = "StringAsCharArray")]
+    pub to: Vec<char>,
+    /// Whether the uppercase versions of `from` are typed as the dead key followed by shift and
+    /// the character in `to`.
+    #[serde(default)]
+    pub uppercase_versions: bool,
+}
+
+impl DeadKey {
+    /// Pairs of a composed character and the character pressed after the dead key.
+    pub fn compositions(&self) -> impl Iterator<Item = (char, char)> + '_ {
+        self.from.iter().copied().zip(self.to.iter().copied())
+    }
+
+    /// Pairs of the character pressed after the dead key and the uppercase composed character,
+    /// if `uppercase_versions` is enabled. Characters that don't have a single character
+    /// uppercase version are skipped.
+    pub fn uppercase_compositions(&self) -> impl Iterator<Item = (char, char)> + '_ {
+        self.compositions()
+            .filter(|_| self.uppercase_versions)
+            .filter_map(|(from, to)| {
+                let mut upper = from.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(u), None) if u != from => Some((to, u)),
+                    _ => None,
+                }
+            })
+    }
+
+    fn validate(&self, dead_key: char) -> Result<()> {
+        match self.from.len() == self.to.len() {
+            true => Ok(()),
+            false => Err(OxeylyzerError::UnevenDeadKeyMapping(
+                dead_key,
+                self.from.len(),
+                self.to.len(),
+            )),
+        }
+    }
+}
+
+impl std::ops::Add for DeadKey {
+    type Output = DeadKey;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let from = self.from.into_iter().chain(rhs.from).collect();
+        let to = self.to.into_iter().chain(rhs.to).collect();
+        let uppercase_versions = self.uppercase_versions || rhs.uppercase_versions;
+
+        Self {
+            from,
+            to,
+            uppercase_versions,
+        }
+    }
+}
+
+serde_conv!(
+    ShiftCharacter,
+    Option<char>,
+    |v: &Option<char>| match v {
+        Some(SHIFT_CHAR) => None,
+        Some(c) => Some(c.to_string()),
+        None => Some("null".to_string()),
+    },
+    |shift_char: Option<String>| -> Result<_> {
+        match shift_char.as_deref() {
+            None => Ok(Some(SHIFT_CHAR)),
+            Some("null") => Ok(None),
+            Some(c) if c.chars().count() == 1 => Ok(Some(c.chars().next().unwrap())),
+            Some(s) => Err(OxeylyzerError::WrongShiftKeyLength(s.to_string())),
+        }
+    }
+);
+
+/// Character used to represent shift presses, or `None` if shift presses aren't simulated.
+/// Defaults to [`SHIFT_CHAR`], and `"null"` in a config disables it.
+#[serde_as]
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct ShiftKey(#[serde_as(as = "ShiftCharacter")] Option<char>);
+
+impl ShiftKey {
+    /// Returns the shift character, if shift presses are simulated.
+    pub fn key(&self) -> Option<char> {
+        self.0
+    }
+}
+
+impl std::default::Default for ShiftKey {
+    fn default() -> Self {
+        Self(Some(SHIFT_CHAR))
+    }
+}
+
+/// How a source is sampled, keeping only part of it. Sampling is evenly spread over the source
+/// rather than random, so loading the same corpus twice gives the same result.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::corpus_config::Sampling;
+/// let sampling: Sampling = toml::from_str("lines = 0.25").unwrap();
+///
+/// assert_eq!(sampling, Sampling::Lines(0.25));
+/// assert_eq!(sampling.rate(), 0.25);
+/// ```
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Sampling {
+    /// Keeps this share of the lines of the source.
+    Lines(f64),
+    /// Keeps this share of the bytes of the source, taken from the start of every block of it
+    /// and cut off after the last space.
+    Bytes(f64),
+}
+
+impl Sampling {
+    /// Share of the source that is kept, between 0 and 1.
+    pub fn rate(&self) -> f64 {
+        match *self {
+            Self::Lines(rate) | Self::Bytes(rate) => rate,
+        }
+    }
+}
+
+/// A text file or directory a corpus is made from. In a corpus config, a source is either just a
+/// path, or a table that also specifies how much the source counts towards the corpus.
+///
+/// By default sources are simply added together, so a large source counts for more than a small
+/// one. As soon as one source of a corpus has a `weight` or a `share`, the counts of every source
+/// are normalized before they are merged: a source with a `share` makes up that share of the
+/// characters of the corpus, and the rest is split between the other sources in proportion to
+/// their `weight`, which defaults to 1.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::corpus_config::{CorpusConfig, Sampling};
+/// let config = toml::from_str::<CorpusConfig>(r#"
+///     sources = [
+///         "./dutch",
+///         { path = "./english", share = 0.25, sample = { lines = 0.1 } },
+///     ]
+/// "#).unwrap();
+///
+/// let [dutch, english] = config.sources() else { panic!() };
+/// assert!(!dutch.is_weighted());
+/// assert_eq!(english.share, Some(0.25));
+/// assert_eq!(english.sample, Some(Sampling::Lines(0.1)));
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(from = "SourceRepr")]
+pub struct CorpusSource {
+    /// Path of the text file or directory.
+    pub path: PathBuf,
+    /// Weight of the source relative to the other sources without a share.
+    pub weight: Option<f64>,
+    /// Share of the characters of the corpus that come from this source, between 0 and 1.
+    pub share: Option<f64>,
+    /// Which part of the source is used, if not all of it.
+    pub sample: Option<Sampling>,
+}
+
+impl CorpusSource {
+    /// Creates a source that uses all of `path` and isn't weighted.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the source has a weight or share, which means the sources of its corpus are
+    /// normalized before they are merged.
+    pub fn is_weighted(&self) -> bool {
+        self.weight.is_some() || self.share.is_some()
+    }
+
+    /// Weight of the source, which is 1 if it isn't specified.
+    pub fn weight(&self) -> f64 {
+        self.weight.unwrap_or(1.0)
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        let invalid = |reason| {
+            Err(OxeylyzerError::InvalidCorpusSource(
+                self.path.clone(),
+                reason,
+            ))
+        };
+
+        match (self.weight, self.share, self.sample.map(|s| s.rate())) {
+            (Some(_), Some(_), _) => invalid("it has both a weight and a share"),
+            (Some(w), _, _) if !(w > 0.0 && w.is_finite()) => invalid("its weight isn't positive"),
+            (_, Some(s), _) if !(s > 0.0 && s <= 1.0) => invalid("its share isn't between 0 and 1"),
+            (_, _, Some(r)) if !(r > 0.0 && r <= 1.0) => {
+                invalid("its sampling rate isn't between 0 and 1")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<PathBuf> for CorpusSource {
+    fn from(path: PathBuf) -> Self {
+        Self::new(path)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SourceRepr {
+    Path(PathBuf),
+    Table(SourceTable),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SourceTable {
+    path: PathBuf,
+    weight: Option<f64>,
+    share: Option<f64>,
+    sample: Option<Sampling>,
+}
+
+impl From<SourceRepr> for CorpusSource {
+    fn from(repr: SourceRepr) -> Self {
+        match repr {
+            SourceRepr::Path(path) => Self::new(path),
+            SourceRepr::Table(table) => Self {
+                path: table.path,
+                weight: table.weight,
+                share: table.share,
+                sample: table.sample,
+            },
+        }
+    }
+}
+
+/// Rules for turning raw text into a corpus, as found in the `.toml` files in
+/// `static/corpus_configs`. Configs can inherit the rules of other configs, which are resolved
+/// when converting the config to a [`CorpusCleaner`].
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{corpus_cleaner::CorpusCleaner, corpus_config::CorpusConfig};
+/// let config = toml::from_str::<CorpusConfig>(r#"
+///     letters_to_lowercase = "abc"
+///     repeat_key = true
+/// "#).unwrap();
+///
+/// let cleaner = CorpusCleaner::try_from(config).unwrap();
+/// assert!(cleaner.repeat_key());
+/// ```
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CorpusConfig {
+    inherits: Vec<PathBuf>,
+    sources: Vec<CorpusSource>,
+    #[serde_as(as = "StringAsCharArray")]
+    letters_to_lowercase: Vec<char>,
+    #[serde_as(as = "StringAsCharArray")]
+    keep: Vec<char>,
+    #[serde_as(as = "MultipleAsVec")]
+    multiple: Vec<(char, String)>,
+    one_to_one: OneToOne,
+    punct_unshifted: OneToOne,
+    dead_keys: BTreeMap<char, DeadKey>,
+    decompose: bool,
+    combining_marks: BTreeMap<char, String>,
+    shift_key: ShiftKey,
+    repeat_key: bool,
+    code: Option<CodeOptions>,
+    #[serde(skip)]
+    inherits_visited: HashSet<PathBuf>,
+}
+
+impl CorpusConfig {
+    /// Loads a corpus config from a `.toml` file. Paths of inherited configs and sources are
+    /// relative to the directory of the file. Inherited configs are not loaded until
+    /// [`resolve`](Self::resolve) is called.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::corpus_config::CorpusConfig;
+    /// let config = CorpusConfig::load(concat!(
+    ///     std::env!("CARGO_MANIFEST_DIR"),
+    ///     "/../static/corpus_configs/provided/english.toml"
+    /// )).unwrap();
+    ///
+    /// assert_eq!(config.inherits().len(), 1);
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let dir = path
+            .as_ref()
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or(PathBuf::from("./"));
+
+        let content = std::fs::read_to_string(&path).path_context(&path)?;
+
+        let mut config = toml::from_str::<Self>(&content).path_context(&path)?;
+
+        config.inherits.iter_mut().for_each(|p| *p = dir.join(&p));
+        config
+            .sources
+            .iter_mut()
+            .for_each(|s| s.path = dir.join(&s.path));
+
+        if let Ok(canonical) = path.as_ref().canonicalize() {
+            config.inherits_visited.insert(canonical);
+        }
+
+        Ok(config)
+    }
+
+    /// Loads all inherited configs, including the ones they inherit themselves, and merges their
+    /// rules into this one. Every config is merged only once, so circular inheritance is fine.
+    /// Inherited paths without an extension, like `default`, refer to `.toml` files.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::corpus_config::CorpusConfig;
+    /// let config = CorpusConfig::load(concat!(
+    ///     std::env!("CARGO_MANIFEST_DIR"),
+    ///     "/../static/corpus_configs/provided/english.toml"
+    /// )).unwrap();
+    ///
+    /// let resolved = config.resolve().unwrap();
+    /// assert!(resolved.letters_to_lowercase().contains(&'a'));
+    /// ```
+    pub fn resolve(mut self) -> Result<Self> {
+        let mut i = 0;
+
+        while let Some(mut path) = self.inherits.get(i).cloned() {
+            i += 1;
+
+            if path.extension().is_none() {
+                path.set_extension("toml");
+            }
+
+            let canonical = path.canonicalize().path_context(&path)?;
+            if self.inherits_visited.insert(canonical) {
+                self = self + Self::load(&path)?;
+            }
+        }
+
+        self.sources.iter().try_for_each(CorpusSource::validate)?;
+        if let Some(code) = &self.code {
+            code.skip_patterns()?;
+        }
+        self.one_to_one.validate("one_to_one")?;
+        self.punct_unshifted.validate("punct_unshifted")?;
+        self.dead_keys
+            .iter()
+            .try_for_each(|(&dk, dead_key)| dead_key.validate(dk))?;
+
+        Ok(self)
+    }
+
+    /// Paths of the configs this config inherits from.
+    pub fn inherits(&self) -> &[PathBuf] {
+        &self.inherits
+    }
+
+    /// The text files or directories the corpus is made from.
+    pub fn sources(&self) -> &[CorpusSource] {
+        &self.sources
+    }
+
+    /// Letters that are kept, with their uppercase versions turned into shift presses.
+    pub fn letters_to_lowercase(&self) -> &[char] {
+        &self.letters_to_lowercase
+    }
+
+    /// Characters that are kept exactly as they are.
+    pub fn keep(&self) -> &[char] {
+        &self.keep
+    }
+
+    /// Characters that are replaced by a sequence of characters.
+    pub fn multiple(&self) -> &[(char, String)] {
+        &self.multiple
+    }
+
+    /// Characters that are replaced by a single other character.
+    pub fn one_to_one(&self) -> &OneToOne {
+        &self.one_to_one
+    }
+
+    /// Shifted characters in `from` that are typed as shift with the character in `to`.
+    pub fn punct_unshifted(&self) -> &OneToOne {
+        &self.punct_unshifted
+    }
+
+    /// Dead keys and the characters that are typed with them.
+    pub fn dead_keys(&self) -> &BTreeMap<char, DeadKey> {
+        &self.dead_keys
+    }
+
+    /// Whether characters without a rule are canonically decomposed, see
+    /// [`CorpusCleaner::decomposition`].
+    pub fn decompose(&self) -> bool {
+        self.decompose
+    }
+
+    /// Keys pressed for combining marks when characters are decomposed. Marks mapped to an empty
+    /// string, or not mapped at all, are left out.
+    pub fn combining_marks(&self) -> &BTreeMap<char, String> {
+        &self.combining_marks
+    }
+
+    /// Character used for shift presses.
+    pub fn shift_key(&self) -> ShiftKey {
+        self.shift_key
+    }
+
+    /// Whether repeated characters are replaced by a repeat key.
+    pub fn repeat_key(&self) -> bool {
+        self.repeat_key
+    }
+
+    /// Options for corpora made from source code, if the sources are code.
+    pub fn code(&self) -> Option<&CodeOptions> {
+        self.code.as_ref()
+    }
+}
+
+impl std::ops::Add<CorpusConfig> for CorpusConfig {
+    type Output = CorpusConfig;
+
+    fn add(self, rhs: CorpusConfig) -> Self::Output {
+        let inherits = self.inherits.into_iter().chain(rhs.inherits).collect();
+        let sources = self
+            .sources
+            .into_iter()
+            .chain(rhs.sources)
+            .unique_by(|s| s.path.canonicalize().unwrap_or_else(|_| s.path.clone()))
+            .collect();
+        let multiple = self.multiple.into_iter().chain(rhs.multiple).collect();
+        let letters_to_lowercase = self
+            .letters_to_lowercase
+            .into_iter()
+            .chain(rhs.letters_to_lowercase)
+            .collect();
+        let keep = self.keep.into_iter().chain(rhs.keep).collect();
+        let punct_unshifted = self.punct_unshifted + rhs.punct_unshifted;
+        let one_to_one = self.one_to_one + rhs.one_to_one;
+        let mut dead_keys = self.dead_keys;
+        for (dk, dead_key) in rhs.dead_keys {
+            let merged = dead_keys.remove(&dk).unwrap_or_default() + dead_key;
+            dead_keys.insert(dk, merged);
+        }
+        let decompose = self.decompose || rhs.decompose;
+        let mut combining_marks = rhs.combining_marks;
+        combining_marks.extend(self.combining_marks);
+        let repeat_key = self.repeat_key || rhs.repeat_key;
+        let inherits_visited = self
+            .inherits_visited
+            .into_iter()
+            .chain(rhs.inherits_visited)
+            .collect();
+        let shift_key = ShiftKey(self.shift_key.key().or(rhs.shift_key.key()));
+        let code = self.code.or(rhs.code);
+
+        CorpusConfig {
+            inherits,
+            sources,
+            letters_to_lowercase,
+            punct_unshifted,
+            keep,
+            multiple,
+            one_to_one,
+            dead_keys,
+            decompose,
+            combining_marks,
+            shift_key,
+            repeat_key,
+            code,
+            inherits_visited,
+        }
+    }
+}
+
+impl TryFrom<CorpusConfig> for CorpusCleaner {
+    type Error = OxeylyzerError;
+
+    fn try_from(config: CorpusConfig) -> Result<Self> {
+        let config = config.resolve()?;
+
+        let mut builder = CorpusCleaner::builder();
+
+        // indentation that is typed with tabs or undone with backspace is counted by default
+        match config.code.as_ref().map(|code| code.indentation) {
+            Some(Indentation::Tab) => builder.with_exact_mappings(['\t']),
+            Some(Indentation::Auto) => builder.with_exact_mappings(['\t', BACKSPACE_CHAR]),
+            _ => &mut builder,
+        };
+        // so are the shift presses that are inserted for identifiers
+        if config
+            .code
+            .as_ref()
+            .is_some_and(|code| code.fold_identifier_case)
+        {
+            builder.with_exact_mappings([SHIFT_CHAR]);
+        }
+
+        for (&dk, dead_key) in config.dead_keys.iter() {
+            builder
+                .with_dead_key(dead_key.compositions(), dk)
+                .with_uppercase_dead_key(dead_key.uppercase_compositions(), dk);
+        }
+
+        let cleaner = builder
+            .with_chars(config.letters_to_lowercase)
+            .with_exact_mappings(config.keep)
+            .with_char_mappings(config.one_to_one.from.into_iter().zip(config.one_to_one.to))
+            .with_uppercase_mappings(
+                config
+                    .punct_unshifted
+                    .to
+                    .into_iter()
+                    .zip(config.punct_unshifted.from),
+            )
+            .with_mappings(
+                config
+                    .multiple
+                    .into_iter()
+                    .map(|(c, s)| (c, s.chars().collect())),
+            )
+            .decompose(config.decompose)
+            .with_combining_marks(
+                config
+                    .combining_marks
+                    .into_iter()
+                    .map(|(mark, keys)| (mark, keys.chars().collect())),
+            )
+            .shift_char(config.shift_key.key())
+            .repeat_key(config.repeat_key)
+            .build();
+
+        Ok(cleaner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{REPLACEMENT_CHAR, corpus_cleaner::CleanCorpus};
+
+    #[test]
+    fn inherits() {
+        let config1 = r#"inherits = ["dofsmie"]"#;
+        let config2 = r#"inherits = ["yeah"]"#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(config1.inherits, vec!["dofsmie".to_string()]);
+        assert_eq!(config2.inherits, vec![PathBuf::from("yeah")]);
+
+        let config = config1 + config2;
+        assert_eq!(
+            config.inherits,
+            vec![PathBuf::from("dofsmie"), PathBuf::from("yeah")]
+        );
+    }
+
+    #[test]
+    fn resolve_inherits() {
+        let base = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
+        let path = base.join("static/corpus_configs/provided/english.toml");
+
+        let config = CorpusConfig::load(&path).unwrap();
+        assert!(config.letters_to_lowercase.is_empty());
+
+        let config = config.resolve().unwrap();
+        assert!(config.one_to_one.from.contains(&'‘'));
+        assert_eq!(config.letters_to_lowercase, ('a'..='z').collect::<Vec<_>>());
+
+        // resolving again doesn't merge anything twice
+        let resolved_twice = config.clone().resolve().unwrap();
+        assert_eq!(resolved_twice.one_to_one, config.one_to_one);
+
+        let without_extension = base.join("static/corpus_configs/provided/default");
+        let config = CorpusConfig {
+            inherits: vec![without_extension],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolve().unwrap().one_to_one,
+            resolved_twice.one_to_one
+        );
+
+        let missing = toml::from_str::<CorpusConfig>(r#"inherits = ["./does_not_exist.toml"]"#);
+        assert!(missing.unwrap().resolve().is_err());
+    }
+
+    #[test]
+    fn sources() {
+        let config1 = r#"sources = ["../static"]"#;
+        let config2 = r#"sources = ["../target", "./../static"]"#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(config1.sources, vec![CorpusSource::new("../static")]);
+        assert_eq!(
+            config2.sources,
+            vec![
+                CorpusSource::new("../target"),
+                CorpusSource::new("./../static")
+            ]
+        );
+
+        let config = config1 + config2;
+        assert_eq!(
+            config.sources,
+            vec![
+                CorpusSource::new("../static"),
+                CorpusSource::new("../target")
+            ]
+        );
+
+        let weighted =
+            r#"sources = [{ path = "../static", weight = 2, sample = { bytes = 0.5 } }]"#;
+        let weighted = toml::from_str::<CorpusConfig>(weighted).unwrap();
+        assert_eq!(weighted.sources[0].weight(), 2.0);
+        assert_eq!(weighted.sources[0].sample, Some(Sampling::Bytes(0.5)));
+        assert!(weighted.resolve().is_ok());
+
+        let typo = r#"sources = [{ path = "../static", wieght = 2 }]"#;
+        assert!(toml::from_str::<CorpusConfig>(typo).is_err());
+
+        for invalid in [
+            r#"sources = [{ path = "../static", weight = 0 }]"#,
+            r#"sources = [{ path = "../static", share = 1.5 }]"#,
+            r#"sources = [{ path = "../static", weight = 1, share = 0.5 }]"#,
+            r#"sources = [{ path = "../static", sample = { lines = 0 } }]"#,
+        ] {
+            let config = toml::from_str::<CorpusConfig>(invalid).unwrap();
+            assert!(matches!(
+                config.resolve(),
+                Err(OxeylyzerError::InvalidCorpusSource(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn code() {
+        let config = r#"
+            letters_to_lowercase = "abc"
+
+            [code]
+            indentation = "auto"
+            skip = ["*.min.js"]
+            max_line_repeats = 3
+            fold_identifier_case = true
+        "#;
+        let config = toml::from_str::<CorpusConfig>(config).unwrap();
+        let code = config.code().unwrap();
+        assert_eq!(code.indentation, Indentation::Auto);
+        assert_eq!(code.max_line_repeats, Some(3));
+
+        let cleaner = CorpusCleaner::try_from(config).unwrap();
+        let cleaned = "a\tb⌫c⇑a".chars().clean_corpus(&cleaner).flatten();
+        assert_eq!(cleaned.collect::<String>(), "a\tb⌫c⇑a");
+
+        let merged = toml::from_str::<CorpusConfig>("").unwrap()
+            + toml::from_str::<CorpusConfig>("[code]\nindentation = \"tab\"").unwrap();
+        assert_eq!(merged.code().unwrap().indentation, Indentation::Tab);
+
+        let invalid = toml::from_str::<CorpusConfig>("[code]\nskip = [\"[\"]").unwrap();
+        assert!(matches!(
+            invalid.resolve(),
+            Err(OxeylyzerError::InvalidSkipPattern(_))
+        ));
+        assert!(toml::from_str::<CorpusConfig>("[code]\nindent = \"tab\"").is_err());
+    }
+
+    #[test]
+    fn letters_to_lowercase() {
+        let config1 = r#"letters_to_lowercase = "dofsmie""#;
+        let config2 = r#"letters_to_lowercase = "yeah""#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(
+            config1.letters_to_lowercase,
+            "dofsmie".chars().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            config2.letters_to_lowercase,
+            "yeah".chars().collect::<Vec<_>>()
+        );
+
+        let config = config1 + config2;
+        assert_eq!(
+            config.letters_to_lowercase,
+            concat!("dofsmie", "yeah").chars().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn keep() {
+        let config1 = r#"keep = "dofsmie""#;
+        let config2 = r#"keep = "yeah""#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(config1.keep, "dofsmie".chars().collect::<Vec<_>>());
+        assert_eq!(config2.keep, "yeah".chars().collect::<Vec<_>>());
+
+        let config = config1 + config2;
+        assert_eq!(
+            config.keep,
+            concat!("dofsmie", "yeah").chars().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn multiple() {
+        let config1 = r#"
+            [multiple]
+            list = [
+              ["a", "dofsmie"],
+            ]
+        "#;
+        let config2 = r#"
+            [multiple]
+            uppercase_versions = true
+            list = [
+              ["b", "yeah"],
+            ]
+        "#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(config1.multiple, vec![('a', "dofsmie".to_string()),]);
+        assert_eq!(
+            config2.multiple,
+            vec![('b', "yeah".to_string()), ('B', "yeah".to_string()),]
+        );
+
+        let config = config1 + config2;
+        assert_eq!(
+            config.multiple,
+            vec![
+                ('a', "dofsmie".to_string()),
+                ('b', "yeah".to_string()),
+                ('B', "yeah".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn one_to_one() {
+        let config1 = r#"
+            [one_to_one]
+            from = "yeah"
+            to =   "dofs"
+        "#;
+        let config2 = r#"
+            [one_to_one]
+            from = "nah"
+            to =   "lol"
+        "#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(
+            config1.one_to_one,
+            OneToOne {
+                from: "yeah".chars().collect::<Vec<_>>(),
+                to: "dofs".chars().collect::<Vec<_>>()
+            }
+        );
+        assert_eq!(
+            config2.one_to_one,
+            OneToOne {
+                from: "nah".chars().collect::<Vec<_>>(),
+                to: "lol".chars().collect::<Vec<_>>()
+            }
+        );
+
+        let config = config1 + config2;
+        assert_eq!(
+            config.one_to_one,
+            OneToOne {
+                from: concat!("yeah", "nah").chars().collect::<Vec<_>>(),
+                to: concat!("dofs", "lol").chars().collect::<Vec<_>>()
+            }
+        );
+
+        let uneven = r#"
+            [one_to_one]
+            from = "abc"
+            to =   "de"
+        "#;
+        let uneven = toml::from_str::<CorpusConfig>(uneven).unwrap();
+        assert!(matches!(
+            CorpusCleaner::try_from(uneven),
+            Err(OxeylyzerError::UnevenCorpusMapping("one_to_one", 3, 2))
+        ));
+    }
+
+    #[test]
+    fn dead_keys() {
+        let config1 = r#"
+            letters_to_lowercase = "aeo"
+
+            [dead_keys."´"]
+            uppercase_versions = true
+            from = "áé"
+            to =   "ae"
+        "#;
+        let config2 = r#"
+            [dead_keys."´"]
+            from = "ó"
+            to =   "o"
+
+            [dead_keys."¨"]
+            from = "ë"
+            to =   "e"
+        "#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert_eq!(config1.dead_keys.len(), 1);
+        assert_eq!(config1.dead_keys[&'´'].from, vec!['á', 'é']);
+
+        let config = config1 + config2;
+        assert_eq!(config.dead_keys.len(), 2);
+        assert_eq!(config.dead_keys[&'´'].from, vec!['á', 'é', 'ó']);
+        assert!(config.dead_keys[&'´'].uppercase_versions);
+
+        let cleaner = CorpusCleaner::try_from(config).unwrap();
+        let cleaned = "Éaó ë Ë"
+            .chars()
+            .clean_corpus(&cleaner)
+            .flatten()
+            .collect::<String>();
+
+        // Ë has no dead key mapping for its uppercase version
+        assert_eq!(
+            cleaned,
+            format!("´{SHIFT_CHAR}ea´o{REPLACEMENT_CHAR}¨e{REPLACEMENT_CHAR}{REPLACEMENT_CHAR}")
+        );
+
+        let uneven = r#"
+            [dead_keys."`"]
+            from = "àè"
+            to =   "a"
+        "#;
+        let uneven = toml::from_str::<CorpusConfig>(uneven).unwrap();
+        assert!(matches!(
+            CorpusCleaner::try_from(uneven),
+            Err(OxeylyzerError::UnevenDeadKeyMapping('`', 2, 1))
+        ));
+
+        let base = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
+        let path = base.join("static/corpus_configs/provided/german_deadkey.toml");
+        let cleaner = CorpusCleaner::try_from(CorpusConfig::load(path).unwrap()).unwrap();
+        let cleaned = "Übergröße"
+            .chars()
+            .clean_corpus(&cleaner)
+            .flatten()
+            .collect::<String>();
+
+        assert_eq!(cleaned, format!("*{SHIFT_CHAR}ubergr*o*se"));
+    }
+
+    #[test]
+    fn decompose() {
+        let config1 = r#"
+            letters_to_lowercase = "aceo"
+            decompose = true
+
+            [combining_marks]
+            "\u0301" = "´"
+            "\u0327" = ""
+        "#;
+        let config2 = r#"
+            [combining_marks]
+            "\u0301" = "'"
+            "\u0308" = "¨"
+        "#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+        assert!(config1.decompose && !config2.decompose);
+
+        // marks of the inheriting config take precedence
+        let config = config1 + config2;
+        assert!(config.decompose);
+        assert_eq!(config.combining_marks[&'\u{301}'], "´");
+        assert_eq!(config.combining_marks[&'\u{308}'], "¨");
+
+        let cleaner = CorpusCleaner::try_from(config).unwrap();
+        let cleaned = "çÉöô"
+            .chars()
+            .clean_corpus(&cleaner)
+            .flatten()
+            .collect::<String>();
+
+        assert_eq!(cleaned, format!("c´{SHIFT_CHAR}e¨oo"));
+
+        let base = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
+        let path = base.join("static/corpus_configs/provided/korean.toml");
+        let cleaner = CorpusCleaner::try_from(CorpusConfig::load(path).unwrap()).unwrap();
+        let cleaned = "읽과"
+            .chars()
+            .clean_corpus(&cleaner)
+            .flatten()
+            .collect::<String>();
+
+        assert_eq!(cleaned, "ㅇㅣㄹㄱㄱㅗㅏ");
+    }
+
+    #[test]
+    fn shift_char() {
+        let config1 = r#"shift_key = "a" "#;
+        let config2 = r#""#;
+        let config3 = r#"shift_key = "abc" "#;
+
+        let config1 = toml::from_str::<CorpusConfig>(config1).unwrap();
+        let config2 = toml::from_str::<CorpusConfig>(config2).unwrap();
+
+        assert_eq!(config1.shift_key.key(), Some('a'));
+        assert_eq!(config2.shift_key.key(), Some(SHIFT_CHAR));
+
+        assert!(toml::from_str::<CorpusConfig>(config3).is_err());
+    }
+
+    #[test]
+    fn existing_file_validity() {
+        let base_path = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
+        let corpus_configs = base_path.join("static/corpus_configs");
+
+        std::fs::read_dir(&corpus_configs)
+            .unwrap()
+            .flat_map(|dir| std::fs::read_dir(dir.unwrap().path()).unwrap())
+            .map(|p| p.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .for_each(|path| {
+                let config = CorpusConfig::load(&path).unwrap().resolve().unwrap();
+                let lang = path.file_name().unwrap().to_string_lossy();
+
+                config.keep.into_iter().for_each(|c| {
+                    assert_eq!(
+                        c.to_uppercase().to_string(),
+                        c.to_lowercase().to_string(),
+                        "Corpus config for lang {lang} has keep rule for {c} which has an uppercase"
+                    );
+                });
+
+                let multiple_map = config.multiple.into_iter().collect::<HashMap<_, _>>();
+
+                multiple_map.iter().for_each(|(c, str)| {
+                    let lower = c.to_lowercase().collect::<Vec<char>>();
+                    let upper = c.to_uppercase().collect::<Vec<char>>();
+
+                    if lower != upper && lower.len() == 1 && upper.len() == 1 {
+                        let (lower, upper) = (lower[0], upper[0]);
+
+                        let lower_to = multiple_map.get(&lower);
+                        let upper_to = multiple_map.get(&upper);
+
+                        assert!(
+                            lower_to.is_some(),
+                            concat!(
+                                "multiple mapping for language {} has character '{}' mapped to ",
+                                "\"{}\", but no such mapping exists for the lowercase variant {}",
+                            ),
+                            lang,
+                            upper,
+                            str,
+                            lower
+                        );
+
+                        assert!(
+                            upper_to.is_some(),
+                            concat!(
+                                "multiple mapping for language {} has character '{}' mapped to ",
+                                "\"{}\", but no such mapping exists for the uppercase variant {}.\n",
+                                "Did you mean to enable `uppercase_versions = true`?"
+                            ),
+                            lang,
+                            lower,
+                            str,
+                            upper
+                        );
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,127 @@
+use std::{fmt::Display, path::Path};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{OxeylyzerError, OxeylyzerResultExt, Result, data::Data};
+
+/// How many unmapped characters are shown when a report is printed.
+const SHOWN_UNMAPPED: usize = 20;
+
+/// Report on how a corpus was cleaned, showing which characters weren't mapped and how much
+/// was added by the cleaner itself.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{corpus_report::CorpusReport, data::Data};
+/// let data = Data::from("abcabc");
+/// let report = CorpusReport::new(&data, [('x', 1), ('é', 3)], 0, 0);
+///
+/// assert_eq!(report.replaced, 4);
+/// assert_eq!(report.unmapped.first(), Some((&'é', &3)));
+/// assert_eq!(report.replaced_share(), 0.4);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorpusReport {
+    /// The name of the corpus.
+    pub name: String,
+    /// Characters in the source text that couldn't be mapped, most frequent first.
+    pub unmapped: IndexMap<char, i64>,
+    /// Total amount of characters that were replaced because they couldn't be mapped.
+    pub replaced: i64,
+    /// Amount of shift presses the cleaner inserted.
+    pub shifts: i64,
+    /// Amount of characters that were substituted with the repeat key.
+    pub repeats: i64,
+    /// Total count of characters in the corpus.
+    pub char_total: i64,
+    /// Total count of bigrams in the corpus.
+    pub bigram_total: i64,
+    /// Total count of trigrams in the corpus.
+    pub trigram_total: i64,
+}
+
+impl CorpusReport {
+    /// Creates a report for cleaned corpus data from the characters that couldn't be mapped and
+    /// the amount of shift and repeat key presses in it.
+    pub fn new(
+        data: &Data,
+        unmapped: impl IntoIterator<Item = (char, i64)>,
+        shifts: i64,
+        repeats: i64,
+    ) -> Self {
+        let mut unmapped = unmapped.into_iter().collect::<IndexMap<_, _>>();
+        unmapped.sort_by(|c1, f1, c2, f2| f2.cmp(f1).then_with(|| c1.cmp(c2)));
+
+        Self {
+            name: data.name.clone(),
+            replaced: unmapped.values().sum(),
+            unmapped,
+            shifts,
+            repeats,
+            char_total: data.char_total,
+            bigram_total: data.bigram_total,
+            trigram_total: data.trigram_total,
+        }
+    }
+
+    /// Share of characters in the cleaned text that were replaced, between 0 and 1.
+    pub fn replaced_share(&self) -> f64 {
+        match self.char_total + self.replaced {
+            0 => 0.0,
+            total => self.replaced as f64 / total as f64,
+        }
+    }
+
+    /// Saves the report to `<name>.report.json` in the specified folder, which is usually the
+    /// folder the corpus data itself is saved in.
+    pub fn save<P: AsRef<Path>>(&self, folder: P) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(OxeylyzerError::MissingDataName);
+        }
+
+        std::fs::create_dir_all(&folder).path_context(&folder)?;
+
+        let path = folder.as_ref().join(format!("{}.report.json", self.name));
+
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+        let mut ser = serde_json::ser::Serializer::with_formatter(vec![], formatter);
+        self.serialize(&mut ser)
+            .map_err(|_| OxeylyzerError::CouldNotSerializeData(self.name.clone()))?;
+
+        std::fs::write(&path, ser.into_inner()).path_context(path)
+    }
+}
+
+impl Display for CorpusReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Corpus report for {}:", self.name)?;
+        writeln!(
+            f,
+            "  chars: {}, bigrams: {}, trigrams: {}",
+            self.char_total, self.bigram_total, self.trigram_total
+        )?;
+        writeln!(
+            f,
+            "  replaced: {} ({:.3}%)",
+            self.replaced,
+            self.replaced_share() * 100.0
+        )?;
+        writeln!(f, "  shift insertions: {}", self.shifts)?;
+        write!(f, "  repeat key substitutions: {}", self.repeats)?;
+
+        if !self.unmapped.is_empty() {
+            write!(f, "\n  most frequent unmapped characters:")?;
+
+            for (c, count) in self.unmapped.iter().take(SHOWN_UNMAPPED) {
+                write!(f, "\n    {:?} (U+{:04X}): {count}", c, *c as u32)?;
+            }
+
+            if self.unmapped.len() > SHOWN_UNMAPPED {
+                write!(f, "\n    and {} more", self.unmapped.len() - SHOWN_UNMAPPED)?;
+            }
+        }
+
+        Ok(())
+    }
+}
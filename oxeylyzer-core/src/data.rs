@@ -12,17 +12,26 @@ use crate::{corpus_cleaner::CorpusCleanerIterator, *};
 mod exclude_wasm {
     pub use std::{
         fs::{File, OpenOptions},
-        io::Write,
+        io::{BufRead, BufReader, Read, Seek, Write},
         path::Path,
     };
 
+    pub use flate2::bufread::MultiGzDecoder;
+
     pub use file_chunker::FileChunker;
     pub use rayon::prelude::*;
     pub use serde_json::ser::PrettyFormatter;
 
-    pub use crate::corpus_cleaner::{CleanCorpus, CorpusCleaner};
+    pub use crate::code_corpus::{CodeOptions, RepeatedLines};
+    pub use crate::corpus_cleaner::{CleanCorpus, CleanerState, CorpusCleaner};
+    pub use crate::corpus_config::{CorpusSource, Sampling};
+    pub use crate::corpus_report::CorpusReport;
 
     pub const CHUNK_SIZE: usize = 1024 * 1024;
+    pub const SAMPLE_BLOCK_SIZE: usize = 64 * 1024;
+
+    pub const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+    pub const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -57,6 +66,19 @@ serde_conv!(
     }
 );
 
+serde_conv!(
+    QuadgramAsStr,
+    [char; 4],
+    |quadgram: &[char; 4]| String::from_iter(quadgram),
+    |value: String| -> Result<_> {
+        value
+            .chars()
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|v: Vec<_>| OxeylyzerError::InvalidQuadgramLength(v.len()))
+    }
+);
+
 // type BigramAsStr = DisplayFromStr;
 // type TrigramAsStr = DisplayFromStr;
 
@@ -70,7 +92,7 @@ serde_conv!(
 /// assert_eq!(data.char_total, 0);
 /// ```
 #[serde_as]
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(into = "SaveData")]
 pub struct Data {
     /// The name of the corpus or language.
@@ -93,6 +115,13 @@ pub struct Data {
     /// Frequencies of trigrams (three consecutive characters).
     #[serde_as(as = "HashMap<TrigramAsStr, _>")]
     pub trigrams: HashMap<[char; 3], f64>,
+    /// Frequencies of quadgrams (four consecutive characters). Only collected on request.
+    #[serde_as(as = "HashMap<QuadgramAsStr, _>")]
+    #[serde(default)]
+    pub quadgrams: HashMap<[char; 4], f64>,
+    /// Frequencies of words, which are separated by spaces. Only collected on request.
+    #[serde(default)]
+    pub words: HashMap<String, f64>,
 
     /// Total count of characters in the corpus.
     pub char_total: i64,
@@ -106,6 +135,32 @@ pub struct Data {
     pub skipgram3_total: i64,
     /// Total count of trigrams in the corpus.
     pub trigram_total: i64,
+    /// Total count of quadgrams in the corpus, or 0 if they weren't collected.
+    #[serde(default)]
+    pub quadgram_total: i64,
+    /// Total count of words in the corpus, or 0 if they weren't collected.
+    #[serde(default)]
+    pub word_total: i64,
+}
+
+/// Optional data that can be collected when creating [`Data`] from text.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::data::CollectionOptions;
+/// let options = CollectionOptions {
+///     words: Some(5000),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(options.quadgrams, None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectionOptions {
+    /// Collects quadgrams, only keeping this many of the most frequent ones.
+    pub quadgrams: Option<usize>,
+    /// Collects words, only keeping this many of the most frequent ones.
+    pub words: Option<usize>,
 }
 
 impl Data {
@@ -178,11 +233,84 @@ impl Data {
     pub fn get_trigram(&self, trigram: [char; 3]) -> Option<&f64> {
         self.trigrams.get(&trigram)
     }
+
+    /// Returns the frequency of a specific quadgram if it exists.
+    pub fn get_quadgram(&self, quadgram: [char; 4]) -> Option<&f64> {
+        self.quadgrams.get(&quadgram)
+    }
+
+    /// Only keeps the `n` most frequent quadgrams. Frequencies stay relative to all quadgrams
+    /// in the corpus.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::data::Data;
+    ///
+    /// let mut data = Data::new();
+    /// data.quadgrams.insert(['t', 'h', 'e', ' '], 1.2);
+    /// data.quadgrams.insert(['t', 'h', 'a', 't'], 0.4);
+    /// data.retain_top_quadgrams(1);
+    ///
+    /// assert_eq!(data.quadgrams.len(), 1);
+    /// assert_eq!(data.get_quadgram(['t', 'h', 'e', ' ']), Some(&1.2));
+    /// ```
+    pub fn retain_top_quadgrams(&mut self, n: usize) {
+        if self.quadgrams.len() <= n {
+            return;
+        }
+
+        self.quadgrams = std::mem::take(&mut self.quadgrams)
+            .into_iter()
+            .sorted_by(|(q1, f1), (q2, f2)| f2.total_cmp(f1).then_with(|| q1.cmp(q2)))
+            .take(n)
+            .collect();
+    }
+
+    /// Returns the frequency of a specific word if it exists.
+    pub fn get_word(&self, word: &str) -> Option<&f64> {
+        self.words.get(word)
+    }
+
+    /// Only keeps the `n` most frequent words. Frequencies stay relative to all words in the
+    /// corpus.
+    ///
+    /// # Examples:
+    /// ```
+    /// use oxeylyzer_core::data::Data;
+    ///
+    /// let mut data = Data::new();
+    /// data.words.insert("the".to_string(), 5.0);
+    /// data.words.insert("of".to_string(), 3.0);
+    /// data.retain_top_words(1);
+    ///
+    /// assert_eq!(data.words.len(), 1);
+    /// assert_eq!(data.get_word("the"), Some(&5.0));
+    /// ```
+    pub fn retain_top_words(&mut self, n: usize) {
+        if self.words.len() <= n {
+            return;
+        }
+
+        self.words = std::mem::take(&mut self.words)
+            .into_iter()
+            .sorted_by(|(w1, f1), (w2, f2)| f2.total_cmp(f1).then_with(|| w1.cmp(w2)))
+            .take(n)
+            .collect();
+    }
 }
 
 impl Data {
-    /// Loads layout data from a JSON file.
+    /// Loads layout data from a JSON file. If a binary file with the same name is next to it
+    /// and was written for the current version of the JSON file, that is loaded instead, which
+    /// is a lot faster. A `.bin` path is always loaded as binary.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if path.as_ref().extension().is_some_and(|ext| ext == "bin") {
+            return Self::load_binary(path);
+        }
+        if let Some(data) = Self::load_binary_for(path.as_ref()) {
+            return Ok(data);
+        }
+
         let content = std::fs::read_to_string(&path).path_context(&path)?;
         let data = serde_json::from_str::<Self>(&content).path_context(path)?;
         Ok(data)
@@ -194,26 +322,182 @@ impl Data {
         name: &str,
         cleaner: &CorpusCleaner,
     ) -> Result<Self> {
-        let paths = paths
+        Self::from_paths_with_options(paths, name, cleaner, CollectionOptions::default())
+    }
+
+    /// Generates frequency data from a set of paths (files or directories), also collecting
+    /// the optional data specified in `options`.
+    pub fn from_paths_with_options<P: AsRef<Path>>(
+        paths: &[P],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<Self> {
+        Self::from_paths_with_report(paths, name, cleaner, options).map(|(data, _)| data)
+    }
+
+    /// Generates frequency data from a set of paths (files or directories) like
+    /// [`from_paths_with_options`](Self::from_paths_with_options), together with a report on
+    /// what the cleaner replaced or added.
+    pub fn from_paths_with_report<P: AsRef<Path>>(
+        paths: &[P],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<(Self, CorpusReport)> {
+        let sources = paths
             .iter()
-            .map(|p| p.as_ref().to_path_buf())
+            .map(|p| CorpusSource::new(p.as_ref()))
             .collect::<Vec<_>>();
 
-        let result = paths
-            .into_par_iter()
-            .map(|path| {
+        Self::from_sources_with_report(&sources, name, cleaner, options)
+    }
+
+    /// Generates frequency data from the sources of a corpus config together with a report, like
+    /// [`from_paths_with_report`](Self::from_paths_with_report). Every source is sampled as it
+    /// specifies, and if any source has a weight or share, the counts of every source are
+    /// normalized to match before they are merged.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{corpus_cleaner::CorpusCleaner, corpus_config::CorpusSource};
+    /// # use oxeylyzer_core::data::{CollectionOptions, Data};
+    /// # let dir = std::env::temp_dir().join(format!("oxeylyzer-sources-doc-{}", std::process::id()));
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// std::fs::write(dir.join("large.txt"), "a".repeat(900)).unwrap();
+    /// std::fs::write(dir.join("small.txt"), "b".repeat(100)).unwrap();
+    ///
+    /// let cleaner = CorpusCleaner::builder().with_chars("ab".chars()).build();
+    /// let sources = [
+    ///     CorpusSource::new(dir.join("large.txt")),
+    ///     CorpusSource { share: Some(0.5), ..CorpusSource::new(dir.join("small.txt")) },
+    /// ];
+    /// let (data, _) =
+    ///     Data::from_sources_with_report(&sources, "mixed", &cleaner, CollectionOptions::default())
+    ///         .unwrap();
+    ///
+    /// assert_eq!(data.get_char('a'), data.get_char('b'));
+    /// # std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn from_sources_with_report(
+        sources: &[CorpusSource],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<(Self, CorpusReport)> {
+        let intermediate = Self::from_sources_inner(sources, name, cleaner, options)?;
+
+        Ok(Self::with_report(intermediate, name, cleaner, options))
+    }
+
+    /// Generates frequency data from sources of source code together with a report, like
+    /// [`from_sources_with_report`](Self::from_sources_with_report). Directories are searched
+    /// recursively and every file is processed as specified in `code`, in alphabetical order so
+    /// repeated lines are always removed from the same files.
+    ///
+    /// Unlike prose, every file is read into memory as a whole, because the width of its
+    /// indentation is only known after seeing all of its lines. A batch of files is read at a
+    /// time, so very large files should be left out with
+    /// [`max_file_size`](CodeOptions::max_file_size).
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{code_corpus::{CodeOptions, Indentation}, corpus_config::CorpusSource};
+    /// # use oxeylyzer_core::{corpus_cleaner::CorpusCleaner, data::{CollectionOptions, Data}};
+    /// # let dir = std::env::temp_dir().join(format!("oxeylyzer-code-doc-{}", std::process::id()));
+    /// # std::fs::create_dir_all(dir.join("src")).unwrap();
+    /// std::fs::write(dir.join("src/lib.py"), "if a:\n        b\n").unwrap();
+    ///
+    /// let cleaner = CorpusCleaner::builder().with_chars("abfi:\t".chars()).build();
+    /// let code = CodeOptions { indentation: Indentation::Tab, ..Default::default() };
+    /// let (data, _) = Data::from_code_with_report(
+    ///     &[CorpusSource::new(&dir)], "code", &cleaner, &code, CollectionOptions::default()
+    /// ).unwrap();
+    ///
+    /// assert_eq!(data.char_total, 6);
+    /// assert!(data.get_bigram([':', '\t']).is_none());
+    /// assert!(data.get_bigram(['\t', 'b']).is_some());
+    /// # std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn from_code_with_report(
+        sources: &[CorpusSource],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        code: &CodeOptions,
+        options: CollectionOptions,
+    ) -> Result<(Self, CorpusReport)> {
+        sources.iter().try_for_each(CorpusSource::validate)?;
+
+        let mut repeated = RepeatedLines::new(code.max_line_repeats);
+        let result = sources
+            .iter()
+            .map(|source| {
+                IntermediateData::from_code(source, name, cleaner, code, options, &mut repeated)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let intermediate = merge_sources(sources, result)?;
+
+        Ok(Self::with_report(intermediate, name, cleaner, options))
+    }
+
+    /// Turns counts into frequency data, trimming quadgrams and words to what `options` asks
+    /// for, and reports on how the corpus was cleaned.
+    fn with_report(
+        mut intermediate: IntermediateData,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> (Self, CorpusReport) {
+        let count = |c: char| intermediate.chars.get(&c).copied().unwrap_or_default();
+        let shifts = cleaner.shift_key().map(count).unwrap_or_default();
+        let repeats = count(REPEAT_KEY);
+        let unmapped = std::mem::take(&mut intermediate.unmapped);
+
+        intermediate.name = name.into();
+        let mut data = Self::from(intermediate);
+        let report = CorpusReport::new(&data, unmapped, shifts, repeats);
+
+        if let Some(n) = options.quadgrams {
+            data.retain_top_quadgrams(n);
+        }
+        if let Some(n) = options.words {
+            data.retain_top_words(n);
+        }
+
+        (data, report)
+    }
+
+    fn from_sources_inner(
+        sources: &[CorpusSource],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<IntermediateData> {
+        sources.iter().try_for_each(CorpusSource::validate)?;
+
+        let result = sources
+            .par_iter()
+            .map(|source| {
+                let path = &source.path;
+                let sample = source.sample;
+
                 if path.is_file() {
-                    let f = std::fs::File::open(&path).path_context(path)?;
-                    IntermediateData::from_file(f, name, cleaner)
+                    IntermediateData::from_path(path, name, cleaner, options, sample)
                 } else if path.is_dir() {
-                    let mut new = std::fs::read_dir(&path)
+                    let mut new = std::fs::read_dir(path)
                         .path_context(path)?
                         .flatten()
                         .par_bridge()
                         .filter(|entry| entry.path().is_file())
                         .flat_map(|entry| {
-                            let f = std::fs::File::open(entry.path()).path_context(entry.path())?;
-                            IntermediateData::from_file(f, name, cleaner)
+                            IntermediateData::from_path(
+                                &entry.path(),
+                                name,
+                                cleaner,
+                                options,
+                                sample,
+                            )
                         })
                         .reduce(IntermediateData::default, |a, b| a + b);
 
@@ -221,26 +505,139 @@ impl Data {
 
                     Ok(new)
                 } else if path.exists() {
-                    Err(OxeylyzerError::NotAFile(path))
+                    Err(OxeylyzerError::NotAFile(path.clone()))
                 } else {
-                    Err(OxeylyzerError::PathDoesNotExist(path))
+                    Err(OxeylyzerError::PathDoesNotExist(path.clone()))
                 }
             })
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(result
-            .into_iter()
-            .reduce(|a, b| a + b)
-            .unwrap_or_default()
-            .into())
+        merge_sources(sources, result)
     }
 
     /// Generates frequency data from a single open file.
     pub fn from_file(file: File, name: &str, cleaner: &CorpusCleaner) -> Result<Data> {
-        IntermediateData::from_file(file, name, cleaner).map(Into::into)
+        IntermediateData::from_file(file, name, cleaner, CollectionOptions::default())
+            .map(Into::into)
+    }
+
+    /// Generates frequency data from any reader, like stdin or a network stream. Gzip and zstd
+    /// compressed input is decompressed transparently. The input is read in chunks of about a
+    /// megabyte, so memory usage stays bounded no matter how large the corpus is, while the
+    /// chunks are still counted in parallel.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{data::{CollectionOptions, Data}, corpus_cleaner::CorpusCleaner};
+    /// let cleaner = CorpusCleaner::builder()
+    ///     .with_chars("abc".chars())
+    ///     .build();
+    ///
+    /// let data = Data::from_reader(
+    ///     "abc cab".as_bytes(),
+    ///     "stream",
+    ///     &cleaner,
+    ///     CollectionOptions::default(),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(data.name, "stream");
+    /// assert_eq!(data.char_total, 6);
+    /// assert_eq!(data.get_bigram(['a', 'b']), Some(&50.0));
+    /// ```
+    pub fn from_reader<R: Read>(
+        reader: R,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<Self> {
+        Self::from_reader_with_report(reader, name, cleaner, options).map(|(data, _)| data)
+    }
+
+    /// Generates frequency data from any reader like [`from_reader`](Self::from_reader),
+    /// together with a report on what the cleaner replaced or added.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{data::{CollectionOptions, Data}, corpus_cleaner::CorpusCleaner};
+    /// let cleaner = CorpusCleaner::builder()
+    ///     .with_chars("abc".chars())
+    ///     .build();
+    ///
+    /// let (data, report) = Data::from_reader_with_report(
+    ///     "Abc déjà".as_bytes(),
+    ///     "report",
+    ///     &cleaner,
+    ///     CollectionOptions::default(),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(report.name, "report");
+    /// assert_eq!(report.char_total, data.char_total);
+    /// assert_eq!(report.shifts, 1);
+    /// assert_eq!(report.replaced, 5);
+    /// assert_eq!(report.unmapped.get(&'é'), Some(&1));
+    /// ```
+    pub fn from_reader_with_report<R: Read>(
+        reader: R,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<(Self, CorpusReport)> {
+        let intermediate = IntermediateData::from_reader(reader, name, cleaner, options)?;
+
+        Ok(Self::with_report(intermediate, name, cleaner, options))
+    }
+
+    /// Generates frequency data from characters that were already cleaned, also collecting the
+    /// optional data specified in `options`.
+    pub(crate) fn from_cleaned<T: IntoIterator<Item = char>>(
+        iter: T,
+        name: &str,
+        options: CollectionOptions,
+    ) -> Self {
+        let mut intermediate = IntermediateData::from_chars(iter, options);
+        intermediate.name = name.into();
+
+        let mut data = Self::from(intermediate);
+
+        if let Some(n) = options.quadgrams {
+            data.retain_top_quadgrams(n);
+        }
+        if let Some(n) = options.words {
+            data.retain_top_words(n);
+        }
+
+        data
+    }
+
+    /// Generates frequency data from the cleaned characters of every source, which are weighted
+    /// like the sources of a text corpus, along with the characters that couldn't be mapped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn from_cleaned_sources_with_report(
+        sources: &[CorpusSource],
+        cleaned: impl IntoIterator<Item = (Vec<char>, Vec<(char, i64)>)>,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<(Self, CorpusReport)> {
+        let counts = cleaned
+            .into_iter()
+            .map(|(chars, unmapped)| {
+                let mut intermediate = IntermediateData::from_chars(chars, options);
+                for (c, freq) in unmapped {
+                    *intermediate.unmapped.entry(c).or_insert(0) += freq;
+                }
+                intermediate
+            })
+            .collect();
+        let intermediate = merge_sources(sources, counts)?;
+
+        Ok(Self::with_report(intermediate, name, cleaner, options))
     }
 
-    /// Saves the frequency data to a JSON file in the specified folder.
+    /// Saves the frequency data to a JSON file in the specified folder, with a binary file next
+    /// to it that [`Data::load`] prefers as long as the JSON file isn't changed.
     pub fn save<P: AsRef<Path>>(&self, folder: P) -> Result<()> {
         if self.name.is_empty() {
             return Err(OxeylyzerError::MissingDataName);
@@ -263,9 +660,10 @@ impl Data {
             .map_err(|_| OxeylyzerError::CouldNotSerializeData(self.name.clone()))?;
 
         f.write_all(ser.into_inner().as_slice())
-            .path_context(path)?;
+            .path_context(&path)?;
+        drop(f);
 
-        Ok(())
+        self.save_binary(path.with_extension("bin"), &path)
     }
 }
 
@@ -289,6 +687,10 @@ struct SaveData {
     pub skipgram2_total: i64,
     pub skipgram3_total: i64,
     pub trigram_total: i64,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub quadgram_total: i64,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub word_total: i64,
 
     pub chars: IndexMap<char, f64>,
     pub bigrams: IndexMap<String, f64>,
@@ -296,6 +698,14 @@ struct SaveData {
     pub skipgrams2: IndexMap<String, f64>,
     pub skipgrams3: IndexMap<String, f64>,
     pub trigrams: IndexMap<String, f64>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub quadgrams: IndexMap<String, f64>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub words: IndexMap<String, f64>,
+}
+
+fn is_zero(n: &i64) -> bool {
+    *n == 0
 }
 
 impl From<Data> for SaveData {
@@ -341,6 +751,19 @@ impl From<Data> for SaveData {
             .map(|(b, f)| (String::from_iter(b), f))
             .collect();
 
+        let quadgrams = data
+            .quadgrams
+            .into_iter()
+            .sorted_by(|(q1, f1), (q2, f2)| f2.total_cmp(f1).then_with(|| q1.cmp(q2)))
+            .map(|(q, f)| (String::from_iter(q), f))
+            .collect();
+
+        let words = data
+            .words
+            .into_iter()
+            .sorted_by(|(w1, f1), (w2, f2)| f2.total_cmp(f1).then_with(|| w1.cmp(w2)))
+            .collect();
+
         Self {
             name: data.name,
 
@@ -350,6 +773,8 @@ impl From<Data> for SaveData {
             skipgram2_total: data.skipgram2_total,
             skipgram3_total: data.skipgram3_total,
             trigram_total: data.trigram_total,
+            quadgram_total: data.quadgram_total,
+            word_total: data.word_total,
 
             chars,
             bigrams,
@@ -357,11 +782,13 @@ impl From<Data> for SaveData {
             skipgrams2,
             skipgrams3,
             trigrams,
+            quadgrams,
+            words,
         }
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 struct IntermediateData {
     pub name: String,
     pub chars: HashMap<char, i64>,
@@ -370,6 +797,9 @@ struct IntermediateData {
     pub skipgrams2: HashMap<[char; 2], i64>,
     pub skipgrams3: HashMap<[char; 2], i64>,
     pub trigrams: HashMap<[char; 3], i64>,
+    pub quadgrams: HashMap<[char; 4], i64>,
+    pub words: HashMap<String, i64>,
+    pub unmapped: HashMap<char, i64>,
 }
 
 impl IntermediateData {
@@ -420,30 +850,178 @@ impl IntermediateData {
             .and_modify(|f| *f += 1)
             .or_insert(1);
     }
+
+    fn add_quadgram(&mut self, c1: char, c2: char, c3: char, c4: char) {
+        self.quadgrams
+            .entry([c1, c2, c3, c4])
+            .and_modify(|f| *f += 1)
+            .or_insert(1);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl IntermediateData {
-    fn from_file(file: File, name: &str, cleaner: &CorpusCleaner) -> Result<Self> {
+    fn from_file(
+        file: File,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<Self> {
         let chunker = FileChunker::new(&file).map_err(|_| OxeylyzerError::ChunkerInitError)?;
 
         let file_len = file.metadata().str_context(name)?.len() as usize;
-        let chunk_count = (file_len / CHUNK_SIZE).clamp(1, num_cpus::get() - 1);
+        let chunk_count = (file_len / CHUNK_SIZE).max(1);
 
         let chunks = chunker
             .chunks(chunk_count, Some(' '))
-            .map_err(|_| OxeylyzerError::ChunkerChunkError)?;
+            .map_err(|_| OxeylyzerError::ChunkerChunkError)?
+            .into_iter()
+            .flat_map(std::str::from_utf8)
+            .collect::<Vec<_>>();
 
+        let mut context = ChunkContext::default();
         let mut intermediate = chunks
-            .into_par_iter()
-            .flat_map(|chunk| std::str::from_utf8(chunk))
-            .map(|s| {
-                s.chars()
-                    .clean_corpus(cleaner)
-                    .flatten()
-                    .collect::<IntermediateData>()
+            .chunks(num_cpus::get().max(1))
+            .map(|batch| Self::from_chunks(batch, cleaner, options, &mut context))
+            .fold(IntermediateData::default(), |a, b| a + b)
+            .finish(context, options);
+
+        intermediate.name = name.into();
+
+        Ok(intermediate)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IntermediateData {
+    /// Reads a single file, streaming it if it is compressed or sampled and chunking it in place
+    /// if not.
+    fn from_path(
+        path: &Path,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+        sample: Option<Sampling>,
+    ) -> Result<Self> {
+        let mut file = File::open(path).path_context(path)?;
+
+        let mut magic = [0u8; 4];
+        let read = file.read(&mut magic).path_context(path)?;
+        let magic = &magic[..read];
+        file.rewind().path_context(path)?;
+
+        if let Some(sampling) = sample {
+            let reader = Sampled::new(decompress(file).path_context(path)?, sampling);
+            Self::from_stream(reader, name, cleaner, options)
+        } else if magic.starts_with(GZIP_MAGIC) || magic.starts_with(ZSTD_MAGIC) {
+            Self::from_reader(file, name, cleaner, options)
+        } else {
+            Self::from_file(file, name, cleaner, options)
+        }
+    }
+
+    fn from_reader<R: Read>(
+        reader: R,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<Self> {
+        let reader = decompress(reader).str_context(name)?;
+        Self::from_stream(reader, name, cleaner, options)
+    }
+
+    /// Reads text that is already decompressed in chunks, see [`from_chunks`](Self::from_chunks).
+    fn from_stream<R: Read>(
+        mut reader: R,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+    ) -> Result<Self> {
+        let batch_size = num_cpus::get().max(1);
+
+        let mut intermediate = IntermediateData::default();
+        let mut context = ChunkContext::default();
+        let mut leftover = Vec::new();
+        let mut eof = false;
+
+        while !eof {
+            let mut chunks = Vec::with_capacity(batch_size);
+
+            while chunks.len() < batch_size && !eof {
+                let mut chunk = std::mem::take(&mut leftover);
+                let read = reader
+                    .by_ref()
+                    .take(CHUNK_SIZE as u64)
+                    .read_to_end(&mut chunk)
+                    .str_context(name)?;
+
+                // `take` only stops short of a full chunk once the input is exhausted
+                if read < CHUNK_SIZE {
+                    eof = true;
+                } else {
+                    leftover = chunk.split_off(chunk_boundary(&chunk));
+                }
+
+                if !chunk.is_empty() {
+                    chunks.push(chunk);
+                }
+            }
+
+            let chunks = chunks
+                .iter()
+                .map(|chunk| String::from_utf8_lossy(chunk))
+                .collect::<Vec<_>>();
+
+            intermediate =
+                intermediate + Self::from_chunks(&chunks, cleaner, options, &mut context);
+        }
+
+        let mut intermediate = intermediate.finish(context, options);
+        intermediate.name = name.into();
+
+        Ok(intermediate)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IntermediateData {
+    /// Reads the files of a source of source code in batches. Every batch is read in parallel,
+    /// has its repeated lines removed in order and is then processed and counted in parallel.
+    fn from_code(
+        source: &CorpusSource,
+        name: &str,
+        cleaner: &CorpusCleaner,
+        code: &CodeOptions,
+        options: CollectionOptions,
+        repeated: &mut RepeatedLines,
+    ) -> Result<Self> {
+        let files = code.source_files(&source.path)?;
+        let batch_size = num_cpus::get().max(1) * 4;
+
+        let mut intermediate = files
+            .chunks(batch_size)
+            .map(|batch| {
+                let texts = batch
+                    .par_iter()
+                    .map(|path| read_code(path, source.sample))
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(texts
+                    .into_iter()
+                    .map(|text| repeated.remove(text))
+                    .collect::<Vec<_>>()
+                    .into_par_iter()
+                    .map(|text| {
+                        let text = code.process(&text);
+                        Self::from_stream(text.as_bytes(), name, cleaner, options)
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .fold(IntermediateData::default(), |a, b| a + b))
             })
-            .reduce(IntermediateData::default, |a, b| a + b);
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .fold(IntermediateData::default(), |a, b| a + b);
 
         intermediate.name = name.into();
 
@@ -451,6 +1029,362 @@ impl IntermediateData {
     }
 }
 
+/// Reads a source code file as text, decompressing and sampling it if needed.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_code(path: &Path, sample: Option<Sampling>) -> Result<String> {
+    let file = File::open(path).path_context(path)?;
+    let mut reader = decompress(file).path_context(path)?;
+
+    let mut bytes = Vec::new();
+    match sample {
+        Some(sampling) => Sampled::new(reader, sampling).read_to_end(&mut bytes),
+        None => reader.read_to_end(&mut bytes),
+    }
+    .path_context(path)?;
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Context that is carried over from one chunk of a corpus to the next, so chunks can be cleaned
+/// and counted in parallel while giving exactly the same result as a single sequential pass.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+struct ChunkContext {
+    /// State of the corpus cleaner at the end of the previous chunk.
+    state: CleanerState,
+    /// Up to four of the last cleaned characters, which start n-grams that end in the next chunk.
+    tail: Vec<char>,
+    /// The word that was being typed when the previous chunk ended.
+    word: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ChunkContext {
+    /// Moves the context past a cleaned chunk that ended in cleaner state `state`.
+    fn advance(&mut self, cleaned: &[char], state: CleanerState, options: CollectionOptions) {
+        self.state = state;
+
+        self.tail
+            .extend(&cleaned[cleaned.len().saturating_sub(4)..]);
+        self.tail.drain(..self.tail.len().saturating_sub(4));
+
+        if options.words.is_some() {
+            let ends_word = |&c: &char| c == SPACE_CHAR || c.is_whitespace();
+
+            let start = match cleaned.iter().rposition(ends_word) {
+                Some(i) => {
+                    self.word.clear();
+                    i + 1
+                }
+                None => 0,
+            };
+
+            self.word
+                .extend(cleaned[start..].iter().filter(|&&c| c != SHIFT_CHAR));
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IntermediateData {
+    /// Cleans and counts a batch of consecutive chunks in parallel, continuing from `context` and
+    /// leaving it at the end of the batch.
+    ///
+    /// Every chunk is first cleaned as if it started the corpus. Then, one after another, the
+    /// start of each chunk is cleaned again from the state the previous chunk actually ended in,
+    /// until both cleaners are in the same state, which usually only takes a character or two.
+    /// Finally the chunks are counted, each starting with the tail and unfinished word of the
+    /// chunk before it.
+    fn from_chunks<S: AsRef<str> + Sync>(
+        chunks: &[S],
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+        context: &mut ChunkContext,
+    ) -> Self {
+        let cleaned = chunks
+            .par_iter()
+            .map(|chunk| {
+                let mut iter = chunk.as_ref().chars().clean_corpus(cleaner);
+                let cleaned = iter.by_ref().flatten().collect::<Vec<_>>();
+                (
+                    cleaned,
+                    iter.state(),
+                    iter.unmapped().collect::<HashMap<_, _>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut contexts = Vec::with_capacity(chunks.len());
+        let mut unmapped = HashMap::default();
+
+        let cleaned = chunks
+            .iter()
+            .zip(cleaned)
+            .map(|(chunk, (cleaned, state, chunk_unmapped))| {
+                for (c, freq) in chunk_unmapped {
+                    *unmapped.entry(c).or_insert(0) += freq;
+                }
+
+                let (cleaned, state) = match context.state == CleanerState::default() {
+                    true => (cleaned, state),
+                    false => reclean(chunk.as_ref(), cleaner, context.state, cleaned, state),
+                };
+
+                contexts.push(context.clone());
+                context.advance(&cleaned, state, options);
+
+                cleaned
+            })
+            .collect::<Vec<_>>();
+
+        let mut intermediate = cleaned
+            .into_par_iter()
+            .zip(contexts)
+            .map(|(cleaned, context)| {
+                Self::from_chunk(&context.tail, cleaned, context.word, options, false)
+            })
+            .reduce(IntermediateData::default, |a, b| a + b);
+
+        intermediate.unmapped = unmapped;
+        intermediate
+    }
+
+    /// Counts the word that was still being typed when the corpus ended.
+    fn finish(mut self, mut context: ChunkContext, options: CollectionOptions) -> Self {
+        if options.words.is_some() && !context.word.contains(REPLACEMENT_CHAR) {
+            end_word(&mut self.words, &mut context.word);
+        }
+
+        self
+    }
+}
+
+/// Cleans the start of a chunk again from the state the cleaner was actually in, until it
+/// reaches the same state as when the chunk was cleaned from the default state. Everything
+/// cleaned from that point on is the same, so the rest of `cleaned` is reused.
+#[cfg(not(target_arch = "wasm32"))]
+fn reclean(
+    chunk: &str,
+    cleaner: &CorpusCleaner,
+    state: CleanerState,
+    cleaned: Vec<char>,
+    end_state: CleanerState,
+) -> (Vec<char>, CleanerState) {
+    let mut actual = chunk.chars().clean_corpus_from(cleaner, state);
+    let mut assumed = chunk.chars().clean_corpus(cleaner);
+
+    let mut prefix = Vec::new();
+    let mut skip = 0;
+
+    while actual.state() != assumed.state() {
+        match (actual.next(), assumed.next()) {
+            (Some(a), Some(b)) => {
+                prefix.extend(a);
+                skip += b.len();
+            }
+            _ => return (prefix, actual.state()),
+        }
+    }
+
+    prefix.extend_from_slice(&cleaned[skip..]);
+
+    (prefix, end_state)
+}
+
+/// Wraps a reader in a decoder if it starts with a gzip or zstd header.
+#[cfg(not(target_arch = "wasm32"))]
+fn decompress<'a, R: Read + 'a>(reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE, reader);
+    let magic = reader.fill_buf()?;
+
+    if magic.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        Ok(Box::new(zstd::Decoder::with_buffer(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Reader that only passes on the part of another reader that is kept by a [`Sampling`].
+#[cfg(not(target_arch = "wasm32"))]
+struct Sampled<R> {
+    reader: BufReader<R>,
+    sampling: Sampling,
+    /// Amount of lines or blocks that were read so far.
+    read: u64,
+    /// Whether the last block was cut short, so the next one starts halfway through a word.
+    truncated: bool,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<R: Read> Sampled<R> {
+    fn new(reader: R, sampling: Sampling) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            sampling,
+            read: 0,
+            truncated: false,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Reads the next line or block into the buffer, leaving it empty if it isn't sampled.
+    /// Returns `false` once the reader is exhausted.
+    fn fill(&mut self) -> std::io::Result<bool> {
+        self.buf.clear();
+        self.pos = 0;
+
+        let read = match self.sampling {
+            Sampling::Lines(rate) => {
+                let read = self.reader.read_until(b'\n', &mut self.buf)?;
+                if !is_sampled(self.read, rate) {
+                    self.buf.clear();
+                }
+                read
+            }
+            Sampling::Bytes(rate) => {
+                let read = self
+                    .reader
+                    .by_ref()
+                    .take(SAMPLE_BLOCK_SIZE as u64)
+                    .read_to_end(&mut self.buf)?;
+                let keep = (read as f64 * rate).ceil() as usize;
+                let truncated = keep < read;
+                if truncated {
+                    self.buf.truncate(chunk_boundary(&self.buf[..keep]));
+                }
+                // a block after one that was cut short starts halfway through a word, or even
+                // a character when the text has no spaces
+                if std::mem::replace(&mut self.truncated, truncated) {
+                    let start = match self.buf.iter().position(|&b| b == b' ') {
+                        Some(i) => i + 1,
+                        None => self
+                            .buf
+                            .iter()
+                            .position(|&b| b & 0xc0 != 0x80)
+                            .unwrap_or(self.buf.len()),
+                    };
+                    self.buf.drain(..start);
+                }
+                read
+            }
+        };
+        self.read += 1;
+
+        Ok(read > 0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<R: Read> Read for Sampled<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.buf.len() {
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+/// Whether the line or block at `index` is kept when sampling at `rate`, which spreads the kept
+/// ones evenly.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_sampled(index: u64, rate: f64) -> bool {
+    ((index + 1) as f64 * rate).floor() > (index as f64 * rate).floor()
+}
+
+/// Merges the counts of every source, normalizing them first if any source is weighted.
+#[cfg(not(target_arch = "wasm32"))]
+fn merge_sources(
+    sources: &[CorpusSource],
+    counts: Vec<IntermediateData>,
+) -> Result<IntermediateData> {
+    let scales = source_scales(sources, &counts)?;
+
+    Ok(counts
+        .into_iter()
+        .zip(scales)
+        .map(|(data, scale)| data.scale(scale))
+        .reduce(|a, b| a + b)
+        .unwrap_or_default())
+}
+
+/// Factors to scale the counts of every source by before merging them. Without weights or shares
+/// every source is kept as it is. Otherwise every source makes up its share of the characters of
+/// the corpus, and the rest is split between the other sources by weight. Sources are only ever
+/// scaled up, so no rare n-grams are rounded away.
+#[cfg(not(target_arch = "wasm32"))]
+fn source_scales(sources: &[CorpusSource], counts: &[IntermediateData]) -> Result<Vec<f64>> {
+    if !sources.iter().any(CorpusSource::is_weighted) {
+        return Ok(vec![1.0; sources.len()]);
+    }
+
+    let shared = sources.iter().filter_map(|s| s.share).sum::<f64>();
+    let weights = sources
+        .iter()
+        .filter(|s| s.share.is_none())
+        .map(CorpusSource::weight)
+        .sum::<f64>();
+
+    if shared > 1.0 || (shared >= 1.0 && weights > 0.0) {
+        return Err(OxeylyzerError::CorpusSharesTooLarge(shared));
+    }
+
+    let shares = sources
+        .iter()
+        .map(|s| {
+            s.share
+                .unwrap_or_else(|| (1.0 - shared) * s.weight() / weights)
+        })
+        .collect::<Vec<_>>();
+    let totals = counts
+        .iter()
+        .map(|data| data.chars.values().sum::<i64>() as f64)
+        .collect::<Vec<_>>();
+
+    let target = totals
+        .iter()
+        .zip(&shares)
+        .filter(|(total, _)| **total > 0.0)
+        .map(|(total, share)| total / share)
+        .fold(0.0, f64::max);
+
+    let scales = totals
+        .iter()
+        .zip(&shares)
+        .map(|(&total, share)| match total > 0.0 {
+            true => target * share / total,
+            false => 1.0,
+        })
+        .collect();
+
+    Ok(scales)
+}
+
+/// Finds where to split a chunk that was read from a stream: right after its last space, or if
+/// it has none, before its last character so no character is cut in half.
+#[cfg(not(target_arch = "wasm32"))]
+fn chunk_boundary(chunk: &[u8]) -> usize {
+    match chunk.iter().rposition(|&b| b == b' ') {
+        Some(i) => i + 1,
+        None => chunk
+            .iter()
+            .rposition(|&b| b & 0xc0 != 0x80)
+            .filter(|&i| i > 0)
+            .unwrap_or(chunk.len()),
+    }
+}
+
 impl std::ops::Add for IntermediateData {
     type Output = Self;
 
@@ -497,10 +1431,89 @@ impl std::ops::Add for IntermediateData {
                 .or_insert(freq);
         }
 
+        for (quadgram, freq) in rhs.quadgrams.into_iter() {
+            self.quadgrams
+                .entry(quadgram)
+                .and_modify(|f| *f += freq)
+                .or_insert(freq);
+        }
+
+        for (word, freq) in rhs.words.into_iter() {
+            self.words
+                .entry(word)
+                .and_modify(|f| *f += freq)
+                .or_insert(freq);
+        }
+
+        for (c, freq) in rhs.unmapped.into_iter() {
+            self.unmapped
+                .entry(c)
+                .and_modify(|f| *f += freq)
+                .or_insert(freq);
+        }
+
         self
     }
 }
 
+impl IntermediateData {
+    /// Multiplies every count by `scale`, rounding to the nearest count and dropping counts that
+    /// round to zero.
+    fn scale(mut self, scale: f64) -> Self {
+        if scale == 1.0 {
+            return self;
+        }
+
+        fn scale_map<K>(map: &mut HashMap<K, i64>, scale: f64) {
+            map.values_mut()
+                .for_each(|f| *f = (*f as f64 * scale).round() as i64);
+            map.retain(|_, f| *f > 0);
+        }
+
+        scale_map(&mut self.chars, scale);
+        scale_map(&mut self.bigrams, scale);
+        scale_map(&mut self.skipgrams, scale);
+        scale_map(&mut self.skipgrams2, scale);
+        scale_map(&mut self.skipgrams3, scale);
+        scale_map(&mut self.trigrams, scale);
+        scale_map(&mut self.quadgrams, scale);
+        scale_map(&mut self.words, scale);
+        scale_map(&mut self.unmapped, scale);
+
+        self
+    }
+}
+
+impl std::ops::Sub for IntermediateData {
+    type Output = Self;
+
+    /// Removes counts that are also in `rhs`, which should be a subset of `self`.
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        subtract(&mut self.chars, rhs.chars);
+        subtract(&mut self.bigrams, rhs.bigrams);
+        subtract(&mut self.skipgrams, rhs.skipgrams);
+        subtract(&mut self.skipgrams2, rhs.skipgrams2);
+        subtract(&mut self.skipgrams3, rhs.skipgrams3);
+        subtract(&mut self.trigrams, rhs.trigrams);
+        subtract(&mut self.quadgrams, rhs.quadgrams);
+        subtract(&mut self.words, rhs.words);
+
+        self
+    }
+}
+
+fn subtract<K: std::hash::Hash + Eq>(lhs: &mut HashMap<K, i64>, rhs: HashMap<K, i64>) {
+    for (key, freq) in rhs {
+        if let Some(f) = lhs.get_mut(&key) {
+            *f -= freq;
+
+            if *f == 0 {
+                lhs.remove(&key);
+            }
+        }
+    }
+}
+
 impl From<IntermediateData> for Data {
     fn from(data: IntermediateData) -> Self {
         let char_total = data.chars.values().sum::<i64>();
@@ -509,6 +1522,8 @@ impl From<IntermediateData> for Data {
         let skipgram2_total = data.skipgrams2.values().sum::<i64>();
         let skipgram3_total = data.skipgrams3.values().sum::<i64>();
         let trigram_total = data.trigrams.values().sum::<i64>();
+        let quadgram_total = data.quadgrams.values().sum::<i64>();
+        let word_total = data.words.values().sum::<i64>();
 
         let char_total_f = char_total as f64 / 100.0;
         let bigram_total_f = bigram_total as f64 / 100.0;
@@ -516,6 +1531,8 @@ impl From<IntermediateData> for Data {
         let skipgram2_total_f = skipgram2_total as f64 / 100.0;
         let skipgram3_total_f = skipgram3_total as f64 / 100.0;
         let trigram_total_f = trigram_total as f64 / 100.0;
+        let quadgram_total_f = quadgram_total as f64 / 100.0;
+        let word_total_f = word_total as f64 / 100.0;
 
         let chars = data
             .chars
@@ -553,6 +1570,18 @@ impl From<IntermediateData> for Data {
             .map(|(c, f)| (c, f as f64 / trigram_total_f))
             .collect();
 
+        let quadgrams = data
+            .quadgrams
+            .into_iter()
+            .map(|(c, f)| (c, f as f64 / quadgram_total_f))
+            .collect();
+
+        let words = data
+            .words
+            .into_iter()
+            .map(|(w, f)| (w, f as f64 / word_total_f))
+            .collect();
+
         Self {
             name: data.name,
 
@@ -562,6 +1591,8 @@ impl From<IntermediateData> for Data {
             skipgrams2,
             skipgrams3,
             trigrams,
+            quadgrams,
+            words,
 
             char_total,
             bigram_total,
@@ -569,14 +1600,53 @@ impl From<IntermediateData> for Data {
             skipgram2_total,
             skipgram3_total,
             trigram_total,
+            quadgram_total,
+            word_total,
         }
     }
 }
 
-impl FromIterator<char> for IntermediateData {
-    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+impl IntermediateData {
+    fn from_chars<T: IntoIterator<Item = char>>(iter: T, options: CollectionOptions) -> Self {
+        Self::from_chunk(&[], iter, String::new(), options, true)
+    }
+
+    /// Counts the characters of `iter` and every n-gram ending in them, where n-grams may start
+    /// in `tail`, the characters right before it. Words continue from `word`, and the last word
+    /// is only counted when `finish` is set.
+    fn from_chunk<T: IntoIterator<Item = char>>(
+        tail: &[char],
+        iter: T,
+        mut word: String,
+        options: CollectionOptions,
+        finish: bool,
+    ) -> Self {
+        let quadgrams = options.quadgrams.is_some();
+
+        let mut words = HashMap::default();
+        let iter = iter.into_iter().inspect(|&c| {
+            if options.words.is_some() {
+                add_to_word(&mut words, &mut word, c);
+            }
+        });
+
+        let mut res = Self::count_ngrams(tail.iter().copied().chain(iter), quadgrams);
+
+        if !tail.is_empty() {
+            res = res - Self::count_ngrams(tail.iter().copied(), quadgrams);
+        }
+
+        if finish {
+            end_word(&mut words, &mut word);
+        }
+        words.retain(|w, _| !w.contains(REPLACEMENT_CHAR));
+        res.words = words;
+
+        res
+    }
+
+    fn count_ngrams<T: Iterator<Item = char>>(mut iter: T, quadgrams: bool) -> Self {
         let mut res = Self::default();
-        let mut iter = iter.into_iter();
 
         if let Some(mut c1) = iter.next() {
             res.add_char(c1);
@@ -597,6 +1667,9 @@ impl FromIterator<char> for IntermediateData {
                         res.add_skipgram(c2, c4);
                         res.add_skipgram2(c1, c4);
                         res.add_trigram(c2, c3, c4);
+                        if quadgrams {
+                            res.add_quadgram(c1, c2, c3, c4);
+                        }
 
                         for c5 in iter {
                             res.add_char(c5);
@@ -605,6 +1678,9 @@ impl FromIterator<char> for IntermediateData {
                             res.add_skipgram2(c2, c5);
                             res.add_skipgram3(c1, c5);
                             res.add_trigram(c3, c4, c5);
+                            if quadgrams {
+                                res.add_quadgram(c2, c3, c4, c5);
+                            }
 
                             c1 = c2;
                             c2 = c3;
@@ -622,11 +1698,35 @@ impl FromIterator<char> for IntermediateData {
         res.skipgrams2.retain(|s, _| !s.contains(&REPLACEMENT_CHAR));
         res.skipgrams3.retain(|s, _| !s.contains(&REPLACEMENT_CHAR));
         res.trigrams.retain(|t, _| !t.contains(&REPLACEMENT_CHAR));
+        res.quadgrams.retain(|q, _| !q.contains(&REPLACEMENT_CHAR));
 
         res
     }
 }
 
+/// Adds a character to the word that is currently being read. Spaces and other whitespace end
+/// the word, and shift is ignored so capitalized words count as the same word.
+fn add_to_word(words: &mut HashMap<String, i64>, word: &mut String, c: char) {
+    match c {
+        SHIFT_CHAR => {}
+        SPACE_CHAR => end_word(words, word),
+        c if c.is_whitespace() => end_word(words, word),
+        c => word.push(c),
+    }
+}
+
+fn end_word(words: &mut HashMap<String, i64>, word: &mut String) {
+    if !word.is_empty() {
+        *words.entry(std::mem::take(word)).or_insert(0) += 1;
+    }
+}
+
+impl FromIterator<char> for IntermediateData {
+    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+        Self::from_chars(iter, CollectionOptions::default())
+    }
+}
+
 impl FromIterator<char> for Data {
     fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
         iter.into_iter().collect::<IntermediateData>().into()
@@ -679,3 +1779,262 @@ impl FromParallelIterator<Vec<char>> for Data {
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(a: &Data, b: &Data) {
+        assert_eq!(a.chars, b.chars);
+        assert_eq!(a.bigrams, b.bigrams);
+        assert_eq!(a.skipgrams, b.skipgrams);
+        assert_eq!(a.trigrams, b.trigrams);
+        assert_eq!(a.char_total, b.char_total);
+        assert_eq!(a.bigram_total, b.bigram_total);
+        assert_eq!(a.trigram_total, b.trigram_total);
+    }
+
+    #[test]
+    fn stream_compressed() {
+        let cleaner = CorpusCleaner::default();
+        let options = CollectionOptions::default();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(50_000);
+
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::fast());
+        gz.write_all(text.as_bytes()).unwrap();
+        let gz = gz.finish().unwrap();
+        let zst = zstd::encode_all(text.as_bytes(), 1).unwrap();
+
+        let plain = Data::from_reader(text.as_bytes(), "plain", &cleaner, options).unwrap();
+        let gz = Data::from_reader(gz.as_slice(), "gz", &cleaner, options).unwrap();
+        let zst = Data::from_reader(zst.as_slice(), "zst", &cleaner, options).unwrap();
+
+        assert!(text.len() > 2 * CHUNK_SIZE);
+        assert!(plain.char_total > 0);
+        assert_same(&plain, &gz);
+        assert_same(&plain, &zst);
+
+        let short = "Hello there, General Kenobi";
+        let streamed = Data::from_reader(short.as_bytes(), "short", &cleaner, options).unwrap();
+        let collected = short.chars().clean_corpus(&cleaner).collect::<Data>();
+        assert_same(&streamed, &collected);
+    }
+
+    #[test]
+    fn weighted_sources() {
+        let dir = std::env::temp_dir().join(format!("oxeylyzer-sources-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let large = dir.join("large.txt");
+        let small = dir.join("small.txt");
+        let lines = (0..1000)
+            .map(|i| format!("{}\n", i % 10))
+            .collect::<String>();
+        std::fs::write(&large, "ab ".repeat(3000)).unwrap();
+        std::fs::write(&small, &lines).unwrap();
+
+        let cleaner = CorpusCleaner::builder()
+            .with_chars("ab0123456789".chars())
+            .build();
+        let options = CollectionOptions::default();
+        let load = |sources: &[CorpusSource]| {
+            Data::from_sources_with_report(sources, "sources", &cleaner, options).map(|(d, _)| d)
+        };
+        let digits = |data: &Data| ('0'..='9').flat_map(|c| data.get_char(c)).sum::<f64>();
+
+        // unweighted sources are simply added together
+        let unweighted = load(&[CorpusSource::new(&large), CorpusSource::new(&small)]).unwrap();
+        assert_eq!(unweighted.char_total, 7000);
+
+        let weighted = load(&[
+            CorpusSource::new(&large),
+            CorpusSource {
+                weight: Some(1.0),
+                ..CorpusSource::new(&small)
+            },
+        ])
+        .unwrap();
+        assert!((digits(&weighted) - 50.0).abs() < 1e-9);
+
+        let shared = load(&[
+            CorpusSource::new(&large),
+            CorpusSource {
+                share: Some(0.25),
+                ..CorpusSource::new(&small)
+            },
+        ])
+        .unwrap();
+        assert!((digits(&shared) - 25.0).abs() < 1e-9);
+        assert_eq!(shared.get_char('a'), shared.get_char('b'));
+
+        let too_large = [
+            CorpusSource {
+                share: Some(1.0),
+                ..CorpusSource::new(&large)
+            },
+            CorpusSource::new(&small),
+        ];
+        assert!(matches!(
+            load(&too_large),
+            Err(OxeylyzerError::CorpusSharesTooLarge(_))
+        ));
+
+        // every fifth line is sampled, which is the line with a 4 every time
+        let sampled = load(&[CorpusSource {
+            sample: Some(Sampling::Lines(0.2)),
+            ..CorpusSource::new(&small)
+        }])
+        .unwrap();
+        assert_eq!(sampled.char_total, 200);
+        assert_eq!(sampled.get_char('4'), Some(&50.0));
+        assert_eq!(sampled.get_char('9'), Some(&50.0));
+
+        let text = "ab ".repeat(50_000);
+        let mut reader = Sampled::new(text.as_bytes(), Sampling::Bytes(0.5));
+        let mut sampled = String::new();
+        reader.read_to_string(&mut sampled).unwrap();
+        assert!(sampled.len() < 80_000 && sampled.len() > 70_000);
+        assert!(
+            sampled
+                .split(' ')
+                .all(|word| word == "ab" || word.is_empty())
+        );
+
+        // blocks that aren't cut short continue where the last one stopped
+        let mut reader = Sampled::new(text.as_bytes(), Sampling::Bytes(1.0));
+        let mut sampled = String::new();
+        reader.read_to_string(&mut sampled).unwrap();
+        assert_eq!(sampled, text);
+
+        // without spaces, blocks still start at a character
+        let text = "日本語".repeat(50_000);
+        let mut reader = Sampled::new(text.as_bytes(), Sampling::Bytes(0.5));
+        let mut sampled = String::new();
+        reader.read_to_string(&mut sampled).unwrap();
+        assert!(sampled.len() < 240_000 && sampled.len() > 210_000);
+        assert!(sampled.chars().all(|c| "日本語".contains(c)));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parallel_equals_sequential() {
+        let path = concat!(
+            std::env!("CARGO_MANIFEST_DIR"),
+            "/../static/text/monkeyracer/mr.txt"
+        );
+        let text = std::fs::read_to_string(path).unwrap();
+
+        let cleaner = CorpusCleaner::builder()
+            .with_chars("abcdefghijklmnopqrstuvwxyz".chars())
+            .with_char_mappings([(' ', SPACE_CHAR), ('\n', SPACE_CHAR)])
+            .qwerty_punctuation_mappings(true)
+            .repeat_key(true)
+            .build();
+        let options = CollectionOptions {
+            quadgrams: Some(usize::MAX),
+            words: Some(usize::MAX),
+        };
+
+        let mut iter = text.chars().clean_corpus(&cleaner);
+        let mut sequential = IntermediateData::from_chars(iter.by_ref().flatten(), options);
+        sequential.unmapped = iter.unmapped().collect();
+
+        // Chunks of 1 to 50 characters, so some are shorter than the n-grams spanning them
+        let small = text.chars().take(200_000).collect::<String>();
+        let mut chunks = Vec::new();
+        let mut rest = small.as_str();
+        for len in (1..=50).cycle() {
+            match rest.char_indices().nth(len) {
+                Some((i, _)) => {
+                    chunks.push(&rest[..i]);
+                    rest = &rest[i..];
+                }
+                None => {
+                    chunks.push(rest);
+                    break;
+                }
+            }
+        }
+
+        let mut context = ChunkContext::default();
+        let chunked = chunks
+            .chunks(8)
+            .map(|batch| IntermediateData::from_chunks(batch, &cleaner, options, &mut context))
+            .fold(IntermediateData::default(), |a, b| a + b)
+            .finish(context, options);
+        let mut iter = small.chars().clean_corpus(&cleaner);
+        let mut expected = IntermediateData::from_chars(iter.by_ref().flatten(), options);
+        expected.unmapped = iter.unmapped().collect();
+        assert_eq!(chunked, expected);
+
+        let mut file = std::fs::File::open(path).unwrap();
+        let mut from_file = IntermediateData::from_file(
+            std::fs::File::open(path).unwrap(),
+            "monkeyracer",
+            &cleaner,
+            options,
+        )
+        .unwrap();
+        let mut from_reader =
+            IntermediateData::from_reader(&mut file, "monkeyracer", &cleaner, options).unwrap();
+        from_file.name.clear();
+        from_reader.name.clear();
+
+        assert!(text.len() > 2 * CHUNK_SIZE);
+        assert_eq!(from_file, sequential);
+        assert_eq!(from_reader, sequential);
+        assert_eq!(Data::from(from_file), Data::from(sequential));
+    }
+
+    #[test]
+    fn report() {
+        let path = concat!(
+            std::env!("CARGO_MANIFEST_DIR"),
+            "/../static/text/monkeyracer/mr.txt"
+        );
+        let text = std::fs::read_to_string(path).unwrap();
+
+        let cleaner = CorpusCleaner::builder()
+            .with_chars("abcdefghijklmnopqrstuvwxyz".chars())
+            .with_char_mappings([(' ', SPACE_CHAR)])
+            .repeat_key(true)
+            .build();
+
+        let (data, report) = Data::from_paths_with_report(
+            &[path],
+            "monkeyracer",
+            &cleaner,
+            CollectionOptions::default(),
+        )
+        .unwrap();
+
+        let cleaned = text
+            .chars()
+            .clean_corpus(&cleaner)
+            .flatten()
+            .collect::<Vec<_>>();
+        let count = |c: char| cleaned.iter().filter(|&&x| x == c).count() as i64;
+
+        assert_eq!(report.name, "monkeyracer");
+        assert_eq!(report.char_total, data.char_total);
+        assert_eq!(report.bigram_total, data.bigram_total);
+        assert_eq!(report.replaced, count(REPLACEMENT_CHAR));
+        assert_eq!(report.shifts, count(SHIFT_CHAR));
+        assert_eq!(report.repeats, count(REPEAT_KEY));
+        assert!(report.repeats > 0);
+        assert_eq!(
+            report.unmapped.get(&','),
+            Some(&(text.matches(',').count() as i64))
+        );
+        assert!(report.unmapped.values().is_sorted_by(|a, b| a >= b));
+    }
+
+    #[test]
+    fn chunk_boundaries() {
+        assert_eq!(chunk_boundary(b"ab cd"), 3);
+        assert_eq!(chunk_boundary(b"abcd"), 3);
+        assert_eq!(chunk_boundary("aé".as_bytes()), 1);
+        assert_eq!(chunk_boundary("é".as_bytes()), 2);
+    }
+}
@@ -2,14 +2,15 @@ use std::sync::Arc;
 
 use ahash::AHashMap as HashMap;
 use itertools::Itertools;
-use libdof::prelude::{Finger, PhysicalKey, Shape};
+use libdof::prelude::{Finger, Hand, PhysicalKey, Shape};
 use serde::Serialize;
 
 use crate::{
     char_mapping::CharMapping,
     layout::{LayoutMetadata, Pos, PosPair},
+    timing::TimingTable,
     utility::*,
-    weights::FingerWeights,
+    weights::{AnalyzerKeyEffort, EffortPreset, FingerWeights, Geometry},
 };
 
 const KEY_EDGE_OFFSET: f64 = 0.5;
@@ -22,7 +23,7 @@ const KEY_EDGE_OFFSET: f64 = 0.5;
 /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
 /// let config = Config::with_defaults();
 /// let data = Data::default();
-/// let oxeylyzer = Oxeylyzer::new(data, config);
+/// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
 ///
 /// let stronk = serde_json::from_str::<Layout>(include_str!(
 ///     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
@@ -55,6 +56,12 @@ pub struct FastLayout {
     pub stretch_indices: StretchIndices,
     /// Indices for calculating finger usage.
     pub usage_indices: UsageIndices,
+    /// Indices for calculating row usage.
+    pub row_indices: RowIndices,
+    /// Indices for calculating finger travel distance.
+    pub travel_indices: TravelIndices,
+    /// Effort of pressing every key.
+    pub effort_indices: EffortIndices,
     /// List of all possible key swaps.
     pub possible_swaps: Arc<[PosPair]>,
     /// Mapping between characters and internal byte representations.
@@ -76,7 +83,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -98,7 +105,7 @@ impl FastLayout {
     /// # Examples:
     /// ```
     /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
-    /// # let oxeylyzer = Oxeylyzer::new(Data::default(), Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(Data::default(), Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -123,7 +130,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -158,7 +165,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -184,7 +191,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -208,7 +215,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -244,7 +251,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -266,7 +273,7 @@ impl FastLayout {
     /// #    "/static/language_data/english.json"
     /// # );
     /// # let data = Data::load(data_path).unwrap();
-    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults());
+    /// # let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
     /// # let stronk = serde_json::from_str::<Layout>(include_str!(
     /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
     /// # )).unwrap();
@@ -327,14 +334,15 @@ pub struct BigramPair {
 ///
 /// # Examples:
 /// ```
-/// # use oxeylyzer_core::{fast_layout::FSpeedIndices, weights::FingerWeights};
+/// # use oxeylyzer_core::{fast_layout::FSpeedIndices, weights::{FingerWeights, Geometry}};
 /// use libdof::prelude::*;
 ///
 /// let fingers = [Finger::LP, Finger::LP];
 /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(0.0, 1.0)];
 /// let finger_weights = FingerWeights::default();
+/// let geometry = Geometry::default();
 ///
-/// let fspeed_indices = FSpeedIndices::new(&fingers, &keyboard, &finger_weights);
+/// let fspeed_indices = FSpeedIndices::new(&fingers, &keyboard, &finger_weights, &geometry);
 /// assert_eq!(fspeed_indices.all.len(), 1);
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -351,14 +359,15 @@ impl FSpeedIndices {
     /// # Examples:
     /// ```
     /// # use oxeylyzer_core::fast_layout::{FSpeedIndices, BigramPair};
-    /// # use oxeylyzer_core::{layout::PosPair, weights::FingerWeights};
+    /// # use oxeylyzer_core::{layout::PosPair, weights::{FingerWeights, Geometry}};
     /// use libdof::prelude::*;
     ///
     /// let fingers = [Finger::LP, Finger::LP];
     /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(0.0, 1.0)];
     /// let finger_weights = FingerWeights::default();
+    /// let geometry = Geometry::default();
     ///
-    /// let fspeed_indices = FSpeedIndices::new(&fingers, &keyboard, &finger_weights);
+    /// let fspeed_indices = FSpeedIndices::new(&fingers, &keyboard, &finger_weights, &geometry);
     /// assert_eq!(
     ///     fspeed_indices.get_finger(Finger::LP),
     ///     &[BigramPair { dist: 100, pair: PosPair(0, 1) }]
@@ -372,20 +381,22 @@ impl FSpeedIndices {
     ///
     /// # Examples:
     /// ```
-    /// # use oxeylyzer_core::{fast_layout::FSpeedIndices, weights::FingerWeights};
+    /// # use oxeylyzer_core::{fast_layout::FSpeedIndices, weights::{FingerWeights, Geometry}};
     /// use libdof::prelude::*;
     ///
     /// let fingers = [Finger::LP, Finger::LP];
     /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(0.0, 1.0)];
     /// let finger_weights = FingerWeights::default();
+    /// let geometry = Geometry::default();
     ///
-    /// let fspeed_indices = FSpeedIndices::new(&fingers, &keyboard, &finger_weights);
+    /// let fspeed_indices = FSpeedIndices::new(&fingers, &keyboard, &finger_weights, &geometry);
     /// assert_eq!(fspeed_indices.all.len(), 1);
     /// ```
     pub fn new(
         fingers: &[Finger],
         keyboard: &[PhysicalKey],
         finger_weights: &FingerWeights,
+        geometry: &Geometry,
     ) -> Self {
         assert!(
             fingers.len() <= u8::MAX as usize,
@@ -410,7 +421,7 @@ impl FSpeedIndices {
                     .tuple_combinations::<(_, _)>()
                     .map(|((k1, i1), (k2, i2))| {
                         let pair = PosPair(i1, i2);
-                        let dist = (dist(k1, k2, finger, finger)
+                        let dist = (finger_dist(k1, k2, finger, geometry)
                             * 100.0
                             * (max_finger_weight / finger_weights.get(finger)))
                             as i64;
@@ -429,19 +440,85 @@ impl FSpeedIndices {
 
         Self { fingers, all }
     }
+
+    /// Creates new `FSpeedIndices` where the distance of every same finger bigram is its
+    /// measured time in milliseconds, averaged over both directions and scaled by the finger
+    /// weights like [`new`](Self::new) does. Pairs without a measurement get a distance of 0.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{fast_layout::FSpeedIndices, timing::TimingTable};
+    /// # use oxeylyzer_core::weights::FingerWeights;
+    /// use libdof::prelude::*;
+    ///
+    /// let fingers = [Finger::LP, Finger::LP, Finger::LI, Finger::LI];
+    /// let table = TimingTable::from_csv("0,1,150\n1,0,170\nli,li,160").unwrap();
+    /// let finger_weights = FingerWeights {
+    ///     lp: 0.5,
+    ///     li: 1.0,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let fspeed_indices = FSpeedIndices::from_timing(&fingers, &table, &finger_weights);
+    /// assert_eq!(fspeed_indices.all.len(), 2);
+    /// assert_eq!(fspeed_indices.all[0].dist, 320);
+    /// assert_eq!(fspeed_indices.all[1].dist, 160);
+    /// ```
+    pub fn from_timing(
+        fingers: &[Finger],
+        table: &TimingTable,
+        finger_weights: &FingerWeights,
+    ) -> Self {
+        assert!(
+            fingers.len() <= u8::MAX as usize,
+            "Too many keys to index with u8, max is {}",
+            u8::MAX
+        );
+
+        let max_finger_weight = finger_weights.max();
+
+        let per_finger: Box<[_; 10]> = Finger::FINGERS
+            .map(|finger| {
+                fingers
+                    .iter()
+                    .zip(0u8..)
+                    .filter_map(|(f, i)| (f == &finger).then_some(i))
+                    .tuple_combinations::<(_, _)>()
+                    .map(|(i1, i2)| {
+                        let pair = PosPair(i1, i2);
+                        let ms = table.pair_cost(i1, i2, finger, finger).unwrap_or_default();
+                        let dist = (ms * (max_finger_weight / finger_weights.get(finger))) as i64;
+
+                        BigramPair { pair, dist }
+                    })
+                    .collect::<Box<_>>()
+            })
+            .into();
+
+        let all = per_finger
+            .iter()
+            .flat_map(|f| f.iter())
+            .cloned()
+            .collect::<Box<_>>();
+
+        Self {
+            fingers: per_finger,
+            all,
+        }
+    }
 }
 
 /// Indices used for calculating scissor movements.
 ///
 /// # Examples:
 /// ```
-/// # use oxeylyzer_core::fast_layout::ScissorIndices;
+/// # use oxeylyzer_core::{fast_layout::ScissorIndices, weights::Geometry};
 /// use libdof::prelude::*;
 ///
 /// let fingers = [Finger::LP, Finger::LR];
 /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 2.0)];
 ///
-/// let scissor_indices = ScissorIndices::new(&fingers, &keyboard);
+/// let scissor_indices = ScissorIndices::new(&fingers, &keyboard, &Geometry::default());
 /// assert_eq!(scissor_indices.pairs.len(), 1);
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -453,20 +530,22 @@ pub struct ScissorIndices {
 }
 
 impl ScissorIndices {
-    /// Creates new `ScissorIndices` identifying uncomfortable stretches based on geometry.
+    /// Creates new `ScissorIndices` identifying uncomfortable stretches based on geometry. Keys
+    /// on adjacent fingers are a scissor when they're more than
+    /// [`Geometry::scissor_dy`] apart vertically.
     ///
     /// # Examples:
     /// ```
-    /// # use oxeylyzer_core::fast_layout::ScissorIndices;
+    /// # use oxeylyzer_core::{fast_layout::ScissorIndices, weights::Geometry};
     /// use libdof::prelude::*;
     ///
     /// let fingers = [Finger::LP, Finger::LR];
     /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 2.0)];
     ///
-    /// let scissor_indices = ScissorIndices::new(&fingers, &keyboard);
+    /// let scissor_indices = ScissorIndices::new(&fingers, &keyboard, &Geometry::default());
     /// assert_eq!(scissor_indices.pairs.len(), 1);
     /// ```
-    pub fn new(fingers: &[Finger], keyboard: &[PhysicalKey]) -> Self {
+    pub fn new(fingers: &[Finger], keyboard: &[PhysicalKey], geometry: &Geometry) -> Self {
         assert!(
             fingers.len() <= u8::MAX as usize,
             "Too many keys to index with u8, max is {}",
@@ -515,7 +594,7 @@ impl ScissorIndices {
 
                 let (_, dy) = ((k1.x() - k2.x()).abs(), (k1.y() - k2.y()).abs());
 
-                if dy.abs() <= 1.9 {
+                if dy.abs() <= geometry.scissor_dy {
                     return None;
                 }
 
@@ -550,13 +629,13 @@ impl ScissorIndices {
     ///
     /// # Examples:
     /// ```
-    /// # use oxeylyzer_core::fast_layout::ScissorIndices;
+    /// # use oxeylyzer_core::{fast_layout::ScissorIndices, weights::Geometry};
     /// use libdof::prelude::*;
     ///
     /// let fingers = [Finger::LP, Finger::LR];
     /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 2.0)];
     ///
-    /// let scissor_indices = ScissorIndices::new(&fingers, &keyboard);
+    /// let scissor_indices = ScissorIndices::new(&fingers, &keyboard, &Geometry::default());
     /// assert_eq!(scissor_indices.affects_scissor_idx(0), true);
     /// assert_eq!(scissor_indices.affects_scissor_idx(1), true);
     /// assert_eq!(scissor_indices.affects_scissor_idx(2), false);
@@ -574,13 +653,13 @@ impl ScissorIndices {
     ///
     /// # Examples:
     /// ```
-    /// # use oxeylyzer_core::{fast_layout::ScissorIndices, layout::PosPair};
+    /// # use oxeylyzer_core::{fast_layout::ScissorIndices, layout::PosPair, weights::Geometry};
     /// use libdof::prelude::*;
     ///
     /// let fingers = [Finger::LP, Finger::LR];
     /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 2.0)];
     ///
-    /// let scissor_indices = ScissorIndices::new(&fingers, &keyboard);
+    /// let scissor_indices = ScissorIndices::new(&fingers, &keyboard, &Geometry::default());
     /// assert_eq!(scissor_indices.affects_scissor(PosPair(0, 1)), true);
     /// assert_eq!(scissor_indices.affects_scissor(PosPair(1, 2)), true);
     /// assert_eq!(scissor_indices.affects_scissor(PosPair(2, 3)), false);
@@ -594,13 +673,13 @@ impl ScissorIndices {
 ///
 /// # Examples:
 /// ```
-/// # use oxeylyzer_core::fast_layout::LsbIndices;
+/// # use oxeylyzer_core::{fast_layout::LsbIndices, weights::Geometry};
 /// use libdof::prelude::*;
 ///
 /// let fingers = [Finger::LM, Finger::LI];
 /// let keyboard = [PhysicalKey::xy(0.2, 0.0), PhysicalKey::xy(4.0, 0.0)];
 ///
-/// let lsb_indices = LsbIndices::new(&fingers, &keyboard);
+/// let lsb_indices = LsbIndices::new(&fingers, &keyboard, &Geometry::default());
 /// assert_eq!(lsb_indices.pairs.len(), 1);
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -610,21 +689,22 @@ pub struct LsbIndices {
 }
 
 impl LsbIndices {
-    /// Creates new `LsbIndices` identifying lateral stretches based on geometry.
+    /// Creates new `LsbIndices` identifying lateral stretches based on geometry. Middle and
+    /// index finger keys are a lateral stretch when they're at least [`Geometry::lsb_dx`] apart
+    /// horizontally.
     ///
     /// # Examples:
     /// ```
-    /// # use oxeylyzer_core::fast_layout::LsbIndices;
+    /// # use oxeylyzer_core::{fast_layout::LsbIndices, weights::Geometry};
     /// use libdof::prelude::*;
     ///
     /// let fingers = [Finger::LM, Finger::LI];
     /// let keyboard = [PhysicalKey::xy(0.2, 0.0), PhysicalKey::xy(4.0, 0.0)];
     ///
-    /// let lsb_indices = LsbIndices::new(&fingers, &keyboard);
+    /// let lsb_indices = LsbIndices::new(&fingers, &keyboard, &Geometry::default());
     /// assert_eq!(lsb_indices.pairs.len(), 1);
     /// ```
-    /// ```
-    pub fn new(fingers: &[Finger], keyboard: &[PhysicalKey]) -> Self {
+    pub fn new(fingers: &[Finger], keyboard: &[PhysicalKey], geometry: &Geometry) -> Self {
         assert!(
             fingers.len() <= u8::MAX as usize,
             "Too many keys to index with u8, max is {}",
@@ -648,8 +728,8 @@ impl LsbIndices {
                 }
 
                 if f1.is_middle() && f2.is_index() || f2.is_middle() && f1.is_index() {
-                    let (dx, _) = dx_dy(k1, k2, f1, f2);
-                    if dx.abs() >= 1.5 {
+                    let (dx, _) = dx_dy(k1, k2, f1, f2, geometry);
+                    if dx.abs() >= geometry.lsb_dx {
                         Some(PosPair(i1, i2))
                     } else {
                         None
@@ -793,14 +873,14 @@ impl PinkyRingIndices {
 ///
 /// # Examples:
 /// ```
-/// # use oxeylyzer_core::fast_layout::StretchIndices;
+/// # use oxeylyzer_core::{fast_layout::StretchIndices, weights::Geometry};
 /// use libdof::prelude::*;
 ///
 /// let keys = ['a', 'b'];
 /// let fingers = [Finger::LP, Finger::LR];
 /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 0.0)];
 ///
-/// let stretch_indices = StretchIndices::new(&keys, &fingers, &keyboard);
+/// let stretch_indices = StretchIndices::new(&keys, &fingers, &keyboard, &Geometry::default());
 /// assert_eq!(stretch_indices.all_pairs.len(), 0);
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -812,21 +892,28 @@ pub struct StretchIndices {
 }
 
 impl StretchIndices {
-    /// Creates new `StretchIndices` identifying stretch distances based on geometry.
+    /// Creates new `StretchIndices` identifying stretch distances based on geometry. Two keys on
+    /// the same hand are a stretch when they're further apart than
+    /// [`Geometry::stretch_per_finger`] for every finger between them.
     ///
     /// # Examples:
     /// ```
-    /// # use oxeylyzer_core::fast_layout::StretchIndices;
+    /// # use oxeylyzer_core::{fast_layout::StretchIndices, weights::Geometry};
     /// use libdof::prelude::*;
     ///
     /// let keys = ['a', 'b'];
     /// let fingers = [Finger::LP, Finger::LR];
     /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 0.0)];
     ///
-    /// let stretch_indices = StretchIndices::new(&keys, &fingers, &keyboard);
+    /// let stretch_indices = StretchIndices::new(&keys, &fingers, &keyboard, &Geometry::default());
     /// assert_eq!(stretch_indices.all_pairs.len(), 0);
     /// ```
-    pub fn new(keys: &[char], fingers: &[Finger], keyboard: &[PhysicalKey]) -> Self {
+    pub fn new(
+        keys: &[char],
+        fingers: &[Finger],
+        keyboard: &[PhysicalKey],
+        geometry: &Geometry,
+    ) -> Self {
         assert!(
             fingers.len() <= u8::MAX as usize,
             "Too many keys to index with u8, max is {}",
@@ -848,13 +935,13 @@ impl StretchIndices {
             .filter(|((_, ((_, f1), _)), (_, ((_, f2), _)))| f1 != f2 && (f1.hand() == f2.hand()))
             .filter_map(|((i1, ((k1, &f1), _c1)), (i2, ((k2, &f2), _c2)))| {
                 let diff = (f1 as u8).abs_diff(f2 as u8) as f64;
-                let fd = diff * 1.35;
+                let fd = diff * geometry.stretch_per_finger;
                 // let minimum_diff = diff * 0.9;
-                let (dx, dy) = dx_dy(k1, k2, f1, f2);
+                let (dx, dy) = dx_dy(k1, k2, f1, f2, geometry);
                 let negative_lsb = 0.0; //(minimum_diff - dx.abs() - 1.0).max(0.0) * 2.0;
                 let dist = dx.hypot(dy);
 
-                let xo = x_overlap(dx, dy, f1, f2);
+                let xo = x_overlap(dx, dy, f1, f2, geometry);
 
                 let stretch = dist + xo + negative_lsb - fd;
 
@@ -871,8 +958,73 @@ impl StretchIndices {
 
         // println!("pair count: {}", all_pairs.len());
 
-        let per_keypair = (0..(fingers.len() as u8))
-            .cartesian_product(0..(fingers.len() as u8))
+        Self::from_pairs(all_pairs, fingers.len())
+    }
+
+    /// Creates new `StretchIndices` from measured times of bigrams on different fingers of the
+    /// same hand, averaged over both directions. The fastest bigram on a pair of fingers is their
+    /// baseline, and every bigram that is slower than that is a stretch, with the amount of
+    /// milliseconds it is slower as distance.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{fast_layout::StretchIndices, timing::TimingTable};
+    /// use libdof::prelude::*;
+    ///
+    /// let fingers = [Finger::LP, Finger::LR, Finger::LR, Finger::RI];
+    /// let table = TimingTable::from_csv("0,1,150\nlp,lr,100\nlr,ri,80").unwrap();
+    ///
+    /// let stretch_indices = StretchIndices::from_timing(&fingers, &table);
+    /// assert_eq!(stretch_indices.all_pairs.len(), 1);
+    /// assert_eq!(stretch_indices.all_pairs[0].dist, 50);
+    /// ```
+    pub fn from_timing(fingers: &[Finger], table: &TimingTable) -> Self {
+        assert!(
+            fingers.len() <= u8::MAX as usize,
+            "Too many keys to index with u8, max is {}",
+            u8::MAX
+        );
+
+        let finger_pair = |f1: Finger, f2: Finger| {
+            let (f1, f2) = (f1 as u8, f2 as u8);
+            (f1.min(f2), f1.max(f2))
+        };
+
+        let measured = fingers
+            .iter()
+            .zip(0u8..)
+            .tuple_combinations::<(_, _)>()
+            .filter(|((f1, _), (f2, _))| f1 != f2 && f1.hand() == f2.hand())
+            .filter_map(|((&f1, i1), (&f2, i2))| {
+                let ms = table.pair_cost(i1, i2, f1, f2)?;
+
+                Some((finger_pair(f1, f2), PosPair(i1, i2), ms))
+            })
+            .collect::<Vec<_>>();
+
+        let mut baselines = HashMap::new();
+        for &(fingers, _, ms) in &measured {
+            baselines
+                .entry(fingers)
+                .and_modify(|baseline: &mut f64| *baseline = baseline.min(ms))
+                .or_insert(ms);
+        }
+
+        let all_pairs = measured
+            .into_iter()
+            .filter_map(|(fingers, pair, ms)| {
+                let dist = (ms - baselines[&fingers]) as i64;
+
+                (dist > 0).then_some(BigramPair { pair, dist })
+            })
+            .collect::<Box<[_]>>();
+
+        Self::from_pairs(all_pairs, fingers.len())
+    }
+
+    fn from_pairs(all_pairs: Box<[BigramPair]>, len: usize) -> Self {
+        let per_key_pair = (0..(len as u8))
+            .cartesian_product(0..(len as u8))
             .map(|(i1, i2)| {
                 let is = [i1, i2];
 
@@ -888,37 +1040,13 @@ impl StretchIndices {
 
         Self {
             all_pairs,
-            per_key_pair: per_keypair,
+            per_key_pair,
         }
     }
 }
 
-fn x_finger_overlap(f1: Finger, f2: Finger) -> f64 {
-    use Finger::*;
-
-    match (f1, f2) {
-        (LP, LR) => 0.8,
-        (LR, LP) => 0.8,
-        (LR, LM) => 0.4,
-        (LM, LR) => 0.4,
-        (LM, LI) => 0.1,
-        (LI, LM) => 0.1,
-        (LI, LT) => -2.5,
-        (LT, LI) => -2.5,
-        (RT, RI) => -2.5,
-        (RI, RT) => -2.5,
-        (RI, RM) => 0.1,
-        (RM, RI) => 0.1,
-        (RM, RR) => 0.4,
-        (RR, RM) => 0.4,
-        (RR, RP) => 0.8,
-        (RP, RR) => 0.8,
-        _ => 0.0,
-    }
-}
-
-fn x_overlap(dx: f64, dy: f64, f1: Finger, f2: Finger) -> f64 {
-    let x_offset = x_finger_overlap(f1, f2);
+fn x_overlap(dx: f64, dy: f64, f1: Finger, f2: Finger, geometry: &Geometry) -> f64 {
+    let x_offset = geometry.finger_overlap.get(f1, f2);
 
     let dx_offset = x_offset - dx * 1.3;
     let dy_offset = 0.3333 * dy;
@@ -926,14 +1054,14 @@ fn x_overlap(dx: f64, dy: f64, f1: Finger, f2: Finger) -> f64 {
     (dx_offset + dy_offset).max(0.0)
 }
 
-fn dx_dy(k1: &PhysicalKey, k2: &PhysicalKey, f1: Finger, f2: Finger) -> (f64, f64) {
-    let f_len = |f: Finger| match f {
-        Finger::LP | Finger::RP => -0.15,
-        Finger::LR | Finger::RR => 0.35,
-        Finger::LM | Finger::RM => 0.25,
-        Finger::LI | Finger::RI => -0.30,
-        Finger::LT | Finger::RT => -1.80,
-    };
+fn dx_dy(
+    k1: &PhysicalKey,
+    k2: &PhysicalKey,
+    f1: Finger,
+    f2: Finger,
+    geometry: &Geometry,
+) -> (f64, f64) {
+    let f_len = |f: Finger| geometry.finger_lengths.get(f);
 
     let ox1 = (k1.width() * KEY_EDGE_OFFSET).min(KEY_EDGE_OFFSET);
     let ox2 = (k1.width() * KEY_EDGE_OFFSET).min(KEY_EDGE_OFFSET);
@@ -959,7 +1087,7 @@ fn dx_dy(k1: &PhysicalKey, k2: &PhysicalKey, f1: Finger, f2: Finger) -> (f64, f6
     // pressed with middle and index is considered 1, if each key were pressed with the other
     // finger, the distance is negative (because who the fuck is doing that, that's not good).
 
-    let xo = x_finger_overlap(f1, f2);
+    let xo = geometry.finger_overlap.get(f1, f2);
 
     match ((f1 as u8) > (f2 as u8), (f1 as u8) < (f2 as u8)) {
         (true, false) if r1 < l2 + xo => (-dx, dy),
@@ -968,8 +1096,9 @@ fn dx_dy(k1: &PhysicalKey, k2: &PhysicalKey, f1: Finger, f2: Finger) -> (f64, f6
     }
 }
 
-fn dist(k1: &PhysicalKey, k2: &PhysicalKey, f1: Finger, f2: Finger) -> f64 {
-    let (dx, dy) = dx_dy(k1, k2, f1, f2);
+/// Distance between two keys pressed by the same finger.
+fn finger_dist(k1: &PhysicalKey, k2: &PhysicalKey, finger: Finger, geometry: &Geometry) -> f64 {
+    let (dx, dy) = dx_dy(k1, k2, finger, finger, geometry);
 
     dx.hypot(dy)
 }
@@ -1037,43 +1166,404 @@ impl UsageIndices {
     }
 }
 
+type PerFingerDistances = Box<[Box<[(u8, i64)]>; 10]>;
+
+/// Indices used for calculating how far fingers travel.
+///
+/// Every finger rests on its home key. Pressing a key moves the finger there, either from the
+/// last key it pressed if the previous keystroke used the same finger, or from its home key
+/// otherwise. If the next keystroke uses a different finger, the finger returns home. This means
+/// every keystroke costs twice the distance from home, minus what is saved by same finger
+/// bigrams moving between keys directly. Distances are multiplied by 100.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{fast_layout::TravelIndices, weights::Geometry};
+/// use libdof::prelude::*;
+///
+/// let fingers = [Finger::LI, Finger::LI, Finger::LI];
+/// let keyboard = [
+///     PhysicalKey::xy(0.0, 0.0),
+///     PhysicalKey::xy(0.0, 1.0),
+///     PhysicalKey::xy(0.0, 2.0),
+/// ];
+///
+/// let travel_indices = TravelIndices::new(&fingers, &keyboard, &Geometry::default());
+/// assert_eq!(travel_indices.home[Finger::LI as usize], Some(1));
+/// assert_eq!(travel_indices.from_home(Finger::LI), &[(0, 100), (1, 0), (2, 100)]);
+/// assert_eq!(travel_indices.pairs(Finger::LI).len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TravelIndices {
+    /// Home key position of every finger, if it has any keys.
+    pub home: [Option<u8>; 10],
+    /// Key positions of every finger, with their distance to the finger's home key.
+    pub from_home: PerFingerDistances,
+    /// Pairs of keys on the same finger. `dist` is the distance that is saved by moving between
+    /// them directly rather than through the home key.
+    pub pairs: Box<[Box<[BigramPair]>; 10]>,
+}
+
+impl TravelIndices {
+    /// Creates new `TravelIndices` based on finger assignments and keyboard geometry. The home
+    /// key of a finger is the key closest to the home row, which is the row closest to the
+    /// average key height. Ties are broken by picking the key closest to the middle finger,
+    /// which is what puts index fingers on qwerty `f` and `j` rather than `g` and `h`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{fast_layout::TravelIndices, weights::Geometry};
+    /// use libdof::prelude::*;
+    ///
+    /// let fingers = [Finger::LM, Finger::LI, Finger::LI];
+    /// let keyboard = [
+    ///     PhysicalKey::xy(0.0, 0.0),
+    ///     PhysicalKey::xy(1.0, 0.0),
+    ///     PhysicalKey::xy(2.0, 0.0),
+    /// ];
+    ///
+    /// let travel_indices = TravelIndices::new(&fingers, &keyboard, &Geometry::default());
+    /// assert_eq!(travel_indices.home[Finger::LM as usize], Some(0));
+    /// assert_eq!(travel_indices.home[Finger::LI as usize], Some(1));
+    /// assert_eq!(travel_indices.home[Finger::RI as usize], None);
+    /// ```
+    pub fn new(fingers: &[Finger], keyboard: &[PhysicalKey], geometry: &Geometry) -> Self {
+        assert_eq!(
+            fingers.len(),
+            keyboard.len(),
+            "finger len is not the same as keyboard len: "
+        );
+
+        let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len().max(1) as f64;
+        let home_row = home_row_height(keyboard);
+
+        let keys_of = |finger: Finger| {
+            fingers
+                .iter()
+                .zip(keyboard)
+                .zip(0u8..)
+                .filter_map(move |((&f, k), i)| (f == finger).then_some((k, i)))
+        };
+
+        let home = Finger::FINGERS.map(|finger| {
+            let middle = match finger.hand() {
+                Hand::Left => Finger::LM,
+                Hand::Right => Finger::RM,
+            };
+            let middle_xs = keys_of(middle)
+                .map(|(k, _)| key_center(k).0)
+                .collect::<Vec<_>>();
+            let own_xs = keys_of(finger)
+                .map(|(k, _)| key_center(k).0)
+                .collect::<Vec<_>>();
+            let middle_x = match middle_xs.is_empty() {
+                true => mean(&own_xs),
+                false => mean(&middle_xs),
+            };
+
+            keys_of(finger)
+                .min_by(|(k1, _), (k2, _)| {
+                    let (x1, y1) = key_center(k1);
+                    let (x2, y2) = key_center(k2);
+
+                    (y1 - home_row)
+                        .abs()
+                        .total_cmp(&(y2 - home_row).abs())
+                        .then((x1 - middle_x).abs().total_cmp(&(x2 - middle_x).abs()))
+                })
+                .map(|(_, i)| i)
+        });
+
+        let from_home: PerFingerDistances = Finger::FINGERS
+            .map(|finger| match home[finger as usize] {
+                Some(h) => keys_of(finger)
+                    .map(|(k, i)| {
+                        let home_key = &keyboard[h as usize];
+                        (
+                            i,
+                            (finger_dist(home_key, k, finger, geometry) * 100.0) as i64,
+                        )
+                    })
+                    .collect::<Box<_>>(),
+                None => Box::new([]) as Box<[_]>,
+            })
+            .into();
+
+        let pairs: Box<[_; 10]> = Finger::FINGERS
+            .map(|finger| {
+                let to_home = |i: u8| {
+                    from_home[finger as usize]
+                        .iter()
+                        .find_map(|&(p, d)| (p == i).then_some(d))
+                        .unwrap_or_default()
+                };
+
+                keys_of(finger)
+                    .tuple_combinations::<(_, _)>()
+                    .map(|((k1, i1), (k2, i2))| {
+                        let direct = (finger_dist(k1, k2, finger, geometry) * 100.0) as i64;
+
+                        BigramPair {
+                            pair: PosPair(i1, i2),
+                            dist: to_home(i1) + to_home(i2) - direct,
+                        }
+                    })
+                    .collect::<Box<_>>()
+            })
+            .into();
+
+        Self {
+            home,
+            from_home,
+            pairs,
+        }
+    }
+
+    /// Returns the key positions of a finger with their distance to its home key.
+    pub fn from_home(&self, finger: Finger) -> &[(u8, i64)] {
+        &self.from_home[finger as usize]
+    }
+
+    /// Returns the pairs of keys on a finger with the distance saved by moving between them.
+    pub fn pairs(&self, finger: Finger) -> &[BigramPair] {
+        &self.pairs[finger as usize]
+    }
+}
+
+fn key_center(k: &PhysicalKey) -> (f64, f64) {
+    (k.x() + k.width() / 2.0, k.y() + k.height() / 2.0)
+}
+
+/// Height of the home row, which is the row closest to the average key height.
+fn home_row_height(keyboard: &[PhysicalKey]) -> f64 {
+    let heights = keyboard.iter().map(|k| key_center(k).1).collect::<Vec<_>>();
+    let average_height = heights.iter().sum::<f64>() / heights.len().max(1) as f64;
+
+    heights
+        .iter()
+        .copied()
+        .min_by(|a, b| {
+            (a - average_height)
+                .abs()
+                .total_cmp(&(b - average_height).abs())
+        })
+        .unwrap_or_default()
+}
+
+/// Rows a key can be on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Row {
+    /// Keys above the home row.
+    Top,
+    /// Keys on the home row.
+    Home,
+    /// Keys below the home row.
+    Bottom,
+    /// Keys pressed with a thumb, wherever they are.
+    Thumb,
+}
+
+impl Row {
+    /// All rows, in the order they're indexed in [`RowIndices`].
+    pub const ROWS: [Row; 4] = [Row::Top, Row::Home, Row::Bottom, Row::Thumb];
+}
+
+/// Indices used for calculating row usage.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::fast_layout::{Row, RowIndices};
+/// use libdof::prelude::*;
+///
+/// let fingers = [Finger::LI, Finger::LI, Finger::LI, Finger::LT];
+/// let keyboard = [
+///     PhysicalKey::xy(0.0, 0.0),
+///     PhysicalKey::xy(0.0, 1.0),
+///     PhysicalKey::xy(0.0, 2.0),
+///     PhysicalKey::xy(0.0, 3.0),
+/// ];
+///
+/// let row_indices = RowIndices::new(&fingers, &keyboard);
+/// assert_eq!(row_indices.get(Row::Top), &[0]);
+/// assert_eq!(row_indices.get(Row::Home), &[1]);
+/// assert_eq!(row_indices.get(Row::Bottom), &[2]);
+/// assert_eq!(row_indices.get(Row::Thumb), &[3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RowIndices {
+    /// Array of key positions on each row, indexed like [`Row::ROWS`].
+    pub per_row: [Box<[usize]>; 4],
+}
+
+impl RowIndices {
+    /// Creates new `RowIndices` based on finger assignments and keyboard geometry. Keys less
+    /// than half a key away from the home row, which is the row closest to the average key
+    /// height, are on the home row. Keys pressed with a thumb are always on the thumb row.
+    pub fn new(fingers: &[Finger], keyboard: &[PhysicalKey]) -> Self {
+        let home_row = home_row_height(keyboard);
+
+        let row = |f: Finger, k: &PhysicalKey| {
+            let dy = key_center(k).1 - home_row;
+
+            match f.is_thumb() {
+                true => Row::Thumb,
+                false if dy.abs() < 0.5 => Row::Home,
+                false if dy < 0.0 => Row::Top,
+                false => Row::Bottom,
+            }
+        };
+
+        let per_row = Row::ROWS.map(|r| {
+            fingers
+                .iter()
+                .zip(keyboard)
+                .enumerate()
+                .filter_map(|(pos, (&f, k))| (row(f, k) == r).then_some(pos))
+                .collect::<Box<[_]>>()
+        });
+
+        Self { per_row }
+    }
+
+    /// Returns the key positions on a specific row.
+    pub fn get(&self, row: Row) -> &[usize] {
+        &self.per_row[row as usize]
+    }
+}
+
+/// Effort of pressing every key, multiplied by 100.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::fast_layout::{EffortIndices, TravelIndices};
+/// # use oxeylyzer_core::weights::{AnalyzerKeyEffort, FingerWeights, Geometry};
+/// use libdof::prelude::*;
+///
+/// let fingers = [Finger::LI, Finger::LI];
+/// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(0.0, 1.0)];
+/// let travel_indices = TravelIndices::new(&fingers, &keyboard, &Geometry::default());
+///
+/// let effort_indices = EffortIndices::new(
+///     &fingers,
+///     &travel_indices,
+///     &AnalyzerKeyEffort::default(),
+///     &FingerWeights::default(),
+/// );
+/// assert_eq!(effort_indices.get(0), 100);
+/// assert_eq!(effort_indices.get(1), 200);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffortIndices {
+    /// Effort of every key position.
+    pub per_key: Box<[i64]>,
+}
+
+impl EffortIndices {
+    /// Creates new `EffortIndices`, either from the configured grid if it has exactly one value
+    /// for every key, or derived from the distance of every key to its finger's home key
+    /// according to the configured [`EffortPreset`]. Finger weights have to be positive, which
+    /// [`FingerWeights::validate`] checks when a config is loaded.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::fast_layout::{EffortIndices, TravelIndices};
+    /// # use oxeylyzer_core::weights::{AnalyzerKeyEffort, FingerWeights, Geometry};
+    /// use libdof::prelude::*;
+    ///
+    /// let fingers = [Finger::LP, Finger::LI];
+    /// let keyboard = [PhysicalKey::xy(0.0, 0.0), PhysicalKey::xy(1.0, 0.0)];
+    /// let travel_indices = TravelIndices::new(&fingers, &keyboard, &Geometry::default());
+    /// let key_effort = AnalyzerKeyEffort {
+    ///     grid: Some(Box::new([3.0, 1.5])),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let effort_indices =
+    ///     EffortIndices::new(&fingers, &travel_indices, &key_effort, &FingerWeights::default());
+    /// assert_eq!(effort_indices.per_key.as_ref(), &[300, 150]);
+    /// ```
+    pub fn new(
+        fingers: &[Finger],
+        travel_indices: &TravelIndices,
+        key_effort: &AnalyzerKeyEffort,
+        finger_weights: &FingerWeights,
+    ) -> Self {
+        if let Some(grid) = &key_effort.grid
+            && grid.len() == fingers.len()
+        {
+            let per_key = grid.iter().map(|e| (e * 100.0) as i64).collect();
+
+            return Self { per_key };
+        }
+
+        let max_finger_weight = finger_weights.max();
+        let mut per_key = vec![0; fingers.len()];
+
+        for finger in Finger::FINGERS {
+            let strength = match key_effort.preset {
+                EffortPreset::Distance => 1.0,
+                EffortPreset::FingerStrength => max_finger_weight / finger_weights.get(finger),
+            };
+
+            for &(p, dist) in travel_indices.from_home(finger) {
+                per_key[p as usize] = ((100 + dist) as f64 * strength) as i64;
+            }
+        }
+
+        Self {
+            per_key: per_key.into(),
+        }
+    }
+
+    /// Returns the effort of a key position.
+    pub fn get(&self, pos: usize) -> i64 {
+        self.per_key[pos]
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, path::PathBuf};
+    use std::collections::HashSet;
 
-    use crate::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
+    use crate::{
+        layout::Layout,
+        test_util::{GEN, QWERTY, oxeylyzer_with},
+    };
 
     use super::*;
-    use once_cell::sync::Lazy;
-
-    static GEN: Lazy<Oxeylyzer> = Lazy::new(|| {
-        let base = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
-        let config = Config::with_loaded_weights(base.join("config.toml")).unwrap();
-        let data = Data::load(base.join(&config.corpus)).unwrap();
-
-        Oxeylyzer::new(data, config)
-    });
-
-    static QWERTY: Lazy<FastLayout> = Lazy::new(|| {
-        let dof_str = r#"
-            {
-                "name": "Qwerty",
-                "board": "ansi",
-                "layers": {
-                    "main": [
-                        "q w e r t  y u i o p",
-                        "a s d f g  h j k l ;",
-                        "z x c v b  n m , . /"
-                    ]
-                },
-                "fingering": "traditional"
-            }
-        "#;
 
-        let layout = serde_json::from_str::<Layout>(dof_str).unwrap();
+    #[test]
+    fn qwerty_home_keys() {
+        let homes = QWERTY
+            .travel_indices
+            .home
+            .iter()
+            .flatten()
+            .map(|&p| GEN.mapping.get_c(QWERTY.keys[p as usize]))
+            .collect::<String>();
+
+        assert_eq!(homes, "asdfjkl;");
+    }
 
-        GEN.fast_layout(&layout, &[])
-    });
+    #[test]
+    fn configurable_geometry() {
+        let oxeylyzer = oxeylyzer_with(|config, _| {
+            config.geometry.scissor_dy = 10.0;
+            config.geometry.lsb_dx = 10.0;
+            config.geometry.stretch_per_finger = 10.0;
+        });
+
+        let strict = oxeylyzer.fast_layout(&Layout::from(QWERTY.clone()), &[]);
+
+        assert!(!QWERTY.scissor_indices.pairs.is_empty());
+        assert!(!QWERTY.lsb_indices.pairs.is_empty());
+        assert!(!QWERTY.stretch_indices.all_pairs.is_empty());
+
+        assert!(strict.scissor_indices.pairs.is_empty());
+        assert!(strict.lsb_indices.pairs.is_empty());
+        assert!(strict.stretch_indices.all_pairs.is_empty());
+        assert_eq!(strict.fspeed_indices, QWERTY.fspeed_indices);
+    }
 
     #[test]
     fn test_key_dist() {
@@ -1085,7 +1575,7 @@ mod tests {
             .parse::<PhysicalKey>()
             .expect("couldn't create k2");
 
-        let d = dist(&k1, &k2, Finger::RP, Finger::RP);
+        let d = finger_dist(&k1, &k2, Finger::RP, &Geometry::default());
 
         approx::assert_abs_diff_eq!(d, 2f64.sqrt(), epsilon = 1e-9);
     }
@@ -1116,7 +1606,7 @@ mod tests {
             .parse::<PhysicalKey>()
             .expect("couldn't create k2");
 
-        let d = dist(&k1, &k2, Finger::LP, Finger::LP);
+        let d = finger_dist(&k1, &k2, Finger::LP, &Geometry::default());
 
         approx::assert_abs_diff_eq!(d, 1.0, epsilon = 1e-9);
 
@@ -2,16 +2,20 @@ use std::sync::Arc;
 
 use ahash::AHashMap as HashMap;
 use itertools::Itertools;
-use libdof::prelude::Finger;
+use libdof::prelude::{Finger, Hand};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
+use crate::Result;
 use crate::analyzer_data::AnalyzerData;
 use crate::char_mapping::CharMapping;
 use crate::data::Data;
 use crate::fast_layout::*;
 use crate::layout::{Layout, PosPair};
-use crate::trigram_patterns::{TrigramPattern, get_trigram_combinations};
-use crate::weights::{AnalyzerWeights, Config};
+use crate::quadgram_patterns::{QuadgramPattern, get_quadgram_combinations};
+use crate::timing::{TimingConfig, TimingTarget};
+use crate::trace::OptimizationTrace;
+use crate::trigram_patterns::{TrigramCategory, TrigramPattern};
+use crate::weights::{AnalyzerWeights, Config, Geometry};
 
 /// Data structure for holding character frequencies.
 ///
@@ -40,8 +44,20 @@ pub type BigramData = Box<[f64]>;
 /// ```
 pub type TrigramData = Box<[([u8; 3], i64)]>;
 
+/// Data structure for holding quadgram frequencies.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::generate::QuadgramData;
+/// let data: QuadgramData = Box::new([]);
+/// ```
+pub type QuadgramData = Box<[([u8; 4], i64)]>;
+
 pub(crate) const SMALLEST_SCORE: i64 = i64::MIN;
 
+/// Maximum amount of swaps a single optimization run is allowed to make.
+pub const MAX_SWAPS: usize = 200;
+
 #[cfg(test)]
 static ANALYZED_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
@@ -60,13 +76,17 @@ pub struct TrigramAccumulator {
     sfbs: i64,
     bad_sfbs: i64,
     sfts: i64,
+    thumb_alternates: i64,
+    thumb_rolls: i64,
+    thumb_redirects: i64,
     thumbs: i64,
     other: i64,
     invalid: i64,
+    custom: Vec<i64>,
 }
 
 impl TrigramAccumulator {
-    fn to_stats(&self, trigram_total: i64) -> TrigramStats {
+    fn to_stats(&self, trigram_total: i64, categories: &[TrigramCategory]) -> TrigramStats {
         let div_total = |stat| ((stat as f64) / (trigram_total as f64)) * 100.0;
 
         TrigramStats {
@@ -82,9 +102,17 @@ impl TrigramAccumulator {
             sfbs: div_total(self.sfbs),
             bad_sfbs: div_total(self.bad_sfbs),
             sfts: div_total(self.sfts),
+            thumb_alternates: div_total(self.thumb_alternates),
+            thumb_rolls: div_total(self.thumb_rolls),
+            thumb_redirects: div_total(self.thumb_redirects),
             thumbs: div_total(self.thumbs),
             other: div_total(self.other),
             invalid: div_total(self.invalid),
+            custom: categories
+                .iter()
+                .zip(&self.custom)
+                .map(|(c, &freq)| (c.name.clone(), div_total(freq)))
+                .collect(),
         }
     }
 }
@@ -116,12 +144,116 @@ pub struct TrigramStats {
     pub bad_sfbs: f64,
     /// Same-finger trigrams.
     pub sfts: f64,
-    /// Trigrams involving the thumb.
+    /// Alternating hand trigrams involving a thumb.
+    pub thumb_alternates: f64,
+    /// Rolling trigrams involving a thumb.
+    pub thumb_rolls: f64,
+    /// Redirecting trigrams involving a thumb.
+    pub thumb_redirects: f64,
+    /// Other trigrams involving a thumb.
     pub thumbs: f64,
     /// Other trigram patterns not explicitly categorized.
     pub other: f64,
     /// Invalid trigram sequences.
     pub invalid: f64,
+    /// User-defined categories, by name.
+    pub custom: Vec<(String, f64)>,
+}
+
+#[derive(Clone, Default)]
+/// Accumulates frequencies for different quadgram patterns during analysis.
+pub struct QuadgramAccumulator {
+    rolls: i64,
+    chained_alternates: i64,
+    double_redirects: i64,
+    other: i64,
+    invalid: i64,
+}
+
+impl QuadgramAccumulator {
+    fn to_stats(&self, quadgram_total: i64) -> QuadgramStats {
+        let div_total = |stat| match quadgram_total {
+            0 => 0.0,
+            total => ((stat as f64) / (total as f64)) * 100.0,
+        };
+
+        QuadgramStats {
+            rolls: div_total(self.rolls),
+            chained_alternates: div_total(self.chained_alternates),
+            double_redirects: div_total(self.double_redirects),
+            other: div_total(self.other),
+            invalid: div_total(self.invalid),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+/// Statistics for different quadgram patterns in a layout. Everything is 0 if the corpus
+/// doesn't contain quadgrams.
+pub struct QuadgramStats {
+    /// One-handed quadgrams rolling in a single direction.
+    pub rolls: f64,
+    /// Quadgrams alternating hands on every stroke.
+    pub chained_alternates: f64,
+    /// One-handed quadgrams changing direction twice.
+    pub double_redirects: f64,
+    /// Other quadgram patterns not explicitly categorized.
+    pub other: f64,
+    /// Invalid quadgram sequences.
+    pub invalid: f64,
+}
+
+#[derive(Clone, Default)]
+/// Statistics for bigram level rolls and hand alternation in a layout. Percentages are relative
+/// to all bigrams in the corpus. Bigrams that repeat the same key aren't counted.
+pub struct BigramStats {
+    /// One-handed bigrams moving towards the index finger, indexed by the distance between
+    /// the two fingers minus one. Bigrams using a thumb aren't rolls.
+    pub inrolls: [f64; 3],
+    /// One-handed bigrams moving towards the pinky, indexed like `inrolls`.
+    pub outrolls: [f64; 3],
+    /// Bigrams typed entirely with one hand, including same finger bigrams.
+    pub same_hand: f64,
+    /// Bigrams that switch hands.
+    pub cross_hand: f64,
+}
+
+impl BigramStats {
+    /// Total of inward rolls over all finger distances.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::generate::BigramStats;
+    /// let stats = BigramStats {
+    ///     inrolls: [3.0, 2.0, 1.0],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(stats.total_inrolls(), 6.0);
+    /// ```
+    pub fn total_inrolls(&self) -> f64 {
+        self.inrolls.iter().sum()
+    }
+
+    /// Total of outward rolls over all finger distances.
+    pub fn total_outrolls(&self) -> f64 {
+        self.outrolls.iter().sum()
+    }
+
+    /// Rolls between adjacent fingers, in either direction.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::generate::BigramStats;
+    /// let stats = BigramStats {
+    ///     inrolls: [3.0, 2.0, 1.0],
+    ///     outrolls: [1.5, 1.0, 0.5],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(stats.adjacent_rolls(), 4.5);
+    /// ```
+    pub fn adjacent_rolls(&self) -> f64 {
+        self.inrolls[0] + self.outrolls[0]
+    }
 }
 
 #[derive(Clone)]
@@ -143,12 +275,31 @@ pub struct LayoutStats {
     pub stretches: f64,
     /// Penalty for uncomfortable pinky-ring combinations.
     pub pinky_ring: f64,
+    /// Statistics for bigram rolls and hand alternation.
+    pub bigram_stats: BigramStats,
     /// Detailed statistics for trigram patterns.
     pub trigram_stats: TrigramStats,
+    /// Detailed statistics for quadgram patterns.
+    pub quadgram_stats: QuadgramStats,
     /// Penalty for finger speed issues.
     pub fspeed: f64,
     /// Finger speed penalties for each finger.
     pub finger_speed: [f64; 10],
+    /// Finger speed penalties of same finger bigrams and of skipgrams at distance 1, 2 and 3,
+    /// which together add up to `fspeed`.
+    pub fspeed_per_distance: [f64; 4],
+    /// Average distance fingers travel per keystroke, in key widths.
+    pub travel: f64,
+    /// Distance each finger travels per keystroke, in key widths.
+    pub finger_travel: [f64; 10],
+    /// Average effort per keystroke according to the configured key effort.
+    pub effort: f64,
+    /// Percentage of keystrokes typed with the left and right hand.
+    pub hand_usage: [f64; 2],
+    /// Percentage of keystrokes on every row, indexed like [`Row::ROWS`].
+    pub row_usage: [f64; 4],
+    /// Estimated average time per bigram in milliseconds, if bigram timings are configured.
+    pub typing_time: Option<f64>,
     /// The total score for the layout.
     pub score: i64,
 }
@@ -171,8 +322,16 @@ pub struct LayoutCache {
     fspeed: [i64; 10],
     fspeed_total: i64,
 
+    travel: [i64; 10],
+    travel_total: i64,
+
+    effort_total: i64,
+
+    hand_balance: i64,
+
     stretch_total: i64,
     trigrams_total: i64,
+    quadgrams_total: i64,
 }
 
 impl LayoutCache {
@@ -186,14 +345,20 @@ impl LayoutCache {
     /// ```
     pub fn total_score(&self) -> i64 {
         self.trigrams_total
+            + self.quadgrams_total
             + self.pinky_ring
             + self.stretch_total
             + self.usage_total
             + self.fspeed_total
+            + self.travel_total
+            + self.effort_total
+            + self.hand_balance
     }
 }
 
-type PerCharTrigrams = HashMap<[u8; 2], TrigramData>;
+type PerCharNgrams<const N: usize> = HashMap<[u8; 2], Box<[([u8; N], i64)]>>;
+type PerCharTrigrams = PerCharNgrams<3>;
+type PerCharQuadgrams = PerCharNgrams<4>;
 
 /// The main entry point for layout generation and analysis.
 ///
@@ -204,7 +369,7 @@ type PerCharTrigrams = HashMap<[u8; 2], TrigramData>;
 /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, weights::Config};
 /// let data = Data::default();
 /// let config = Config::with_defaults();
-/// let oxeylyzer = Oxeylyzer::new(data, config);
+/// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
 /// assert_eq!(oxeylyzer.language, "");
 /// ```
 pub struct Oxeylyzer {
@@ -218,42 +383,92 @@ pub struct Oxeylyzer {
     pub trigram_precision: usize,
     /// Pre-calculated trigram patterns.
     pub trigram_patterns: Arc<[TrigramPattern; 1000]>,
+    /// User-defined trigram categories, indexed by [`TrigramPattern::Custom`].
+    pub trigram_categories: Arc<[TrigramCategory]>,
+
+    custom_trigram_weights: Box<[i64]>,
 
     per_char_trigrams: PerCharTrigrams,
 
+    /// Amount of most frequent quadgrams used for scoring. 0 disables quadgram analysis.
+    pub quadgram_precision: usize,
+    /// Pre-calculated quadgram patterns.
+    pub quadgram_patterns: Arc<[QuadgramPattern; 10000]>,
+
+    per_char_quadgrams: PerCharQuadgrams,
+
     /// Weights used for scoring layouts.
     pub weights: AnalyzerWeights,
+    /// Thresholds and hand measurements used to build the indices of every layout.
+    pub geometry: Geometry,
+    /// Measured bigram timings, used to estimate typing time and optionally as finger speed or
+    /// stretch distances.
+    pub timing: Option<TimingConfig>,
 }
 
 impl Oxeylyzer {
-    /// Creates a new `Oxeylyzer` instance with the provided data and configuration.
+    /// Creates a new `Oxeylyzer` instance with the provided data and configuration. Fails if the
+    /// trigram rules of the configuration refer to a finger or category that doesn't exist.
     ///
     /// # Examples:
     /// ```
     /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, weights::Config};
     /// let data = Data::new();
     /// let config = Config::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     /// assert_eq!(oxeylyzer.language, "");
     /// ```
-    pub fn new(data: Data, config: Config) -> Self
-where {
+    pub fn new(data: Data, config: Config) -> Result<Self> {
         let data = AnalyzerData::new(data, &config.weights);
 
-        Self {
+        Self::with_analyzer_data(data, config)
+    }
+
+    /// Creates a new `Oxeylyzer` instance with data that was already converted for the weights
+    /// of the configuration, like the data from [`AnalyzerData::load`]. Fails if the trigram
+    /// rules of the configuration refer to a finger or category that doesn't exist.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{analyzer_data::AnalyzerData, generate::Oxeylyzer, weights::Config};
+    /// let config = Config::default();
+    /// let data = AnalyzerData::default();
+    /// let oxeylyzer = Oxeylyzer::with_analyzer_data(data, config).unwrap();
+    /// assert_eq!(oxeylyzer.language, "");
+    /// ```
+    pub fn with_analyzer_data(data: AnalyzerData, config: Config) -> Result<Self> {
+        let trigram_patterns = config.trigram_rules.pattern_table()?;
+        let trigram_categories = config.trigram_rules.categories.clone();
+        let custom_trigram_weights = trigram_categories
+            .iter()
+            .map(|c| (c.weight * 100.0) as i64)
+            .collect();
+
+        Ok(Self {
             language: data.name().to_string(),
-            per_char_trigrams: Self::per_char_trigrams(
+            per_char_trigrams: Self::per_char_ngrams(
                 data.gen_trigrams(),
                 data.len() as u8,
                 config.trigram_precision(),
             ),
+            per_char_quadgrams: Self::per_char_ngrams(
+                data.gen_quadgrams(),
+                data.len() as u8,
+                config.quadgram_precision(),
+            ),
+            quadgram_precision: config.quadgram_precision(),
+            quadgram_patterns: get_quadgram_combinations(),
             mapping: data.mapping.clone(),
             trigram_precision: config.trigram_precision(),
-            trigram_patterns: get_trigram_combinations(),
+            trigram_patterns,
+            trigram_categories: trigram_categories.into(),
+            custom_trigram_weights,
             data,
 
             weights: config.weights.into(),
-        }
+            geometry: config.geometry,
+            timing: config.timing,
+        })
     }
 
     /// Converts a standard [`Layout`] to a [`FastLayout`].
@@ -263,7 +478,7 @@ where {
     /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
     /// let config = Config::with_defaults();
     /// let data = Data::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     ///
     /// let stronk = serde_json::from_str::<Layout>(include_str!(
     ///     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
@@ -300,16 +515,39 @@ where {
             .enumerate()
             .for_each(|(i, &c)| char_to_finger[c as usize] = Some(matrix_fingers[i]));
 
-        let fspeed_indices = FSpeedIndices::new(
+        let fspeed_indices = match self.timing_for(TimingTarget::Fspeed) {
+            Some(table) => {
+                FSpeedIndices::from_timing(&matrix_fingers, table, &self.weights.finger_weights)
+            }
+            None => FSpeedIndices::new(
+                &matrix_fingers,
+                &matrix_physical,
+                &self.weights.finger_weights,
+                &self.geometry,
+            ),
+        };
+        let scissor_indices =
+            ScissorIndices::new(&matrix_fingers, &matrix_physical, &self.geometry);
+        let lsb_indices = LsbIndices::new(&matrix_fingers, &matrix_physical, &self.geometry);
+        let pinky_ring_indices = PinkyRingIndices::new(&matrix_fingers);
+        let stretch_indices = match self.timing_for(TimingTarget::Stretches) {
+            Some(table) => StretchIndices::from_timing(&matrix_fingers, table),
+            None => StretchIndices::new(
+                &layout.keys,
+                &matrix_fingers,
+                &matrix_physical,
+                &self.geometry,
+            ),
+        };
+        let usage_indices = UsageIndices::new(&matrix_fingers);
+        let row_indices = RowIndices::new(&matrix_fingers, &matrix_physical);
+        let travel_indices = TravelIndices::new(&matrix_fingers, &matrix_physical, &self.geometry);
+        let effort_indices = EffortIndices::new(
             &matrix_fingers,
-            &matrix_physical,
+            &travel_indices,
+            &self.weights.key_effort,
             &self.weights.finger_weights,
         );
-        let scissor_indices = ScissorIndices::new(&matrix_fingers, &matrix_physical);
-        let lsb_indices = LsbIndices::new(&matrix_fingers, &matrix_physical);
-        let pinky_ring_indices = PinkyRingIndices::new(&matrix_fingers);
-        let stretch_indices = StretchIndices::new(&layout.keys, &matrix_fingers, &matrix_physical);
-        let usage_indices = UsageIndices::new(&matrix_fingers);
 
         FastLayout {
             name,
@@ -323,6 +561,9 @@ where {
             pinky_ring_indices,
             stretch_indices,
             usage_indices,
+            row_indices,
+            travel_indices,
+            effort_indices,
             possible_swaps,
             metadata,
             mapping,
@@ -340,7 +581,7 @@ where {
     /// # )).unwrap();
     /// let config = Config::with_defaults();
     /// let data = Data::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
     ///
     /// let stats = oxeylyzer.get_layout_stats(&fast_layout);
@@ -356,6 +597,20 @@ where {
         let finger_speed = cache
             .fspeed
             .map(|v| (v as f64 / self.data.bigram_total as f64) / 10.0);
+        let fspeed_per_distance = self
+            .fspeed_per_distance(layout)
+            .map(|v| (v / self.data.bigram_total as f64) / 10.0);
+
+        let finger_travel = Finger::FINGERS.map(|f| {
+            (self.finger_travel_distance(layout, f) as f64 / self.data.char_total as f64) / 100.0
+        });
+        let travel = finger_travel.iter().sum();
+
+        let effort = (self.effort_sum(layout) as f64 / self.data.char_total as f64) / 100.0;
+
+        let percent = |v: i64| v as f64 / self.data.char_total as f64 * 100.0;
+        let hand_usage = self.hand_usage(layout).map(percent);
+        let row_usage = self.row_usage(layout).map(percent);
 
         let stretches = (self.stretch_score(layout) as f64 / self.data.bigram_total as f64) * 10.0;
         let scissors =
@@ -365,9 +620,14 @@ where {
             / self.weights.pinky_ring_bigrams as f64)
             / self.data.bigram_total as f64)
             * 100.0;
+        let bigram_stats = self.bigram_stats(layout);
         let trigram_stats = self
             .trigram_stats(layout, usize::MAX)
-            .to_stats(self.data.trigram_total);
+            .to_stats(self.data.trigram_total, &self.trigram_categories);
+        let quadgram_stats = self
+            .quadgram_stats(layout, usize::MAX)
+            .to_stats(self.data.quadgram_total);
+        let typing_time = self.typing_time(layout);
         let score = self.score_with_precision(layout, usize::MAX);
 
         LayoutStats {
@@ -377,15 +637,54 @@ where {
             dsfb3,
             fspeed,
             finger_speed,
+            fspeed_per_distance,
+            travel,
+            finger_travel,
+            effort,
+            hand_usage,
+            row_usage,
+            typing_time,
             stretches,
             scissors,
             lsbs,
             pinky_ring,
+            bigram_stats,
             trigram_stats,
+            quadgram_stats,
             score,
         }
     }
 
+    /// Splits the finger speed penalty of a layout into same finger bigrams and skipgrams at
+    /// distance 1, 2 and 3, weighted like they are in [`LayoutCache`].
+    fn fspeed_per_distance(&self, layout: &FastLayout) -> [f64; 4] {
+        let len = self.data.len();
+        let sfbs = self.weights.sfbs as f64 / 100.0;
+        let [r1, r2, r3] = self.weights.dsfb_ratios;
+        let ngrams = [
+            (self.data.bigrams(), 1.0),
+            (self.data.skipgrams(), r1),
+            (self.data.skipgrams2(), r2),
+            (self.data.skipgrams3(), r3),
+        ];
+
+        let mut res = [0.0; 4];
+
+        for BigramPair { pair, dist } in &layout.fspeed_indices.all {
+            let c1 = layout.char(pair.0).unwrap() as usize;
+            let c2 = layout.char(pair.1).unwrap() as usize;
+
+            for (r, (data, ratio)) in res.iter_mut().zip(ngrams) {
+                let freq = data.get(c1 * len + c2).copied().unwrap_or_default()
+                    + data.get(c2 * len + c1).copied().unwrap_or_default();
+
+                *r += freq as f64 * ratio * sfbs * *dist as f64;
+            }
+        }
+
+        res
+    }
+
     fn bigram_percent(&self, layout: &FastLayout, data: &[i64], total: i64) -> f64 {
         let mut res = 0;
         let len = self.data.len();
@@ -412,7 +711,7 @@ where {
     /// # )).unwrap();
     /// let config = Config::with_defaults();
     /// let data = Data::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
     ///
     /// let trigram = [
@@ -459,7 +758,7 @@ where {
     /// # )).unwrap();
     /// let config = Config::with_defaults();
     /// let data = Data::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
     ///
     /// let stats = oxeylyzer.trigram_stats(&fast_layout, 1000);
@@ -471,7 +770,10 @@ where {
     ) -> TrigramAccumulator {
         use TrigramPattern::*;
 
-        let mut freqs = TrigramAccumulator::default();
+        let mut freqs = TrigramAccumulator {
+            custom: vec![0; self.trigram_categories.len()],
+            ..Default::default()
+        };
 
         for (trigram, freq) in self.data.gen_trigrams().iter().take(trigram_precision) {
             match self.get_trigram_pattern(layout, trigram) {
@@ -487,14 +789,151 @@ where {
                 Sfb => freqs.sfbs += freq,
                 BadSfb => freqs.bad_sfbs += freq,
                 Sft => freqs.sfts += freq,
+                ThumbAlternate => freqs.thumb_alternates += freq,
+                ThumbRoll => freqs.thumb_rolls += freq,
+                ThumbRedirect => freqs.thumb_redirects += freq,
                 Thumb => freqs.thumbs += freq,
                 Other => freqs.other += freq,
                 Invalid => freqs.invalid += freq,
+                Custom(i) => freqs.custom[i as usize] += freq,
             }
         }
         freqs
     }
 
+    /// Gets the quadgram pattern for four characters.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
+    /// # fn thing() -> Option<()> {
+    /// # let stronk = serde_json::from_str::<Layout>(include_str!(
+    /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
+    /// # )).unwrap();
+    /// let config = Config::with_defaults();
+    /// let data = Data::default();
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
+    /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
+    ///
+    /// let quadgram = [
+    ///     fast_layout.char(0)?,
+    ///     fast_layout.char(1)?,
+    ///     fast_layout.char(2)?,
+    ///     fast_layout.char(3)?,
+    /// ];
+    /// let pattern = oxeylyzer.get_quadgram_pattern(&fast_layout, &quadgram);
+    /// # Some(())
+    /// # }
+    /// # fn main() {
+    /// #   thing().unwrap();
+    /// # }
+    /// ```
+    pub fn get_quadgram_pattern(&self, layout: &FastLayout, quadgram: &[u8; 4]) -> QuadgramPattern {
+        let mut index = 0;
+
+        for &c in quadgram {
+            match layout.char_to_finger.get(c as usize) {
+                Some(&Some(f)) => index = index * 10 + f as usize,
+                _ => return QuadgramPattern::Invalid,
+            }
+        }
+
+        self.quadgram_patterns[index]
+    }
+
+    /// Computes quadgram statistics for a layout. This is empty if the corpus doesn't contain
+    /// quadgrams.
+    pub fn quadgram_stats(
+        &self,
+        layout: &FastLayout,
+        quadgram_precision: usize,
+    ) -> QuadgramAccumulator {
+        use QuadgramPattern::*;
+
+        let mut freqs = QuadgramAccumulator::default();
+
+        for (quadgram, freq) in self.data.gen_quadgrams().iter().take(quadgram_precision) {
+            match self.get_quadgram_pattern(layout, quadgram) {
+                Roll => freqs.rolls += freq,
+                ChainedAlternate => freqs.chained_alternates += freq,
+                DoubleRedirect => freqs.double_redirects += freq,
+                Other => freqs.other += freq,
+                Invalid => freqs.invalid += freq,
+            }
+        }
+        freqs
+    }
+
+    /// Computes bigram roll and hand alternation statistics for a layout.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
+    /// # let stronk = serde_json::from_str::<Layout>(include_str!(
+    /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
+    /// # )).unwrap();
+    /// let data = Data::load(concat!(
+    ///     std::env!("CARGO_MANIFEST_DIR"),
+    ///     "/../static/language_data/english.json"
+    /// )).unwrap();
+    /// let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
+    /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
+    ///
+    /// let stats = oxeylyzer.bigram_stats(&fast_layout);
+    /// assert!(stats.same_hand + stats.cross_hand <= 100.0);
+    /// ```
+    pub fn bigram_stats(&self, layout: &FastLayout) -> BigramStats {
+        let mut inrolls = [0; 3];
+        let mut outrolls = [0; 3];
+        let mut same_hand = 0;
+        let mut cross_hand = 0;
+
+        for (p1, &c1) in layout.keys.iter().enumerate() {
+            for (p2, &c2) in layout.keys.iter().enumerate() {
+                if p1 == p2 {
+                    continue;
+                }
+
+                let freq = self.data.get_bigram_u([c1, c2]);
+                let (f1, f2) = (layout.fingers[p1], layout.fingers[p2]);
+
+                if f1.hand() != f2.hand() {
+                    cross_hand += freq;
+                    continue;
+                }
+
+                same_hand += freq;
+
+                if f1 == f2 || f1.is_thumb() || f2.is_thumb() {
+                    continue;
+                }
+
+                let distance = (f1 as usize).abs_diff(f2 as usize) - 1;
+                let inward = match f1.hand() {
+                    Hand::Left => (f1 as u8) < (f2 as u8),
+                    Hand::Right => (f1 as u8) > (f2 as u8),
+                };
+
+                match inward {
+                    true => inrolls[distance] += freq,
+                    false => outrolls[distance] += freq,
+                }
+            }
+        }
+
+        let percent = |v: i64| match self.data.bigram_total {
+            0 => 0.0,
+            total => v as f64 / total as f64 * 100.0,
+        };
+
+        BigramStats {
+            inrolls: inrolls.map(percent),
+            outrolls: outrolls.map(percent),
+            same_hand: percent(same_hand),
+            cross_hand: percent(cross_hand),
+        }
+    }
+
     /// Calculates the total score for a given layout.
     ///
     /// Higher scores generally indicate better layouts according to the weights.
@@ -507,7 +946,7 @@ where {
     /// # )).unwrap();
     /// let config = Config::with_defaults();
     /// let data = Data::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
     ///
     /// let score = oxeylyzer.score(&fast_layout);
@@ -518,13 +957,20 @@ where {
 
         let fspeed_usage = Finger::FINGERS
             .into_iter()
-            .map(|f| self.finger_usage(layout, f) + self.finger_fspeed(layout, f))
+            .map(|f| {
+                self.finger_usage(layout, f)
+                    + self.finger_fspeed(layout, f)
+                    + self.finger_travel(layout, f)
+            })
             .sum::<i64>();
 
         let pinky_ring = self.pinky_ring_score(layout);
+        let effort = self.effort_score(layout);
+        let hand_balance = self.hand_balance_score(layout);
         let trigram_score = self.trigram_score_iter(layout, self.data.gen_trigrams());
+        let quadgram_score = self.quadgram_score(layout);
 
-        trigram_score + fspeed_usage + pinky_ring
+        trigram_score + quadgram_score + fspeed_usage + pinky_ring + effort + hand_balance
     }
 
     /// Calculates the total score for a given layout.
@@ -539,7 +985,7 @@ where {
     /// # )).unwrap();
     /// let config = Config::with_defaults();
     /// let data = Data::default();
-    /// let oxeylyzer = Oxeylyzer::new(data, config);
+    /// let oxeylyzer = Oxeylyzer::new(data, config).unwrap();
     /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
     ///
     /// let score = oxeylyzer.score_with_precision(&fast_layout, 1000);
@@ -547,36 +993,53 @@ where {
     pub fn score_with_precision(&self, layout: &FastLayout, trigram_precision: usize) -> i64 {
         let fspeed_usage = Finger::FINGERS
             .into_iter()
-            .map(|f| self.finger_usage(layout, f) + self.finger_fspeed(layout, f))
+            .map(|f| {
+                self.finger_usage(layout, f)
+                    + self.finger_fspeed(layout, f)
+                    + self.finger_travel(layout, f)
+            })
             .sum::<i64>();
 
         let pinky_ring = self.pinky_ring_score(layout);
 
         let trigram_iter = self.data.gen_trigrams().iter().take(trigram_precision);
         let trigram_score = self.trigram_score_iter(layout, trigram_iter);
+        let quadgram_score = self.quadgram_score(layout);
         let stretch_score = self.stretch_score(layout);
-
-        trigram_score + stretch_score + fspeed_usage + pinky_ring
+        let effort = self.effort_score(layout);
+        let hand_balance = self.hand_balance_score(layout);
+
+        trigram_score
+            + quadgram_score
+            + stretch_score
+            + fspeed_usage
+            + pinky_ring
+            + effort
+            + hand_balance
     }
 
-    fn per_char_trigrams(
-        trigrams: &[([u8; 3], i64)],
+    fn per_char_ngrams<const N: usize>(
+        ngrams: &[([u8; N], i64)],
         highest: u8,
-        trigram_precision: usize,
-    ) -> HashMap<[u8; 2], TrigramData> {
-        let mut n_trigrams = trigrams.to_vec();
-        n_trigrams.truncate(trigram_precision);
+        precision: usize,
+    ) -> PerCharNgrams<N> {
+        if precision == 0 {
+            return HashMap::default();
+        }
+
+        let mut n_ngrams = ngrams.to_vec();
+        n_ngrams.truncate(precision);
 
         (0..highest)
             .cartesian_product(0..highest)
             .map(|(c1, c2)| {
-                let v1 = n_trigrams
+                let v1 = n_ngrams
                     .iter()
                     .map(|(t, f)| (*t, *f))
                     .filter(|(t, _)| t.contains(&c1))
                     .collect::<Vec<_>>();
 
-                let v2 = n_trigrams
+                let v2 = n_ngrams
                     .iter()
                     .map(|(t, f)| (*t, *f))
                     .filter(|(t, _)| t.contains(&c2))
@@ -600,6 +1063,7 @@ where {
         use TrigramPattern::*;
 
         let mut freqs = TrigramAccumulator::default();
+        let mut custom = 0;
 
         for (trigram, freq) in trigrams {
             match self.get_trigram_pattern(layout, trigram) {
@@ -614,14 +1078,18 @@ where {
                 BadRedirectSfs => freqs.bad_redirects_sfs += freq,
                 Sfb => {}
                 BadSfb => {}
+                ThumbAlternate => freqs.thumb_alternates += freq,
+                ThumbRoll => freqs.thumb_rolls += freq,
+                ThumbRedirect => freqs.thumb_redirects += freq,
                 Sft => {}
                 Thumb => {}
                 Other => {}
                 Invalid => {}
+                Custom(i) => custom += self.custom_trigram_weights[i as usize] * freq,
             }
         }
 
-        let mut score = 0;
+        let mut score = custom;
         score += self.weights.inrolls * freqs.inrolls;
         score += self.weights.outrolls * freqs.outrolls;
         score += self.weights.onehands * freqs.onehands;
@@ -631,6 +1099,9 @@ where {
         score += self.weights.redirects_sfs * freqs.redirects_sfs;
         score += self.weights.bad_redirects * freqs.bad_redirects;
         score += self.weights.bad_redirects_sfs * freqs.bad_redirects_sfs;
+        score += self.weights.thumb_alternates * freqs.thumb_alternates;
+        score += self.weights.thumb_rolls * freqs.thumb_rolls;
+        score += self.weights.thumb_redirects * freqs.thumb_redirects;
         score
     }
 
@@ -645,6 +1116,53 @@ where {
         }
     }
 
+    #[inline]
+    fn quadgram_score_iter<'a, T>(&self, layout: &FastLayout, quadgrams: T) -> i64
+    where
+        T: IntoIterator<Item = &'a ([u8; 4], i64)>,
+    {
+        use QuadgramPattern::*;
+
+        let mut freqs = QuadgramAccumulator::default();
+
+        for (quadgram, freq) in quadgrams {
+            match self.get_quadgram_pattern(layout, quadgram) {
+                Roll => freqs.rolls += freq,
+                ChainedAlternate => freqs.chained_alternates += freq,
+                DoubleRedirect => freqs.double_redirects += freq,
+                Other => {}
+                Invalid => {}
+            }
+        }
+
+        let mut score = 0;
+        score += self.weights.quadgram_rolls * freqs.rolls;
+        score += self.weights.chained_alternates * freqs.chained_alternates;
+        score += self.weights.double_redirects * freqs.double_redirects;
+        score
+    }
+
+    fn quadgram_score(&self, layout: &FastLayout) -> i64 {
+        self.quadgram_score_iter(
+            layout,
+            self.data
+                .gen_quadgrams()
+                .iter()
+                .take(self.quadgram_precision),
+        )
+    }
+
+    fn quadgram_char_score(&self, layout: &FastLayout, &PosPair(p1, p2): &PosPair) -> i64 {
+        if let Some(c1) = layout.char(p1)
+            && let Some(c2) = layout.char(p2)
+            && let Some(q_vec) = self.per_char_quadgrams.get(&[c1, c2])
+        {
+            self.quadgram_score_iter(layout, q_vec)
+        } else {
+            0
+        }
+    }
+
     #[inline]
     fn scissor_percent(&self, layout: &FastLayout) -> i64 {
         layout
@@ -699,13 +1217,83 @@ where {
             .sum()
     }
 
-    fn finger_usage(&self, layout: &FastLayout, finger: Finger) -> i64 {
-        let usage = layout
-            .usage_indices
-            .get(finger)
+    fn effort_sum(&self, layout: &FastLayout) -> i64 {
+        layout
+            .keys
+            .iter()
+            .enumerate()
+            .map(|(p, &c)| self.data.get_char_u(c) * layout.effort_indices.get(p))
+            .sum()
+    }
+
+    fn effort_score(&self, layout: &FastLayout) -> i64 {
+        match self.weights.key_effort.weight {
+            0 => 0,
+            _ => (0..layout.keys.len())
+                .map(|p| self.key_effort(layout, p))
+                .sum(),
+        }
+    }
+
+    #[inline]
+    fn key_effort(&self, layout: &FastLayout, pos: usize) -> i64 {
+        let c = layout.keys[pos];
+
+        self.data.get_char_u(c) * layout.effort_indices.get(pos) * self.weights.key_effort.weight
+            / 100
+    }
+
+    /// Computes the weighted effort of the two keys of a swap.
+    pub fn effort_including_pair(&self, layout: &FastLayout, &PosPair(p1, p2): &PosPair) -> i64 {
+        match self.weights.key_effort.weight {
+            0 => 0,
+            _ => self.key_effort(layout, p1 as usize) + self.key_effort(layout, p2 as usize),
+        }
+    }
+
+    #[inline]
+    fn usage_of(&self, layout: &FastLayout, positions: &[usize]) -> i64 {
+        positions
             .iter()
             .map(|&i| self.data.get_char_u(layout.keys[i]))
-            .sum::<i64>();
+            .sum()
+    }
+
+    /// Computes how often the left and right hand are used over the whole corpus.
+    pub fn hand_usage(&self, layout: &FastLayout) -> [i64; 2] {
+        let mut usage = [0; 2];
+
+        for finger in Finger::FINGERS {
+            let hand = match finger.hand() {
+                Hand::Left => 0,
+                Hand::Right => 1,
+            };
+
+            usage[hand] += self.usage_of(layout, layout.usage_indices.get(finger));
+        }
+
+        usage
+    }
+
+    /// Computes how often every row is used over the whole corpus, indexed like
+    /// [`Row::ROWS`].
+    pub fn row_usage(&self, layout: &FastLayout) -> [i64; 4] {
+        Row::ROWS.map(|row| self.usage_of(layout, layout.row_indices.get(row)))
+    }
+
+    fn hand_balance_score(&self, layout: &FastLayout) -> i64 {
+        match self.weights.hand_balance {
+            0 => 0,
+            weight => {
+                let [left, right] = self.hand_usage(layout);
+
+                (left - right).abs() * weight
+            }
+        }
+    }
+
+    fn finger_usage(&self, layout: &FastLayout, finger: Finger) -> i64 {
+        let usage = self.usage_of(layout, layout.usage_indices.get(finger));
 
         self.weights.max_finger_use.penalty
             * match finger {
@@ -771,6 +1359,39 @@ where {
             .sum()
     }
 
+    /// Computes the distance a finger travels over the whole corpus, in key widths multiplied
+    /// by 100. See [`TravelIndices`] for how this is modeled.
+    pub fn finger_travel_distance(&self, layout: &FastLayout, finger: Finger) -> i64 {
+        let indices = &layout.travel_indices;
+
+        let from_home = indices
+            .from_home(finger)
+            .iter()
+            .map(|&(p, dist)| {
+                let c = layout.keys[p as usize];
+                let repeats = self.data.get_bigram_u([c, c]);
+
+                (self.data.get_char_u(c) - repeats) * dist * 2
+            })
+            .sum::<i64>();
+
+        let saved = indices
+            .pairs(finger)
+            .iter()
+            .map(|pair| self.pair_sfb(layout, pair) * pair.dist)
+            .sum::<i64>();
+
+        from_home - saved
+    }
+
+    #[inline]
+    fn finger_travel(&self, layout: &FastLayout, finger: Finger) -> i64 {
+        match self.weights.finger_travel {
+            0 => 0,
+            weight => self.finger_travel_distance(layout, finger) * weight / 100,
+        }
+    }
+
     /// Computes the stretch penalty including a specific pair.
     pub fn stretches_including_pair(&self, layout: &FastLayout, pair: &PosPair) -> i64 {
         layout
@@ -813,19 +1434,27 @@ where {
         for finger in Finger::FINGERS {
             res.usage[finger as usize] = self.finger_usage(layout, finger);
             res.fspeed[finger as usize] = self.finger_fspeed(layout, finger);
+            res.travel[finger as usize] = self.finger_travel(layout, finger);
         }
         res.usage_total = res.usage.iter().sum();
         res.fspeed_total = res.fspeed.iter().sum();
+        res.travel_total = res.travel.iter().sum();
 
         res.pinky_ring = self.pinky_ring_score(layout);
 
         res.stretch_total = self.stretch_score(layout);
 
+        res.effort_total = self.effort_score(layout);
+
+        res.hand_balance = self.hand_balance_score(layout);
+
         res.trigrams_total = self.trigram_score_iter(
             layout,
             self.data.gen_trigrams().iter().take(self.trigram_precision),
         );
 
+        res.quadgrams_total = self.quadgram_score(layout);
+
         res
     }
 
@@ -880,28 +1509,61 @@ where {
                 + usage2
         };
 
+        let travel_score = if f1 == f2 {
+            let travel = self.finger_travel(layout, f1);
+            cache.travel_total - cache.travel[f1 as usize] + travel
+        } else {
+            let travel1 = self.finger_travel(layout, f1);
+            let travel2 = self.finger_travel(layout, f2);
+            cache.travel_total - cache.travel[f1 as usize] - cache.travel[f2 as usize]
+                + travel1
+                + travel2
+        };
+
         let pinky_ring_score = if layout.pinky_ring_indices.affects_pinky_ring(*swap) {
             self.pinky_ring_score(layout)
         } else {
             cache.pinky_ring
         };
 
-        let (stretch_score, trigrams_score) = {
+        let hand_balance_score = if f1.hand() != f2.hand() {
+            self.hand_balance_score(layout)
+        } else {
+            cache.hand_balance
+        };
+
+        let (stretch_score, trigrams_score, quadgrams_score, effort_score) = {
             let stretch_new = self.stretches_including_pair(layout, swap);
             let trigrams_end = self.trigram_char_score(layout, swap);
+            let quadgrams_end = self.quadgram_char_score(layout, swap);
+            let effort_new = self.effort_including_pair(layout, swap);
 
             layout.swap_pair(swap);
 
             let stretch_old = self.stretches_including_pair(layout, swap);
             let trigrams_start = self.trigram_char_score(layout, swap);
+            let quadgrams_start = self.quadgram_char_score(layout, swap);
+            let effort_old = self.effort_including_pair(layout, swap);
 
             let stretch_score = cache.stretch_total - stretch_old + stretch_new;
             let trigrams_score = cache.trigrams_total - trigrams_start + trigrams_end;
+            let quadgrams_score = cache.quadgrams_total - quadgrams_start + quadgrams_end;
+            let effort_score = cache.effort_total - effort_old + effort_new;
 
-            (stretch_score, trigrams_score)
+            (stretch_score, trigrams_score, quadgrams_score, effort_score)
         };
 
-        Some(trigrams_score + pinky_ring_score + stretch_score + usage_score + fspeed_score)
+        Some(
+            trigrams_score
+                + quadgrams_score
+                + pinky_ring_score
+                + stretch_score
+                + usage_score
+                + fspeed_score
+                + travel_score
+                + effort_score
+                + hand_balance_score,
+        )
     }
 
     /// Accepts a swap and updates the cache according to the new score.
@@ -922,10 +1584,30 @@ where {
             }
         }
 
-        let stretch_start = self.stretches_including_pair(layout, swap);
-        let trigrams_start = self.trigram_char_score(layout, swap);
+        self.accept_change(layout, swap, cache, |layout| {
+            layout.swap_pair(swap).unwrap();
+        })
+    }
+
+    /// Applies a change that only touches the keys on the two positions of `pair`, like a swap
+    /// or placing a key on an empty position, and updates the cache according to the new score.
+    /// The positions of `pair` must be different.
+    pub(crate) fn accept_change(
+        &self,
+        layout: &mut FastLayout,
+        pair: &PosPair,
+        cache: &mut LayoutCache,
+        change: impl FnOnce(&mut FastLayout),
+    ) -> Option<i64> {
+        let PosPair(i1, i2) = *pair;
+        let keys_start = (layout.char(i1)?, layout.char(i2)?);
 
-        layout.swap_pair(swap).unwrap();
+        let stretch_start = self.stretches_including_pair(layout, pair);
+        let trigrams_start = self.trigram_char_score(layout, pair);
+        let quadgrams_start = self.quadgram_char_score(layout, pair);
+        let effort_start = self.effort_including_pair(layout, pair);
+
+        change(layout);
 
         let f1 = layout.finger(i1)?;
         let f2 = layout.finger(i2)?;
@@ -970,16 +1652,46 @@ where {
             total
         };
 
-        let stretch_end = self.stretches_including_pair(layout, swap);
-        let trigrams_end = self.trigram_char_score(layout, swap);
+        cache.travel_total = if f1 == f2 {
+            let travel = self.finger_travel(layout, f1);
+            let total = cache.travel_total - cache.travel[f1 as usize] + travel;
+
+            cache.travel[f1 as usize] = travel;
+
+            total
+        } else {
+            let travel1 = self.finger_travel(layout, f1);
+            let travel2 = self.finger_travel(layout, f2);
+            let total = cache.travel_total - cache.travel[f1 as usize] - cache.travel[f2 as usize]
+                + travel1
+                + travel2;
+
+            cache.travel[f1 as usize] = travel1;
+            cache.travel[f2 as usize] = travel2;
+
+            total
+        };
+
+        let stretch_end = self.stretches_including_pair(layout, pair);
+        let trigrams_end = self.trigram_char_score(layout, pair);
+        let quadgrams_end = self.quadgram_char_score(layout, pair);
+        let effort_end = self.effort_including_pair(layout, pair);
 
         cache.stretch_total = cache.stretch_total - stretch_start + stretch_end;
         cache.trigrams_total = cache.trigrams_total - trigrams_start + trigrams_end;
+        cache.quadgrams_total = cache.quadgrams_total - quadgrams_start + quadgrams_end;
+        cache.effort_total = cache.effort_total - effort_start + effort_end;
 
-        if layout.pinky_ring_indices.affects_pinky_ring(*swap) {
+        if layout.pinky_ring_indices.affects_pinky_ring(*pair) {
             cache.pinky_ring = self.pinky_ring_score(layout);
         }
 
+        // a swap on a single hand keeps the usage of both hands the same
+        let swapped = keys_start == (layout.char(i2)?, layout.char(i1)?);
+        if f1.hand() != f2.hand() || !swapped {
+            cache.hand_balance = self.hand_balance_score(layout);
+        }
+
         Some(cache.total_score())
     }
 
@@ -1008,13 +1720,26 @@ where {
         (best_swap, best_score)
     }
 
-    fn optimize(&self, mut layout: FastLayout) -> FastLayout {
+    pub(crate) fn optimize(&self, layout: FastLayout) -> FastLayout {
+        self.optimize_traced(layout, None)
+    }
+
+    fn optimize_traced(
+        &self,
+        mut layout: FastLayout,
+        mut trace: Option<&mut OptimizationTrace>,
+    ) -> FastLayout {
+        let start = std::time::Instant::now();
         let mut cache = self.initialize_cache(&layout);
 
-        let mut max_swaps = 200; // too high, but makes the system cut off after a while
+        let mut max_swaps = MAX_SWAPS; // too high, but makes the system cut off after a while
         let mut current_best_score = SMALLEST_SCORE;
         let possible_swaps = std::mem::take(&mut layout.possible_swaps);
 
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.initial_score = cache.total_score();
+        }
+
         while let (Some(best_swap), new_score) = self.best_swap_cached(
             &mut layout,
             &cache,
@@ -1025,26 +1750,33 @@ where {
             let accepted_score = self.accept_swap(&mut layout, &best_swap, &mut cache);
             debug_assert_eq!(Some(current_best_score), accepted_score);
 
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.scores.push(current_best_score);
+                trace.swaps += 1;
+            }
+
             max_swaps -= 1;
             if max_swaps == 0 {
                 layout.possible_swaps = possible_swaps;
 
+                if let Some(trace) = trace {
+                    trace.hit_cap = true;
+                    trace.duration = start.elapsed();
+                }
+
                 return layout;
             }
         }
 
-        layout
-    }
+        if let Some(trace) = trace {
+            trace.duration = start.elapsed();
+        }
 
-    /// Generates an optimized layout starting from a basis.
-    pub fn generate(&self, basis: &FastLayout) -> FastLayout {
-        self.generate_with_pins(basis, &[])
+        layout
     }
 
-    /// Generates an optimized layout keeping specific keys pinned to specific positions, starting
-    /// from a basis. The finger map, number of keys and their contents and such are used to create
-    /// the new layout. Returns the optimized [`FastLayout`].
-    pub fn generate_with_pins(&self, based_on: &FastLayout, pins: &[usize]) -> FastLayout {
+    /// Clones `based_on` without the swaps that would move any of the pinned keys.
+    fn without_pinned_swaps(based_on: &FastLayout, pins: &[usize]) -> FastLayout {
         let mut layout = based_on.clone();
 
         if !pins.is_empty() {
@@ -1058,6 +1790,20 @@ where {
                 .collect();
         }
 
+        layout
+    }
+
+    /// Generates an optimized layout starting from a basis.
+    pub fn generate(&self, basis: &FastLayout) -> FastLayout {
+        self.generate_with_pins(basis, &[])
+    }
+
+    /// Generates an optimized layout keeping specific keys pinned to specific positions, starting
+    /// from a basis. The finger map, number of keys and their contents and such are used to create
+    /// the new layout. Returns the optimized [`FastLayout`].
+    pub fn generate_with_pins(&self, based_on: &FastLayout, pins: &[usize]) -> FastLayout {
+        let layout = Self::without_pinned_swaps(based_on, pins);
+
         self.optimize(layout.random_with_pins(pins))
     }
 
@@ -1079,23 +1825,30 @@ where {
         based_on: &FastLayout,
         pins: &'a [usize],
     ) -> impl ParallelIterator<Item = FastLayout> + 'a {
-        let mut layout = based_on.clone();
-
-        if !pins.is_empty() {
-            layout.possible_swaps = layout
-                .possible_swaps
-                .iter()
-                .copied()
-                .filter(|&PosPair(a, b)| {
-                    !pins.contains(&(a as usize)) && !pins.contains(&(b as usize))
-                })
-                .collect();
-        }
+        let layout = Self::without_pinned_swaps(based_on, pins);
 
         (0..amount)
             .into_par_iter()
             .map(move |_| self.optimize(layout.random_with_pins(pins)))
     }
+
+    /// Same as [`generate_n_with_pins_iter`](Self::generate_n_with_pins_iter), but also yields
+    /// an [`OptimizationTrace`] for every optimized layout, describing how it converged.
+    pub fn generate_n_with_pins_traced_iter<'a>(
+        &'a self,
+        amount: usize,
+        based_on: &FastLayout,
+        pins: &'a [usize],
+    ) -> impl ParallelIterator<Item = (FastLayout, OptimizationTrace)> + 'a {
+        let layout = Self::without_pinned_swaps(based_on, pins);
+
+        (0..amount).into_par_iter().map(move |_| {
+            let mut trace = OptimizationTrace::default();
+            let optimized = self.optimize_traced(layout.random_with_pins(pins), Some(&mut trace));
+
+            (optimized, trace)
+        })
+    }
 }
 
 mod obsolete;
@@ -1104,38 +1857,22 @@ mod obsolete;
 mod tests {
     use super::*;
 
-    use once_cell::sync::Lazy;
-    use rayon::iter::ParallelIterator;
-    use std::{collections::HashSet, path::PathBuf, sync::atomic::Ordering};
-
-    static GEN: Lazy<Oxeylyzer> = Lazy::new(|| {
-        let base = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
-        let config = Config::with_loaded_weights(base.join("config.toml")).unwrap();
-        let data = Data::load(base.join(&config.corpus)).unwrap();
-
-        Oxeylyzer::new(data, config)
-    });
-
-    static QWERTY: Lazy<FastLayout> = Lazy::new(|| {
-        let dof_str = r#"
-            {
-                "name": "Qwerty",
-                "board": "ansi",
-                "layers": {
-                    "main": [
-                        "q w e r t  y u i o p",
-                        "a s d f g  h j k l ;",
-                        "z x c v b  n m , . /"
-                    ]
-                },
-                "fingering": "traditional"
-            }
-        "#;
+    use crate::OxeylyzerError;
+    use crate::test_util::{GEN, QWERTY, base_path, oxeylyzer_with};
 
-        let layout = serde_json::from_str::<Layout>(dof_str).unwrap();
+    use rayon::iter::ParallelIterator;
+    use std::{collections::HashSet, sync::atomic::Ordering};
 
-        GEN.fast_layout(&layout, &[])
-    });
+    #[test]
+    fn invalid_trigram_rules() {
+        let mut config = Config::default();
+        config.trigram_rules.bad_fingers = Some(vec!["lx".to_string()]);
+
+        assert!(matches!(
+            Oxeylyzer::new(Data::default(), config),
+            Err(OxeylyzerError::UnknownFinger(_))
+        ));
+    }
 
     #[test]
     fn per_char_trigrams_symmetry() {
@@ -1210,6 +1947,8 @@ mod tests {
 
             assert_eq!(cache.usage_total, GEN.usage_score(&qwerty));
             assert_eq!(cache.fspeed_total, GEN.fspeed_score(&qwerty));
+            assert_eq!(cache.travel_total, GEN.travel_score(&qwerty));
+            assert_eq!(cache.effort_total, GEN.effort_score(&qwerty));
             assert_eq!(cache.stretch_total, GEN.stretch_score(&qwerty));
             assert_eq!(
                 cache.trigrams_total,
@@ -1218,7 +1957,9 @@ mod tests {
                     GEN.data.gen_trigrams().iter().take(GEN.trigram_precision)
                 )
             );
+            assert_eq!(cache.quadgrams_total, GEN.quadgram_score(&qwerty));
             assert_eq!(cache.pinky_ring, GEN.pinky_ring_score(&qwerty));
+            assert_eq!(cache.hand_balance, GEN.hand_balance_score(&qwerty));
             assert_eq!(
                 cache.total_score(),
                 GEN.score_with_precision(&qwerty, GEN.trigram_precision)
@@ -1228,6 +1969,163 @@ mod tests {
         }
     }
 
+    /// Creates an analyzer with `modify` applied to its config and corpus, and checks that the
+    /// cached score `cached` stays in sync with `score` while swapping keys on qwerty.
+    fn assert_cached_score(
+        modify: impl FnOnce(&mut Config, &mut Data),
+        cached: fn(&LayoutCache) -> i64,
+        score: fn(&Oxeylyzer, &FastLayout) -> i64,
+    ) -> Oxeylyzer {
+        let oxeylyzer = oxeylyzer_with(modify);
+
+        let mut qwerty = oxeylyzer.fast_layout(&Layout::from(QWERTY.clone()), &[]);
+        let mut cache = oxeylyzer.initialize_cache(&qwerty);
+
+        assert_ne!(cached(&cache), 0);
+
+        for swap in QWERTY.possible_swaps.iter().take(1_000) {
+            let scored = oxeylyzer.score_swap_cached(&mut qwerty, swap, &cache);
+            let accepted = oxeylyzer.accept_swap(&mut qwerty, swap, &mut cache);
+
+            assert_eq!(scored, accepted);
+            assert_eq!(cached(&cache), score(&oxeylyzer, &qwerty));
+            assert_eq!(oxeylyzer.initialize_cache(&qwerty), cache);
+        }
+
+        oxeylyzer
+    }
+
+    #[test]
+    fn cached_travel() {
+        assert_cached_score(
+            |config, _| config.weights.finger_travel = -2.0,
+            |cache| cache.travel_total,
+            Oxeylyzer::travel_score,
+        );
+    }
+
+    #[test]
+    fn cached_effort() {
+        assert_cached_score(
+            |config, _| {
+                config.weights.key_effort.weight = -0.5;
+                config.weights.key_effort.preset = crate::weights::EffortPreset::FingerStrength;
+            },
+            |cache| cache.effort_total,
+            Oxeylyzer::effort_score,
+        );
+    }
+
+    #[test]
+    fn cached_hand_balance() {
+        assert_cached_score(
+            |config, _| config.weights.hand_balance = -1.0,
+            |cache| cache.hand_balance,
+            Oxeylyzer::hand_balance_score,
+        );
+    }
+
+    #[test]
+    fn qwerty_hand_and_row_usage() {
+        let stats = GEN.get_layout_stats(&QWERTY);
+        let [top, home, bottom, thumb] = stats.row_usage;
+
+        assert!(stats.hand_usage[0] > stats.hand_usage[1]);
+        assert!(top > home && home > bottom);
+        assert_eq!(thumb, 0.0);
+
+        let hands = stats.hand_usage.iter().sum::<f64>();
+        let rows = stats.row_usage.iter().sum::<f64>();
+        assert!((hands - rows).abs() < 1e-9);
+    }
+
+    #[test]
+    fn qwerty_bigram_stats() {
+        let stats = GEN.bigram_stats(&QWERTY);
+        let bigram = |s: &str| {
+            let [c1, c2] = [0, 1].map(|i| GEN.mapping.get_u(s.chars().nth(i).unwrap()));
+            GEN.data.get_bigram_u([c1, c2]) as f64 / GEN.data.bigram_total as f64 * 100.0
+        };
+
+        assert!(stats.same_hand + stats.cross_hand <= 100.0);
+        assert!(stats.total_inrolls() + stats.total_outrolls() < stats.same_hand);
+
+        // 'er' is an inward roll between adjacent fingers, 're' goes the other way
+        assert!(stats.inrolls[0] >= bigram("er"));
+        assert!(stats.outrolls[0] >= bigram("re"));
+        assert!(stats.adjacent_rolls() >= bigram("er") + bigram("re"));
+        // 'ae' skips the ring finger
+        assert!(stats.inrolls[1] >= bigram("ae"));
+    }
+
+    #[test]
+    fn fspeed_distances_add_up() {
+        let stats = GEN.get_layout_stats(&QWERTY);
+        let total = stats.fspeed_per_distance.iter().sum::<f64>();
+
+        assert!(stats.fspeed_per_distance.iter().all(|&v| v <= 0.0));
+        assert!((total - stats.fspeed).abs() < stats.fspeed.abs() * 1e-3);
+    }
+
+    #[test]
+    fn custom_skipgram_ratios() {
+        let oxeylyzer = oxeylyzer_with(|config, _| {
+            config.weights.skipgram_ratios.dsfb = Some(0.0);
+            config.weights.skipgram_ratios.dsfb2 = Some(0.0);
+            config.weights.skipgram_ratios.dsfb3 = Some(0.0);
+        });
+
+        let qwerty = oxeylyzer.fast_layout(&Layout::from(QWERTY.clone()), &[]);
+        let stats = oxeylyzer.get_layout_stats(&qwerty);
+        let default_stats = GEN.get_layout_stats(&QWERTY);
+
+        assert_eq!(stats.fspeed_per_distance[1..], [0.0; 3]);
+        assert_eq!(
+            stats.fspeed_per_distance[0],
+            default_stats.fspeed_per_distance[0]
+        );
+        assert!(stats.fspeed > default_stats.fspeed);
+        // stretches fall back to the finger speed ratios
+        assert!(stats.stretches > default_stats.stretches);
+    }
+
+    #[test]
+    fn cached_quadgrams() {
+        let oxeylyzer = assert_cached_score(
+            |config, data| {
+                config.quadgram_precision = 1_000;
+                config.weights.quadgram_rolls = 1.0;
+                config.weights.chained_alternates = 0.5;
+                config.weights.double_redirects = -2.0;
+
+                let cleaner = crate::corpus_cleaner::CorpusCleaner::builder()
+                    .qwerty_punctuation_mappings(true)
+                    .with_chars("abcdefghijklmnopqrstuvwxyz',.;/ ".chars())
+                    .build();
+                let options = crate::data::CollectionOptions {
+                    quadgrams: Some(2_000),
+                    ..Default::default()
+                };
+                *data = Data::from_paths_with_options(
+                    &[base_path().join("static/text/monkeyracer")],
+                    "monkeyracer",
+                    &cleaner,
+                    options,
+                )
+                .unwrap();
+
+                assert_eq!(data.quadgrams.len(), 2_000);
+                assert!(data.quadgram_total > data.trigram_total / 2);
+            },
+            |cache| cache.quadgrams_total,
+            Oxeylyzer::quadgram_score,
+        );
+
+        let qwerty = oxeylyzer.fast_layout(&Layout::from(QWERTY.clone()), &[]);
+        let stats = oxeylyzer.get_layout_stats(&qwerty).quadgram_stats;
+        assert!(stats.rolls > 0.0 && stats.chained_alternates > 0.0);
+    }
+
     #[test]
     fn best_found_swap() {
         let mut qwerty = QWERTY.clone();
@@ -1307,4 +2205,22 @@ mod tests {
             assert_eq!(normal_score, cached_score, "i: {i}");
         }
     }
+
+    #[test]
+    fn optimize_traced() {
+        let layout = QWERTY.random();
+
+        let mut trace = crate::trace::OptimizationTrace::default();
+        let traced = GEN.optimize_traced(layout.clone(), Some(&mut trace));
+        let untraced = GEN.optimize(layout);
+
+        assert_eq!(traced.layout_str(), untraced.layout_str());
+        assert_eq!(trace.swaps, trace.scores.len());
+        assert_eq!(trace.hit_cap, trace.swaps == MAX_SWAPS);
+        assert_eq!(
+            trace.final_score(),
+            GEN.initialize_cache(&traced).total_score()
+        );
+        assert!(trace.scores.iter().tuple_windows().all(|(a, b)| a < b));
+    }
 }
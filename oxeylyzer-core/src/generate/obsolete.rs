@@ -71,4 +71,11 @@ impl Oxeylyzer {
             .map(|f| self.finger_fspeed(layout, f))
             .sum()
     }
+
+    pub(crate) fn travel_score(&self, layout: &FastLayout) -> i64 {
+        Finger::FINGERS
+            .into_iter()
+            .map(|f| self.finger_travel(layout, f))
+            .sum()
+    }
 }
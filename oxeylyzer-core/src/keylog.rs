@@ -0,0 +1,432 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    BACKSPACE_CHAR, OxeylyzerError, OxeylyzerResultExt, REPLACEMENT_CHAR, Result,
+    corpus_cleaner::{CleanCorpus, CleanerState, CorpusCleaner},
+    corpus_config::CorpusSource,
+    corpus_report::CorpusReport,
+    data::{CollectionOptions, Data},
+};
+
+/// Whether a key was pressed or released.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    #[default]
+    Down,
+    /// The key was released. Releases are ignored.
+    Up,
+}
+
+/// A single event of a keystroke log, which is one line of a JSONL file.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::keylog::{KeyEvent, KeyEventKind, Keystroke};
+/// let event: KeyEvent = serde_json::from_str(r#"{ "t": 1520, "key": "Backspace" }"#).unwrap();
+///
+/// assert_eq!(event.t, Some(1520));
+/// assert_eq!(event.kind, KeyEventKind::Down);
+/// assert_eq!(event.keystroke(), Some(Keystroke::Backspace));
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// Timestamp of the event in milliseconds. Events are used in the order they are logged in.
+    #[serde(default)]
+    pub t: Option<u64>,
+    /// The character that was typed, or the name of a key like `Backspace` or `ArrowLeft`.
+    pub key: String,
+    /// Whether the key was pressed or released.
+    #[serde(default, rename = "type")]
+    pub kind: KeyEventKind,
+}
+
+/// What a key press contributes to a corpus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keystroke {
+    /// A character was typed.
+    Char(char),
+    /// Backspace was pressed.
+    Backspace,
+    /// A key that isn't part of the corpus was pressed, like an arrow key or escape. It breaks
+    /// up n-grams the same way an unmapped character does.
+    Other,
+}
+
+impl KeyEvent {
+    /// Returns the keystroke this event stands for. Key releases and modifiers return `None`,
+    /// as the corpus cleaner derives shift and similar keys from the typed characters instead.
+    pub fn keystroke(&self) -> Option<Keystroke> {
+        if self.kind == KeyEventKind::Up {
+            return None;
+        }
+
+        let mut chars = self.key.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Keystroke::Char(c));
+        }
+
+        match self.key.as_str() {
+            "Backspace" => Some(Keystroke::Backspace),
+            "Space" => Some(Keystroke::Char(' ')),
+            "Enter" => Some(Keystroke::Char('\n')),
+            "Tab" => Some(Keystroke::Char('\t')),
+            "Shift" | "Control" | "Alt" | "AltGraph" | "Meta" | "CapsLock" => None,
+            _ => Some(Keystroke::Other),
+        }
+    }
+}
+
+/// Parses a keystroke log in JSONL format, with one [`KeyEvent`] per line. Empty lines are
+/// skipped.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::keylog::parse_events;
+/// let log = r#"
+///     { "t": 0, "key": "h" }
+///     { "t": 80, "key": "h", "type": "up" }
+///     { "t": 95, "key": "i" }
+/// "#;
+/// let events = parse_events(log.as_bytes()).unwrap();
+///
+/// assert_eq!(events.len(), 3);
+/// assert!(parse_events(r#"{ "t": 0 }"#.as_bytes()).is_err());
+/// ```
+pub fn parse_events<R: BufRead>(reader: R) -> Result<Vec<KeyEvent>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line.str_context("keystroke log")?;
+            serde_json::from_str(&line).str_context(format!("key event on line {}", i + 1))
+        })
+        .collect()
+}
+
+/// Loads the key events of a keystroke log file, or of every file in a directory in
+/// alphabetical order.
+pub fn load_events<P: AsRef<Path>>(path: P) -> Result<Vec<KeyEvent>> {
+    let path = path.as_ref();
+
+    if path.is_dir() {
+        let mut files = std::fs::read_dir(path)
+            .path_context(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        files.sort();
+
+        files.iter().try_fold(Vec::new(), |mut events, file| {
+            events.extend(load_events(file)?);
+            Ok(events)
+        })
+    } else if path.is_file() {
+        let file = std::fs::File::open(path).path_context(path)?;
+        parse_events(BufReader::new(file)).path_context(path)
+    } else if path.exists() {
+        Err(OxeylyzerError::NotAFile(path.to_path_buf()))
+    } else {
+        Err(OxeylyzerError::PathDoesNotExist(path.to_path_buf()))
+    }
+}
+
+/// Turns key events into the keystrokes that were actually pressed. If `discard_corrections` is
+/// set, typed characters that were erased with backspace are dropped along with the backspaces
+/// themselves, assuming the cursor was at the end of the text. A backspace right after another
+/// key, like an arrow key, is dropped without erasing anything, as the cursor might have moved.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::keylog::{KeyEvent, Keystroke::*, keystrokes};
+/// let events = ["t", "e", "h", "Backspace", "Backspace", "h", "e"]
+///     .map(|key| KeyEvent { t: None, key: key.into(), kind: Default::default() });
+///
+/// assert_eq!(keystrokes(&events, false).len(), 7);
+/// assert_eq!(keystrokes(&events, true), [Char('t'), Char('h'), Char('e')]);
+///
+/// let events = ["a", "ArrowLeft", "Backspace"]
+///     .map(|key| KeyEvent { t: None, key: key.into(), kind: Default::default() });
+/// assert_eq!(keystrokes(&events, true), [Char('a'), Other]);
+/// ```
+pub fn keystrokes(events: &[KeyEvent], discard_corrections: bool) -> Vec<Keystroke> {
+    let mut keystrokes = Vec::with_capacity(events.len());
+
+    for keystroke in events.iter().filter_map(KeyEvent::keystroke) {
+        match keystroke {
+            Keystroke::Backspace if discard_corrections => {
+                if let Some(Keystroke::Char(_)) = keystrokes.last() {
+                    keystrokes.pop();
+                }
+            }
+            keystroke => keystrokes.push(keystroke),
+        }
+    }
+
+    keystrokes
+}
+
+/// Cleans keystrokes with a corpus cleaner. Typed characters are cleaned like text, backspace
+/// becomes [`BACKSPACE_CHAR`] much like the cleaner inserts shift presses, and other keys become
+/// [`REPLACEMENT_CHAR`].
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{keylog::{Keystroke::*, clean_keystrokes}, corpus_cleaner::CorpusCleaner};
+/// let cleaner = CorpusCleaner::builder().with_chars("abc".chars()).build();
+/// let keystrokes = [Char('A'), Char('b'), Backspace, Other, Char('c')];
+///
+/// let cleaned = clean_keystrokes(&keystrokes, &cleaner).into_iter().collect::<String>();
+/// assert_eq!(cleaned, "⇑ab⌫�c");
+/// ```
+pub fn clean_keystrokes(keystrokes: &[Keystroke], cleaner: &CorpusCleaner) -> Vec<char> {
+    clean_keystrokes_with_unmapped(keystrokes, cleaner).0
+}
+
+/// Cleans keystrokes like [`clean_keystrokes`], also returning how often every typed character
+/// that couldn't be mapped was typed.
+fn clean_keystrokes_with_unmapped(
+    keystrokes: &[Keystroke],
+    cleaner: &CorpusCleaner,
+) -> (Vec<char>, Vec<(char, i64)>) {
+    let mut cleaned = Vec::with_capacity(keystrokes.len());
+    let mut unmapped = Vec::new();
+    let mut state = CleanerState::default();
+    let mut typed = Vec::new();
+
+    let mut flush = |typed: &mut Vec<char>, cleaned: &mut Vec<char>| {
+        let mut iter = typed.drain(..).clean_corpus_from(cleaner, state);
+        cleaned.extend(iter.by_ref().flatten());
+        unmapped.extend(iter.unmapped());
+        state = iter.state();
+    };
+
+    for keystroke in keystrokes {
+        match keystroke {
+            Keystroke::Char(c) => typed.push(*c),
+            Keystroke::Backspace => {
+                flush(&mut typed, &mut cleaned);
+                cleaned.push(BACKSPACE_CHAR);
+            }
+            Keystroke::Other => {
+                flush(&mut typed, &mut cleaned);
+                cleaned.push(REPLACEMENT_CHAR);
+            }
+        }
+    }
+    flush(&mut typed, &mut cleaned);
+
+    (cleaned, unmapped)
+}
+
+impl Data {
+    /// Generates frequency data from the key events of a keystroke log, counting the keys that
+    /// were actually pressed including backspace, which is counted as [`BACKSPACE_CHAR`].
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{BACKSPACE_CHAR, data::{CollectionOptions, Data}, keylog::parse_events};
+    /// # use oxeylyzer_core::corpus_cleaner::CorpusCleaner;
+    /// let log = r#"
+    ///     { "t": 0, "key": "a" }
+    ///     { "t": 90, "key": "c" }
+    ///     { "t": 250, "key": "Backspace" }
+    ///     { "t": 330, "key": "b" }
+    /// "#;
+    /// let events = parse_events(log.as_bytes()).unwrap();
+    /// let cleaner = CorpusCleaner::builder().with_chars("abc".chars()).build();
+    ///
+    /// let data = Data::from_key_events(&events, "log", &cleaner, CollectionOptions::default(), false);
+    /// assert_eq!(data.bigram_total, 3);
+    /// assert!(data.get_bigram(['c', BACKSPACE_CHAR]).is_some());
+    ///
+    /// let data = Data::from_key_events(&events, "log", &cleaner, CollectionOptions::default(), true);
+    /// assert_eq!(data.get_bigram(['a', 'b']), Some(&100.0));
+    /// ```
+    pub fn from_key_events(
+        events: &[KeyEvent],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+        discard_corrections: bool,
+    ) -> Self {
+        let keystrokes = keystrokes(events, discard_corrections);
+        let cleaned = clean_keystrokes(&keystrokes, cleaner);
+
+        Self::from_cleaned(cleaned, name, options)
+    }
+
+    /// Generates frequency data from several keystroke logs like
+    /// [`from_key_events`](Self::from_key_events), one for every source. Sources are weighted
+    /// by their `weight` or `share` like the sources of a text corpus, but can't be sampled,
+    /// because a log has no lines or blocks of text to pick from.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{corpus_cleaner::CorpusCleaner, corpus_config::CorpusSource};
+    /// # use oxeylyzer_core::{data::{CollectionOptions, Data}, keylog::parse_events};
+    /// let large = parse_events("{ \"key\": \"a\" }\n".repeat(9).as_bytes()).unwrap();
+    /// let small = parse_events(r#"{ "key": "b" }"#.as_bytes()).unwrap();
+    /// let sources = [
+    ///     CorpusSource::new("large.jsonl"),
+    ///     CorpusSource { share: Some(0.5), ..CorpusSource::new("small.jsonl") },
+    /// ];
+    /// let cleaner = CorpusCleaner::builder().with_chars("ab".chars()).build();
+    ///
+    /// let data = Data::from_key_logs(
+    ///     &sources,
+    ///     &[large, small],
+    ///     "logs",
+    ///     &cleaner,
+    ///     CollectionOptions::default(),
+    ///     false,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(data.get_char('a'), data.get_char('b'));
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_key_logs(
+        sources: &[CorpusSource],
+        logs: &[Vec<KeyEvent>],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+        discard_corrections: bool,
+    ) -> Result<Self> {
+        Self::from_key_logs_with_report(sources, logs, name, cleaner, options, discard_corrections)
+            .map(|(data, _)| data)
+    }
+
+    /// Generates frequency data from several keystroke logs like
+    /// [`from_key_logs`](Self::from_key_logs), together with a report on what the cleaner
+    /// replaced or added.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{corpus_cleaner::CorpusCleaner, corpus_config::CorpusSource};
+    /// # use oxeylyzer_core::{data::{CollectionOptions, Data}, keylog::parse_events};
+    /// let log = parse_events(r#"{ "key": "a" }
+    ///     { "key": "é" }
+    ///     { "key": "B" }"#.as_bytes()).unwrap();
+    /// let cleaner = CorpusCleaner::builder().with_chars("ab".chars()).build();
+    ///
+    /// let (data, report) = Data::from_key_logs_with_report(
+    ///     &[CorpusSource::new("log.jsonl")],
+    ///     &[log],
+    ///     "log",
+    ///     &cleaner,
+    ///     CollectionOptions::default(),
+    ///     false,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(report.name, "log");
+    /// assert_eq!(report.char_total, data.char_total);
+    /// assert_eq!(report.unmapped.get(&'é'), Some(&1));
+    /// assert_eq!(report.shifts, 1);
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_key_logs_with_report(
+        sources: &[CorpusSource],
+        logs: &[Vec<KeyEvent>],
+        name: &str,
+        cleaner: &CorpusCleaner,
+        options: CollectionOptions,
+        discard_corrections: bool,
+    ) -> Result<(Self, CorpusReport)> {
+        sources.iter().try_for_each(CorpusSource::validate)?;
+
+        if let Some(source) = sources.iter().find(|s| s.sample.is_some()) {
+            return Err(OxeylyzerError::InvalidCorpusSource(
+                source.path.clone(),
+                "key logs can't be sampled",
+            ));
+        }
+
+        let cleaned = logs.iter().map(|events| {
+            clean_keystrokes_with_unmapped(&keystrokes(events, discard_corrections), cleaner)
+        });
+
+        Self::from_cleaned_sources_with_report(sources, cleaned, name, cleaner, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SHIFT_CHAR, SPACE_CHAR, corpus_config::Sampling};
+
+    static LOG: &str = r#"
+        { "t": 0, "key": "Shift" }
+        { "t": 40, "key": "H" }
+        { "t": 90, "key": "H", "type": "up" }
+        { "t": 95, "key": "Shift", "type": "up" }
+        { "t": 160, "key": "i" }
+        { "t": 210, "key": "Space" }
+        { "t": 300, "key": "y" }
+        { "t": 380, "key": "o" }
+        { "t": 450, "key": "y" }
+        { "t": 700, "key": "Backspace" }
+        { "t": 760, "key": "u" }
+        { "t": 900, "key": "ArrowLeft" }
+        { "t": 990, "key": "Enter" }
+    "#;
+
+    #[test]
+    fn keylog() {
+        let events = parse_events(LOG.as_bytes()).unwrap();
+        assert_eq!(events.len(), 13);
+
+        let cleaner = CorpusCleaner::builder()
+            .with_chars("hiyou".chars())
+            .with_char_mappings([(' ', SPACE_CHAR), ('\n', SPACE_CHAR)])
+            .build();
+
+        let cleaned = clean_keystrokes(&keystrokes(&events, false), &cleaner);
+        assert_eq!(
+            cleaned.iter().collect::<String>(),
+            format!("{SHIFT_CHAR}hi{SPACE_CHAR}yoy{BACKSPACE_CHAR}u{REPLACEMENT_CHAR}{SPACE_CHAR}")
+        );
+
+        let corrected = clean_keystrokes(&keystrokes(&events, true), &cleaner);
+        assert_eq!(
+            corrected.iter().collect::<String>(),
+            format!("{SHIFT_CHAR}hi{SPACE_CHAR}you{REPLACEMENT_CHAR}{SPACE_CHAR}")
+        );
+
+        let data = Data::from_key_events(
+            &events,
+            "keylog",
+            &cleaner,
+            CollectionOptions::default(),
+            false,
+        );
+        assert!(data.get_bigram(['y', BACKSPACE_CHAR]).is_some());
+        assert!(data.get_bigram([BACKSPACE_CHAR, 'u']).is_some());
+        assert_eq!(data.get_bigram(['o', 'u']), None);
+        assert_eq!(data.get_bigram(['u', SPACE_CHAR]), None);
+
+        let sampled = CorpusSource {
+            sample: Some(Sampling::Lines(0.5)),
+            ..CorpusSource::new("log.jsonl")
+        };
+        assert!(matches!(
+            Data::from_key_logs(
+                &[sampled],
+                &[events],
+                "keylog",
+                &cleaner,
+                CollectionOptions::default(),
+                false,
+            ),
+            Err(OxeylyzerError::InvalidCorpusSource(..))
+        ));
+    }
+}
@@ -7,24 +7,51 @@
 
 /// Data structures for analyzing layout performance.
 pub mod analyzer_data;
+/// Compact, versioned binary format for corpus data.
+pub mod binary_data;
 /// Mapping between characters and internal byte representations.
 pub mod char_mapping;
+/// Code-aware processing of corpora made from source code.
+pub mod code_corpus;
 /// Tools for cleaning and processing corpus data.
 pub mod corpus_cleaner;
+/// Corpus config files describing how raw text is turned into a corpus.
+pub mod corpus_config;
+/// Reports on what was replaced or added while cleaning a corpus.
+pub mod corpus_report;
 /// Basic data structures for corpus information.
 pub mod data;
 /// Fast layout representation for optimization.
 pub mod fast_layout;
 /// Layout generation algorithms.
 pub mod generate;
+/// Keystroke logs as a source of corpus data.
+pub mod keylog;
 /// Layout representation and evaluation.
 pub mod layout;
+/// Scoring and generating layouts against multiple corpora at once.
+pub mod multi_corpus;
+/// Quadgram pattern analysis.
+pub mod quadgram_patterns;
+/// Exact optimization of a small amount of free keys using branch and bound.
+pub mod solve;
+/// Layout generation using parallel tempering.
+pub mod tempering;
+/// Measured bigram timings used as an alternative cost model.
+pub mod timing;
+/// Telemetry of optimization runs.
+pub mod trace;
 /// Trigram pattern analysis.
 pub mod trigram_patterns;
 /// Miscellaneous utility functions.
 pub mod utility;
 /// Weights for different layout metrics.
 pub mod weights;
+/// Word level statistics of layouts.
+pub mod words;
+
+#[cfg(test)]
+mod test_util;
 
 use std::path::{Path, PathBuf};
 
@@ -41,6 +68,8 @@ pub const SPACE_CHAR: char = '␣';
 pub const SHIFT_CHAR: char = '⇑';
 /// Internal representation of a repeat key.
 pub const REPEAT_KEY: char = '↻';
+/// Internal representation of a backspace key press.
+pub const BACKSPACE_CHAR: char = '⌫';
 
 /// Errors that can occur within the oxeylyzer-core crate.
 #[derive(Debug, Error)]
@@ -51,6 +80,9 @@ pub enum OxeylyzerError {
     /// Encountered a trigram that does not have a length of 3.
     #[error("Trigrams should contain 3 characters, trigram with length {0} encountered.")]
     InvalidTrigramLength(usize),
+    /// Encountered a quadgram that does not have a length of 4.
+    #[error("Quadgrams should contain 4 characters, quadgram with length {0} encountered.")]
+    InvalidQuadgramLength(usize),
     /// Failed to initialize the file chunker for corpus processing.
     #[error("Failed to create a file chunker")]
     ChunkerInitError,
@@ -72,6 +104,63 @@ pub enum OxeylyzerError {
     /// The corpus output path is invalid (usually missing .json extension).
     #[error("Corpus path '{}' is invalid as it does not end in a (.json) file.", .0.display())]
     InvalidCorpusPath(PathBuf),
+    /// Could not serialize optimization traces to JSON.
+    #[error("Failed to serialize optimization traces")]
+    CouldNotSerializeTrace,
+    /// The provided aggregate is not one of the supported ways to combine scores.
+    #[error("Unknown aggregate '{0}'. Supported aggregates: min, mean, weighted")]
+    UnknownAggregate(String),
+    /// Layouts can't be scored against multiple corpora if there are none.
+    #[error("At least one corpus is needed to score layouts against")]
+    NoCorpora,
+    /// A corpus has a negative weight, or one that isn't a finite number.
+    #[error("Corpus '{0}' has a weight of {1}, but corpus weights have to be 0 or more")]
+    NegativeCorpusWeight(String, f64),
+    /// The weights of all corpora add up to 0, so they can't be averaged.
+    #[error("Corpus weights add up to 0, at least one corpus needs a positive weight")]
+    CorpusWeightsZero,
+    /// A trigram rule refers to a finger that does not exist.
+    #[error("Unknown finger '{0}'. Fingers are named lp, lr, lm, li, lt, rt, ri, rm, rr and rp")]
+    UnknownFinger(String),
+    /// A trigram rule refers to a category that is neither built in nor defined in the config.
+    #[error("Unknown trigram category '{0}'")]
+    UnknownTrigramCategory(String),
+    /// More custom trigram categories were defined than can be represented.
+    #[error("At most {0} custom trigram categories can be defined")]
+    TooManyTrigramCategories(usize),
+    /// A line or entry of a bigram timing file could not be parsed.
+    #[error("Invalid bigram timing '{0}'. Timings are given as 'from,to,ms'")]
+    InvalidTiming(String),
+    /// A finger weight is zero, negative or not a number, so it can't be divided by.
+    #[error("Finger weight of {0} is {1}, but finger weights have to be positive")]
+    InvalidFingerWeight(String, f64),
+    /// A corpus config specifies a shift key that is not a single character.
+    #[error(
+        "Shift key can only be a single char, found '{}' with length {}", .0, .0.chars().count()
+    )]
+    WrongShiftKeyLength(String),
+    /// A one to one mapping in a corpus config has a different amount of characters on each side.
+    #[error("'{0}' in corpus config maps {1} characters to {2} characters")]
+    UnevenCorpusMapping(&'static str, usize, usize),
+    /// A dead key in a corpus config has a different amount of characters on each side.
+    #[error("Dead key '{0}' in corpus config maps {1} characters to {2} characters")]
+    UnevenDeadKeyMapping(char, usize, usize),
+    /// A source in a corpus config has an invalid weight, share or sampling rate.
+    #[error("Corpus source '{}' is invalid because {}", .0.display(), .1)]
+    InvalidCorpusSource(PathBuf, &'static str),
+    /// The shares of the sources of a corpus add up to more than the whole corpus.
+    #[error("Shares of corpus sources add up to {0}, leaving no room for the other sources")]
+    CorpusSharesTooLarge(f64),
+    /// A pattern of files to skip in a corpus config is not a valid glob pattern.
+    #[error("Invalid glob pattern '{0}' for files to skip")]
+    InvalidSkipPattern(String),
+
+    /// A binary corpus data file is damaged or not a data file at all.
+    #[error("Invalid binary corpus data: {0}")]
+    InvalidBinaryData(&'static str),
+    /// A binary corpus data file was written in a different version of the format.
+    #[error("Binary corpus data has version {0}, but only version {1} is supported")]
+    UnsupportedBinaryVersion(u16, u16),
 
     /// Wrapper for general anyhow errors.
     #[error("{0:#}")]
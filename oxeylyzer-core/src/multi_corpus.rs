@@ -0,0 +1,417 @@
+use std::str::FromStr;
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::fast_layout::FastLayout;
+use crate::generate::{LayoutCache, MAX_SWAPS, Oxeylyzer};
+use crate::layout::{Layout, PosPair};
+use crate::utility::shuffle_pins;
+use crate::{OxeylyzerError, Result};
+
+/// Way of combining per-corpus scores into a single score.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::multi_corpus::Aggregate;
+/// let aggregate = "min".parse::<Aggregate>().unwrap();
+/// assert_eq!(aggregate, Aggregate::Min);
+/// assert_eq!(aggregate.apply(&[1.0, -2.0], &[1.0, 1.0]), -2.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The worst score across all corpora.
+    Min,
+    /// The unweighted mean of all corpus scores.
+    #[default]
+    Mean,
+    /// The mean of all corpus scores, weighted by the weight of each corpus.
+    Weighted,
+}
+
+impl Aggregate {
+    /// Combines a set of scores into a single score. `weights` is only used for
+    /// [`Aggregate::Weighted`], and should have the same length as `scores`. There should be at
+    /// least one score and the weights should add up to more than 0, which
+    /// [`MultiOxeylyzer::new`] makes sure of.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::multi_corpus::Aggregate;
+    /// let scores = [-1.0, -3.0];
+    /// let weights = [3.0, 1.0];
+    ///
+    /// assert_eq!(Aggregate::Min.apply(&scores, &weights), -3.0);
+    /// assert_eq!(Aggregate::Mean.apply(&scores, &weights), -2.0);
+    /// assert_eq!(Aggregate::Weighted.apply(&scores, &weights), -1.5);
+    /// ```
+    pub fn apply(&self, scores: &[f64], weights: &[f64]) -> f64 {
+        match self {
+            Self::Min => scores.iter().copied().fold(f64::INFINITY, f64::min),
+            Self::Mean => scores.iter().sum::<f64>() / scores.len() as f64,
+            Self::Weighted => {
+                let total = weights.iter().sum::<f64>();
+                scores.iter().zip(weights).map(|(s, w)| s * w).sum::<f64>() / total
+            }
+        }
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = OxeylyzerError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "min" | "worst" => Ok(Self::Min),
+            "mean" | "avg" | "average" => Ok(Self::Mean),
+            "weighted" => Ok(Self::Weighted),
+            _ => Err(OxeylyzerError::UnknownAggregate(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Min => "min",
+            Self::Mean => "mean",
+            Self::Weighted => "weighted",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+/// Scores of a single layout across multiple corpora.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultiScore {
+    /// Score per corpus, in the same order as the corpora of the [`MultiOxeylyzer`].
+    pub scores: Vec<f64>,
+    /// The unweighted mean of all scores.
+    pub mean: f64,
+    /// The worst score of all corpora.
+    pub worst: f64,
+    /// The score according to the chosen [`Aggregate`].
+    pub aggregate: f64,
+}
+
+/// A single corpus used by a [`MultiOxeylyzer`], along with its weight.
+pub struct WeightedCorpus {
+    /// Analyzer for this corpus.
+    pub oxeylyzer: Oxeylyzer,
+    /// Relative weight of this corpus, used by [`Aggregate::Weighted`].
+    pub weight: f64,
+}
+
+/// Scores and generates layouts against several corpora at the same time, combining the score
+/// for each corpus using an [`Aggregate`].
+///
+/// Scores are normalized per corpus the same way they are displayed, so corpora of different
+/// sizes can be compared with each other.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, weights::Config};
+/// # use oxeylyzer_core::multi_corpus::{Aggregate, MultiOxeylyzer};
+/// let english = Oxeylyzer::new(Data::default(), Config::with_defaults()).unwrap();
+/// let dutch = Oxeylyzer::new(Data::default(), Config::with_defaults()).unwrap();
+///
+/// let multi =
+///     MultiOxeylyzer::new(vec![(english, 2.0), (dutch, 1.0)], Aggregate::Min).unwrap();
+/// assert_eq!(multi.corpora.len(), 2);
+/// ```
+pub struct MultiOxeylyzer {
+    /// All corpora to score against.
+    pub corpora: Vec<WeightedCorpus>,
+    /// How per-corpus scores are combined.
+    pub aggregate: Aggregate,
+}
+
+impl MultiOxeylyzer {
+    /// Creates a new `MultiOxeylyzer` from a list of analyzers and their weights. Fails if there
+    /// are no analyzers, if a weight is negative, or if the weights add up to 0.
+    pub fn new(corpora: Vec<(Oxeylyzer, f64)>, aggregate: Aggregate) -> Result<Self> {
+        if corpora.is_empty() {
+            return Err(OxeylyzerError::NoCorpora);
+        }
+        if let Some((oxeylyzer, weight)) =
+            corpora.iter().find(|(_, w)| !(*w >= 0.0 && w.is_finite()))
+        {
+            return Err(OxeylyzerError::NegativeCorpusWeight(
+                oxeylyzer.language.clone(),
+                *weight,
+            ));
+        }
+        if corpora.iter().map(|(_, w)| w).sum::<f64>() == 0.0 {
+            return Err(OxeylyzerError::CorpusWeightsZero);
+        }
+
+        let corpora = corpora
+            .into_iter()
+            .map(|(oxeylyzer, weight)| WeightedCorpus { oxeylyzer, weight })
+            .collect();
+
+        Ok(Self { corpora, aggregate })
+    }
+
+    /// Returns the name of every corpus, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.corpora
+            .iter()
+            .map(|c| c.oxeylyzer.language.as_str())
+            .collect()
+    }
+
+    fn weights(&self) -> Vec<f64> {
+        self.corpora.iter().map(|c| c.weight).collect()
+    }
+
+    fn normalize(oxeylyzer: &Oxeylyzer, score: i64) -> f64 {
+        score as f64 / oxeylyzer.data.char_total as f64 / 100.0
+    }
+
+    fn multi_score(&self, scores: Vec<f64>) -> MultiScore {
+        let mean = Aggregate::Mean.apply(&scores, &[]);
+        let worst = Aggregate::Min.apply(&scores, &[]);
+        let aggregate = self.aggregate.apply(&scores, &self.weights());
+
+        MultiScore {
+            scores,
+            mean,
+            worst,
+            aggregate,
+        }
+    }
+
+    /// Scores a layout against every corpus.
+    pub fn score(&self, layout: &Layout) -> MultiScore {
+        let scores = self
+            .corpora
+            .iter()
+            .map(|c| {
+                let fast = c.oxeylyzer.fast_layout(layout, &[]);
+                Self::normalize(&c.oxeylyzer, c.oxeylyzer.score(&fast))
+            })
+            .collect();
+
+        self.multi_score(scores)
+    }
+
+    fn aggregate_caches(&self, caches: &[LayoutCache]) -> f64 {
+        let scores = self
+            .corpora
+            .iter()
+            .zip(caches)
+            .map(|(c, cache)| Self::normalize(&c.oxeylyzer, cache.total_score()))
+            .collect::<Vec<_>>();
+
+        self.aggregate.apply(&scores, &self.weights())
+    }
+
+    fn score_swap(
+        &self,
+        layouts: &mut [FastLayout],
+        caches: &[LayoutCache],
+        swap: &PosPair,
+    ) -> f64 {
+        let scores = self
+            .corpora
+            .iter()
+            .zip(layouts.iter_mut())
+            .zip(caches)
+            .map(|((c, layout), cache)| {
+                // a swap the analyzer rejects doesn't change the score for that corpus
+                let score = c
+                    .oxeylyzer
+                    .score_swap_cached(layout, swap, cache)
+                    .unwrap_or_else(|| cache.total_score());
+
+                Self::normalize(&c.oxeylyzer, score)
+            })
+            .collect::<Vec<_>>();
+
+        self.aggregate.apply(&scores, &self.weights())
+    }
+
+    fn accept_swap(&self, layouts: &mut [FastLayout], caches: &mut [LayoutCache], swap: &PosPair) {
+        self.corpora
+            .iter()
+            .zip(layouts.iter_mut())
+            .zip(caches.iter_mut())
+            .for_each(|((c, layout), cache)| {
+                // keys that don't occur in a corpus still need to move to keep layouts in sync
+                if c.oxeylyzer.accept_swap(layout, swap, cache).is_none() {
+                    layout.swap_pair(swap);
+                }
+            });
+    }
+
+    fn optimize(&self, layout: &Layout, pins: &[usize]) -> (Layout, MultiScore) {
+        let mut order = (0..layout.keys.len()).collect::<Vec<_>>();
+        shuffle_pins(&mut order, pins);
+
+        let keys = order.iter().map(|&i| layout.keys[i]).collect::<Vec<_>>();
+        let mut layout = Layout {
+            keys: keys.into(),
+            ..layout.clone()
+        };
+
+        let mut layouts = self
+            .corpora
+            .iter()
+            .map(|c| c.oxeylyzer.fast_layout(&layout, pins))
+            .collect::<Vec<_>>();
+
+        let mut caches = self
+            .corpora
+            .iter()
+            .zip(&layouts)
+            .map(|(c, l)| c.oxeylyzer.initialize_cache(l))
+            .collect::<Vec<_>>();
+
+        let possible_swaps = match layouts.first_mut() {
+            Some(l) => std::mem::take(&mut l.possible_swaps),
+            None => return (layout, MultiScore::default()),
+        };
+
+        let mut keys = layout.keys.to_vec();
+        let mut current_best_score = self.aggregate_caches(&caches);
+
+        for _ in 0..MAX_SWAPS {
+            let mut best_swap = None;
+
+            for swap in possible_swaps.iter() {
+                let score = self.score_swap(&mut layouts, &caches, swap);
+                if score > current_best_score {
+                    current_best_score = score;
+                    best_swap = Some(*swap);
+                }
+            }
+
+            let Some(swap @ PosPair(a, b)) = best_swap else {
+                break;
+            };
+
+            self.accept_swap(&mut layouts, &mut caches, &swap);
+            keys.swap(a as usize, b as usize);
+        }
+
+        layout.name = keys.iter().skip(10).take(4).collect();
+        layout.keys = keys.into();
+
+        let score = self.score(&layout);
+
+        (layout, score)
+    }
+
+    /// Generates multiple layouts in parallel, optimizing for the aggregate score over all
+    /// corpora while keeping pinned positions in place. Returns the
+    /// [`ParallelIterator`](rayon::prelude::ParallelIterator) that yields those optimized layouts
+    /// together with their scores.
+    pub fn generate_n_with_pins_iter<'a>(
+        &'a self,
+        amount: usize,
+        based_on: &'a Layout,
+        pins: &'a [usize],
+    ) -> impl ParallelIterator<Item = (Layout, MultiScore)> + 'a {
+        (0..amount)
+            .into_par_iter()
+            .map(move |_| self.optimize(based_on, pins))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::Data,
+        test_util::{base_path, qwerty},
+        weights::Config,
+    };
+
+    use itertools::Itertools;
+    use once_cell::sync::Lazy;
+
+    static MULTI: Lazy<MultiOxeylyzer> = Lazy::new(|| {
+        let base = base_path();
+        let config = Config::with_loaded_weights(base.join("config.toml")).unwrap();
+
+        let corpora = ["english", "german"]
+            .into_iter()
+            .map(|name| {
+                let path = base.join("static/language_data").join(name);
+                let data = Data::load(path.with_extension("json")).unwrap();
+                (Oxeylyzer::new(data, config.clone()).unwrap(), 1.0)
+            })
+            .collect();
+
+        MultiOxeylyzer::new(corpora, Aggregate::Min).unwrap()
+    });
+
+    static QWERTY: Lazy<Layout> = Lazy::new(qwerty);
+
+    #[test]
+    fn score_matches_single_corpus() {
+        let score = MULTI.score(&QWERTY);
+
+        for (c, &s) in MULTI.corpora.iter().zip(&score.scores) {
+            let fast = c.oxeylyzer.fast_layout(&QWERTY, &[]);
+            let single = MultiOxeylyzer::normalize(&c.oxeylyzer, c.oxeylyzer.score(&fast));
+
+            assert_eq!(s, single);
+        }
+
+        assert_eq!(score.worst, score.aggregate);
+        assert!(score.worst <= score.mean);
+    }
+
+    #[test]
+    fn invalid_corpora() {
+        let corpora = |weights: &[f64]| {
+            weights
+                .iter()
+                .map(|&w| {
+                    (
+                        Oxeylyzer::new(Data::default(), Config::with_defaults()).unwrap(),
+                        w,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(matches!(
+            MultiOxeylyzer::new(corpora(&[]), Aggregate::Mean),
+            Err(OxeylyzerError::NoCorpora)
+        ));
+        assert!(matches!(
+            MultiOxeylyzer::new(corpora(&[0.0, 0.0]), Aggregate::Weighted),
+            Err(OxeylyzerError::CorpusWeightsZero)
+        ));
+        assert!(matches!(
+            MultiOxeylyzer::new(corpora(&[1.0, -1.0]), Aggregate::Weighted),
+            Err(OxeylyzerError::NegativeCorpusWeight(_, -1.0))
+        ));
+        assert!(matches!(
+            MultiOxeylyzer::new(corpora(&[1.0, f64::NAN]), Aggregate::Weighted),
+            Err(OxeylyzerError::NegativeCorpusWeight(..))
+        ));
+        assert!(MultiOxeylyzer::new(corpora(&[0.0, 1.0]), Aggregate::Weighted).is_ok());
+    }
+
+    #[test]
+    fn generate_keeps_keys_and_pins() {
+        let pins = [0, 1, 2];
+
+        let generated = MULTI
+            .generate_n_with_pins_iter(4, &QWERTY, &pins)
+            .collect::<Vec<_>>();
+
+        for (layout, score) in generated {
+            assert_eq!(&layout.keys[..3], &QWERTY.keys[..3]);
+            assert_eq!(
+                layout.keys.iter().copied().sorted().collect::<Vec<_>>(),
+                QWERTY.keys.iter().copied().sorted().collect::<Vec<_>>()
+            );
+            assert!(score.worst <= score.mean);
+        }
+    }
+}
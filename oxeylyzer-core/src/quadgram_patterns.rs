@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use libdof::dofinitions::{Finger, Hand};
+
+/// Represents patterns formed by four consecutive keystrokes (quadgrams).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum QuadgramPattern {
+    /// All four strokes are on one hand, moving in a single direction without repeating a
+    /// finger (e.g. Pinky-Ring-Middle-Index).
+    Roll,
+    /// Every stroke switches hands (e.g. Left-Right-Left-Right).
+    ChainedAlternate,
+    /// All four strokes are on one hand and change direction twice in a row, without any
+    /// same finger bigrams (e.g. Middle-Index-Ring-Index).
+    DoubleRedirect,
+    /// Any other pattern, including every quadgram that uses a thumb.
+    Other,
+    /// Quadgram contains a key that isn't on the layout.
+    Invalid,
+}
+
+#[derive(Debug)]
+struct Quadgram {
+    fingers: [Finger; 4],
+    hands: [Hand; 4],
+}
+
+impl Quadgram {
+    fn new(fingers: [Finger; 4]) -> Self {
+        Quadgram {
+            fingers,
+            hands: fingers.map(|f| f.hand()),
+        }
+    }
+
+    fn is_thumb(&self) -> bool {
+        self.fingers.iter().any(|f| f.is_thumb())
+    }
+
+    fn is_chained_alt(&self) -> bool {
+        self.hands.windows(2).all(|h| h[0] != h[1])
+    }
+
+    fn on_one_hand(&self) -> bool {
+        self.hands.iter().all(|&h| h == self.hands[0])
+    }
+
+    /// Whether each stroke moves towards a higher finger index. Only meaningful when no
+    /// finger is repeated consecutively.
+    fn directions(&self) -> [bool; 3] {
+        let [f1, f2, f3, f4] = self.fingers.map(|f| f as u8);
+
+        [f1 < f2, f2 < f3, f3 < f4]
+    }
+
+    fn has_sfb(&self) -> bool {
+        self.fingers.windows(2).any(|f| f[0] == f[1])
+    }
+
+    fn get_quadgram_pattern(&self) -> QuadgramPattern {
+        use QuadgramPattern::*;
+
+        if self.is_thumb() {
+            Other
+        } else if self.is_chained_alt() {
+            ChainedAlternate
+        } else if !self.on_one_hand() || self.has_sfb() {
+            Other
+        } else {
+            match self.directions() {
+                [d1, d2, d3] if d1 == d2 && d2 == d3 => Roll,
+                [d1, d2, d3] if d1 != d2 && d2 != d3 => DoubleRedirect,
+                _ => Other,
+            }
+        }
+    }
+}
+
+/// Pre-calculates and returns a mapping of all possible finger combinations to their quadgram
+/// patterns. The index of a combination is `f1 * 1000 + f2 * 100 + f3 * 10 + f4`.
+///
+/// # Examples:
+/// ```
+/// use libdof::dofinitions::Finger::*;
+/// use oxeylyzer_core::quadgram_patterns::{QuadgramPattern, get_quadgram_combinations};
+///
+/// let combinations = get_quadgram_combinations();
+/// let index = |f: [libdof::dofinitions::Finger; 4]| {
+///     f.iter().fold(0, |acc, &f| acc * 10 + f as usize)
+/// };
+///
+/// assert_eq!(combinations.len(), 10000);
+/// assert_eq!(combinations[index([LP, LR, LM, LI])], QuadgramPattern::Roll);
+/// assert_eq!(combinations[index([LI, RI, LM, RM])], QuadgramPattern::ChainedAlternate);
+/// ```
+pub fn get_quadgram_combinations() -> Arc<[QuadgramPattern; 10000]> {
+    let mut combinations = [QuadgramPattern::Other; 10000];
+
+    for f1 in Finger::FINGERS {
+        for f2 in Finger::FINGERS {
+            for f3 in Finger::FINGERS {
+                for f4 in Finger::FINGERS {
+                    let index = (f1 as usize) * 1000
+                        + (f2 as usize) * 100
+                        + (f3 as usize) * 10
+                        + (f4 as usize);
+                    combinations[index] = Quadgram::new([f1, f2, f3, f4]).get_quadgram_pattern();
+                }
+            }
+        }
+    }
+
+    Arc::new(combinations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuadgramPattern::*, *};
+    use libdof::dofinitions::Finger::*;
+
+    fn pattern(fingers: [Finger; 4]) -> QuadgramPattern {
+        Quadgram::new(fingers).get_quadgram_pattern()
+    }
+
+    #[test]
+    fn classification() {
+        assert_eq!(pattern([LP, LR, LM, LI]), Roll);
+        assert_eq!(pattern([RI, RM, RR, RP]), Roll);
+        assert_eq!(pattern([LI, LM, LR, LP]), Roll);
+
+        assert_eq!(pattern([LI, RI, LM, RM]), ChainedAlternate);
+        assert_eq!(pattern([RP, LP, RP, LP]), ChainedAlternate);
+
+        assert_eq!(pattern([LM, LI, LR, LI]), DoubleRedirect);
+        assert_eq!(pattern([RI, RR, RM, RP]), DoubleRedirect);
+
+        // single redirect
+        assert_eq!(pattern([LR, LM, LI, LM]), Other);
+        // same finger bigram
+        assert_eq!(pattern([LR, LM, LM, LI]), Other);
+        // two hands, but not alternating
+        assert_eq!(pattern([LR, LM, RM, RR]), Other);
+        // thumbs are never classified
+        assert_eq!(pattern([LT, RI, LT, RI]), Other);
+        assert_eq!(pattern([LR, LM, LI, LT]), Other);
+    }
+
+    #[test]
+    fn table_matches_classification() {
+        let combinations = get_quadgram_combinations();
+
+        for fingers in [
+            [LI, RI, LM, RM],
+            [LM, LI, LR, LI],
+            [RP, RR, RM, RI],
+            [LP; 4],
+        ] {
+            let index = fingers.iter().fold(0, |acc, &f| acc * 10 + f as usize);
+            assert_eq!(combinations[index], pattern(fingers));
+        }
+    }
+}
@@ -0,0 +1,544 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+use libdof::prelude::Finger;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::fast_layout::FastLayout;
+use crate::generate::{LayoutCache, Oxeylyzer};
+use crate::layout::PosPair;
+
+/// Char used for positions that have not been assigned a key yet. This is the byte
+/// representation of [`REPLACEMENT_CHAR`](crate::REPLACEMENT_CHAR), which never has a frequency.
+const EMPTY: u8 = 0;
+
+/// How many nodes are visited between checks of the time budget.
+const DEADLINE_CHECK_INTERVAL: u64 = 256;
+
+/// Margin added to the bound for metrics that are divided per finger or per key, as every
+/// division can round the score of a partial layout down by one.
+const ROUNDING_MARGIN: i64 = 2 * Finger::FINGERS.len() as i64;
+
+/// Outcome of [`Oxeylyzer::solve`].
+#[derive(Debug, Clone)]
+pub struct SolveResult {
+    /// The best layout that was found.
+    pub layout: FastLayout,
+    /// Score of the best layout, as it would be in a [`LayoutCache`](crate::generate::LayoutCache).
+    pub score: i64,
+    /// Whether the search space was exhausted, which proves `layout` is optimal.
+    pub proven: bool,
+    /// Amount of nodes of the search tree that were visited.
+    pub nodes: u64,
+    /// Wall time the search took.
+    pub duration: Duration,
+}
+
+struct Solver<'a> {
+    oxeylyzer: &'a Oxeylyzer,
+    free_positions: Vec<usize>,
+    max_trigram_weight: i64,
+    max_quadgram_weight: i64,
+    key_bounds: Box<[i64; 256]>,
+    penalties_bounded: bool,
+    deadline: Instant,
+    best_score: AtomicI64,
+    best: Mutex<(i64, Box<[u8]>)>,
+    nodes: AtomicU64,
+    timed_out: AtomicBool,
+}
+
+impl<'a> Solver<'a> {
+    /// Creates a solver that places `unplaced` on `free_positions` of the `empty` layout,
+    /// starting with `best` as the best score and keys found so far.
+    fn new(
+        oxeylyzer: &'a Oxeylyzer,
+        empty: &FastLayout,
+        free_positions: Vec<usize>,
+        unplaced: &[u8],
+        deadline: Instant,
+        best: (i64, Box<[u8]>),
+    ) -> Self {
+        let w = &oxeylyzer.weights;
+        let max_trigram_weight = [
+            w.inrolls,
+            w.outrolls,
+            w.onehands,
+            w.alternates,
+            w.alternates_sfs,
+            w.redirects,
+            w.redirects_sfs,
+            w.bad_redirects,
+            w.bad_redirects_sfs,
+            w.thumb_alternates,
+            w.thumb_rolls,
+            w.thumb_redirects,
+        ]
+        .into_iter()
+        .chain(
+            oxeylyzer
+                .trigram_categories
+                .iter()
+                .map(|c| (c.weight * 100.0) as i64),
+        )
+        .fold(0, i64::max);
+        let max_quadgram_weight = [w.quadgram_rolls, w.chained_alternates, w.double_redirects]
+            .into_iter()
+            .fold(0, i64::max);
+        // distances can come from measured timings, which aren't guaranteed to be positive
+        let positive_distances = empty.fspeed_indices.all.iter().all(|p| p.dist >= 0)
+            && empty.stretch_indices.all_pairs.iter().all(|p| p.dist >= 0);
+        let penalties_bounded = w.sfbs <= 0
+            && w.sfs <= 0
+            && w.stretches <= 0
+            && w.pinky_ring_bigrams <= 0
+            && positive_distances;
+
+        let mut key_bounds = Box::new([0; 256]);
+        for c in unplaced.iter().copied().unique() {
+            key_bounds[c as usize] = oxeylyzer.key_bound(empty, c, &free_positions);
+        }
+
+        Solver {
+            oxeylyzer,
+            free_positions,
+            max_trigram_weight,
+            max_quadgram_weight,
+            key_bounds,
+            penalties_bounded,
+            deadline,
+            best_score: AtomicI64::new(best.0),
+            best: Mutex::new(best),
+            nodes: AtomicU64::new(0),
+            timed_out: AtomicBool::new(false),
+        }
+    }
+
+    fn place(layout: &mut FastLayout, pos: usize, c: u8) {
+        layout.keys[pos] = c;
+        layout.char_to_finger[c as usize] = Some(layout.fingers[pos]);
+    }
+
+    fn remove(layout: &mut FastLayout, pos: usize, c: u8) {
+        layout.keys[pos] = EMPTY;
+
+        // the same key can still be on a pinned position
+        layout.char_to_finger[c as usize] = layout
+            .keys
+            .iter()
+            .position(|&k| k == c)
+            .map(|p| layout.fingers[p]);
+    }
+
+    /// Places `c` on the empty position `pos`, updating the cache of the layout. Placing a key
+    /// only changes the key on a single position, so any other position can be used to reuse
+    /// [`accept_change`](Oxeylyzer::accept_change) from swaps.
+    fn place_cached(&self, layout: &mut FastLayout, pos: usize, c: u8, cache: &mut LayoutCache) {
+        let other = (pos + 1) % layout.keys.len();
+
+        if other == pos {
+            Self::place(layout, pos, c);
+            *cache = self.oxeylyzer.initialize_cache(layout);
+        } else {
+            let pair = PosPair(pos as u8, other as u8);
+            self.oxeylyzer
+                .accept_change(layout, &pair, cache, |layout| Self::place(layout, pos, c));
+        }
+    }
+
+    /// Upper bound for everything the keys that haven't been placed yet could still add to the
+    /// score. Finger usage is linear, so it's the same no matter where those keys end up.
+    /// Trigrams and quadgrams can at most get their best weight. Hand balance can change by at
+    /// most the usage of the remaining keys, and finger travel and key effort by at most the
+    /// bound of every remaining key, see [`key_bound`](Oxeylyzer::key_bound). Other bigram based
+    /// metrics are all penalties, so they can only lower the score.
+    fn remainder_bound(&self, unplaced: &[u8]) -> i64 {
+        if !self.penalties_bounded {
+            return i64::MAX / 4;
+        }
+
+        let mut is_unplaced = [false; 256];
+        unplaced
+            .iter()
+            .for_each(|&c| is_unplaced[c as usize] = true);
+
+        let unplaced_usage = unplaced
+            .iter()
+            .map(|&c| self.oxeylyzer.data.get_char_u(c))
+            .sum::<i64>();
+        let usage = unplaced_usage * self.oxeylyzer.weights.max_finger_use.penalty;
+        let hand_balance = unplaced_usage * self.oxeylyzer.weights.hand_balance.abs();
+
+        let trigrams = self
+            .oxeylyzer
+            .data
+            .gen_trigrams()
+            .iter()
+            .take(self.oxeylyzer.trigram_precision)
+            .filter(|(t, _)| t.iter().any(|&c| is_unplaced[c as usize]))
+            .map(|(_, f)| f)
+            .sum::<i64>()
+            * self.max_trigram_weight;
+
+        let quadgrams = self
+            .oxeylyzer
+            .data
+            .gen_quadgrams()
+            .iter()
+            .take(self.oxeylyzer.quadgram_precision)
+            .filter(|(q, _)| q.iter().any(|&c| is_unplaced[c as usize]))
+            .map(|(_, f)| f)
+            .sum::<i64>()
+            * self.max_quadgram_weight;
+
+        let keys = unplaced
+            .iter()
+            .map(|&c| self.key_bounds[c as usize])
+            .sum::<i64>();
+
+        usage + hand_balance + keys + trigrams + quadgrams + ROUNDING_MARGIN
+    }
+
+    fn out_of_time(&self) -> bool {
+        if self.timed_out.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let nodes = self.nodes.fetch_add(1, Ordering::Relaxed);
+        if nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= self.deadline {
+            self.timed_out.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        false
+    }
+
+    fn search(
+        &self,
+        layout: &mut FastLayout,
+        cache: &LayoutCache,
+        depth: usize,
+        unplaced: &mut Vec<u8>,
+    ) {
+        if self.out_of_time() {
+            return;
+        }
+
+        let partial = cache.total_score();
+
+        if unplaced.is_empty() {
+            if partial > self.best_score.fetch_max(partial, Ordering::Relaxed) {
+                let mut best = self.best.lock().unwrap();
+                if partial > best.0 {
+                    *best = (partial, layout.keys.clone());
+                }
+            }
+            return;
+        }
+
+        if partial + self.remainder_bound(unplaced) <= self.best_score.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let pos = self.free_positions[depth];
+
+        for i in 0..unplaced.len() {
+            let c = unplaced[i];
+
+            // identical keys lead to identical subtrees
+            if unplaced[..i].contains(&c) {
+                continue;
+            }
+
+            unplaced.swap_remove(i);
+            let mut child = cache.clone();
+            self.place_cached(layout, pos, c, &mut child);
+
+            self.search(layout, &child, depth + 1, unplaced);
+
+            Self::remove(layout, pos, c);
+            unplaced.push(c);
+            let last = unplaced.len() - 1;
+            unplaced.swap(i, last);
+        }
+    }
+}
+
+impl Oxeylyzer {
+    /// Upper bound for how much placing key `c` on any of `positions` can add to the score
+    /// through finger travel and key effort. Placing a key adds its travel from the home key,
+    /// which can only lower the score with a negative weight, but it also saves the distance
+    /// of bigrams with keys on the same finger, which at most saves the largest distance for
+    /// every bigram `c` is part of.
+    fn key_bound(&self, layout: &FastLayout, c: u8, positions: &[usize]) -> i64 {
+        let freq = self.data.get_char_u(c);
+        let w = &self.weights;
+
+        let travel = match w.finger_travel {
+            0 => 0,
+            weight if weight < 0 => {
+                let bigrams = (0..self.data.len())
+                    .map(|x| x as u8)
+                    .map(|x| self.data.get_bigram_u([c, x]) + self.data.get_bigram_u([x, c]))
+                    .sum::<i64>();
+                let max_saved = Finger::FINGERS
+                    .iter()
+                    .flat_map(|&f| layout.travel_indices.pairs(f))
+                    .map(|pair| pair.dist.max(0))
+                    .max()
+                    .unwrap_or_default();
+
+                bigrams * max_saved * -weight / 100
+            }
+            weight => {
+                let max_from_home = Finger::FINGERS
+                    .iter()
+                    .flat_map(|&f| layout.travel_indices.from_home(f))
+                    .map(|&(_, dist)| dist.max(0))
+                    .max()
+                    .unwrap_or_default();
+
+                freq * max_from_home * 2 * weight / 100
+            }
+        };
+
+        let effort = positions
+            .iter()
+            .map(|&p| layout.effort_indices.get(p) * w.key_effort.weight)
+            .fold(0, i64::max);
+
+        travel + freq * effort / 100 + 1
+    }
+
+    /// Finds the optimal arrangement of all keys that are not pinned using branch and bound.
+    /// Partial layouts are scored with a [`LayoutCache`] that is updated for every key that is
+    /// placed, where keys that haven't been placed yet don't contribute, and branches whose
+    /// optimistic bound can't beat the best layout found so far are skipped. The search starts from a greedily
+    /// optimized version of `layout`, and is split over the current rayon thread pool.
+    ///
+    /// The amount of possible layouts grows factorially with the amount of free keys, so this
+    /// is only feasible for roughly 12 free keys or fewer. If `time_budget` runs out, the best
+    /// layout found so far is returned, and [`SolveResult::proven`] will be false.
+    pub fn solve(&self, layout: &FastLayout, pins: &[usize], time_budget: Duration) -> SolveResult {
+        let start = Instant::now();
+
+        let mut initial = layout.clone();
+        initial.possible_swaps = initial
+            .possible_swaps
+            .iter()
+            .copied()
+            .filter(|&PosPair(a, b)| !pins.contains(&(a as usize)) && !pins.contains(&(b as usize)))
+            .collect();
+        let possible_swaps = initial.possible_swaps.clone();
+
+        let initial = self.optimize(initial);
+        let initial_score = self.initialize_cache(&initial).total_score();
+
+        let free_positions = (0..initial.keys.len())
+            .filter(|p| !pins.contains(p))
+            .collect::<Vec<_>>();
+
+        let mut unplaced = free_positions
+            .iter()
+            .map(|&p| initial.keys[p])
+            .collect::<Vec<_>>();
+        // placing frequent keys first makes bounds tighter early on
+        unplaced.sort_by_key(|&c| std::cmp::Reverse(self.data.get_char_u(c)));
+
+        let mut empty = initial.clone();
+        for (&p, &c) in free_positions.iter().zip(&unplaced) {
+            Solver::remove(&mut empty, p, c);
+        }
+
+        let solver = Solver::new(
+            self,
+            &empty,
+            free_positions,
+            &unplaced,
+            start + time_budget,
+            (initial_score, initial.keys.clone()),
+        );
+
+        let empty_cache = self.initialize_cache(&empty);
+
+        match solver.free_positions.first() {
+            Some(&pos) => {
+                let first_choices = (0..unplaced.len())
+                    .filter(|&i| !unplaced[..i].contains(&unplaced[i]))
+                    .collect::<Vec<_>>();
+
+                first_choices.into_par_iter().for_each(|i| {
+                    let mut layout = empty.clone();
+                    let mut cache = empty_cache.clone();
+                    let mut unplaced = unplaced.clone();
+                    let c = unplaced.remove(i);
+
+                    solver.place_cached(&mut layout, pos, c, &mut cache);
+                    solver.search(&mut layout, &cache, 1, &mut unplaced);
+                });
+            }
+            None => {
+                solver.nodes.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut best = initial;
+        let (_, best_keys) = solver.best.into_inner().unwrap();
+        for (i, &c) in best_keys.iter().enumerate() {
+            Solver::place(&mut best, i, c);
+        }
+        best.possible_swaps = possible_swaps;
+
+        SolveResult {
+            score: self.initialize_cache(&best).total_score(),
+            layout: best,
+            proven: !solver.timed_out.into_inner(),
+            nodes: solver.nodes.into_inner(),
+            duration: start.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{GEN, QWERTY, oxeylyzer_with, qwerty};
+
+    fn assert_matches_brute_force(oxeylyzer: &Oxeylyzer, layout: &FastLayout, free: &[usize]) {
+        let pins = (0..layout.keys.len())
+            .filter(|p| !free.contains(p))
+            .collect::<Vec<_>>();
+
+        let result = oxeylyzer.solve(layout, &pins, Duration::from_secs(600));
+        assert!(result.proven);
+
+        let brute_force = free
+            .iter()
+            .map(|&p| layout.keys[p])
+            .permutations(free.len())
+            .map(|keys| {
+                let mut layout = layout.clone();
+                for (&p, &c) in free.iter().zip(&keys) {
+                    Solver::place(&mut layout, p, c);
+                }
+                oxeylyzer.initialize_cache(&layout).total_score()
+            })
+            .max()
+            .unwrap();
+
+        assert_eq!(result.score, brute_force);
+
+        for &p in pins.iter() {
+            assert_eq!(result.layout.char(p as u8), layout.char(p as u8));
+        }
+    }
+
+    #[test]
+    fn solve_matches_brute_force() {
+        assert_matches_brute_force(&GEN, &QWERTY, &[0, 11, 13, 17, 25]);
+    }
+
+    fn empty_solver<'a>(
+        oxeylyzer: &'a Oxeylyzer,
+        layout: &FastLayout,
+        free: &[usize],
+    ) -> (Solver<'a>, FastLayout) {
+        let mut empty = layout.clone();
+        for &p in free {
+            let c = empty.keys[p];
+            Solver::remove(&mut empty, p, c);
+        }
+
+        let unplaced = free.iter().map(|&p| layout.keys[p]).collect::<Vec<_>>();
+        let solver = Solver::new(
+            oxeylyzer,
+            &empty,
+            free.to_vec(),
+            &unplaced,
+            Instant::now(),
+            (i64::MIN, empty.keys.clone()),
+        );
+
+        (solver, empty)
+    }
+
+    #[test]
+    fn remainder_bound_with_rewarded_travel_and_effort() {
+        let oxeylyzer = oxeylyzer_with(|config, _| {
+            config.weights.finger_travel = 2.0;
+            config.weights.key_effort.weight = 1.0;
+        });
+
+        let qwerty = oxeylyzer.fast_layout(&qwerty(), &[]);
+        let free = [2, 7, 12, 16, 21, 28];
+        let (solver, empty) = empty_solver(&oxeylyzer, &qwerty, &free);
+        assert!(solver.penalties_bounded);
+
+        let keys = free.iter().map(|&p| qwerty.keys[p]).collect::<Vec<_>>();
+        let complete = keys
+            .iter()
+            .copied()
+            .permutations(free.len())
+            .map(|keys| {
+                let mut layout = empty.clone();
+                for (&p, &c) in free.iter().zip(&keys) {
+                    Solver::place(&mut layout, p, c);
+                }
+                (keys, oxeylyzer.initialize_cache(&layout).total_score())
+            })
+            .collect::<Vec<_>>();
+
+        for depth in 0..free.len() {
+            for prefix in keys.iter().copied().permutations(depth) {
+                let mut layout = empty.clone();
+                for (&p, &c) in free.iter().zip(&prefix) {
+                    Solver::place(&mut layout, p, c);
+                }
+                let partial = oxeylyzer.initialize_cache(&layout).total_score();
+                let best = complete
+                    .iter()
+                    .filter(|(keys, _)| keys.starts_with(&prefix))
+                    .map(|&(_, score)| score)
+                    .max()
+                    .unwrap();
+                let unplaced = complete
+                    .iter()
+                    .find(|(keys, _)| keys.starts_with(&prefix))
+                    .map(|(keys, _)| keys[depth..].to_vec())
+                    .unwrap();
+
+                assert!(partial + solver.remainder_bound(&unplaced) >= best);
+            }
+        }
+
+        assert_matches_brute_force(&oxeylyzer, &qwerty, &free);
+    }
+
+    #[test]
+    fn remove_keeps_pinned_key() {
+        let mut layout = QWERTY.clone();
+        let c = layout.keys[0];
+
+        Solver::place(&mut layout, 1, c);
+        Solver::remove(&mut layout, 1, c);
+        assert_eq!(layout.char_to_finger[c as usize], Some(layout.fingers[0]));
+
+        Solver::remove(&mut layout, 0, c);
+        assert_eq!(layout.char_to_finger[c as usize], None);
+    }
+
+    #[test]
+    fn placing_keys_updates_cache() {
+        let free = [3, 10, 14, 22, 29];
+        let (solver, mut layout) = empty_solver(&GEN, &QWERTY, &free);
+
+        let mut cache = GEN.initialize_cache(&layout);
+        for &p in free.iter() {
+            solver.place_cached(&mut layout, p, QWERTY.keys[p], &mut cache);
+            assert_eq!(cache, GEN.initialize_cache(&layout));
+        }
+        assert_eq!(layout.keys, QWERTY.keys);
+    }
+}
@@ -0,0 +1,214 @@
+use nanorand::{Rng, tls_rng};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::fast_layout::FastLayout;
+use crate::generate::{LayoutCache, Oxeylyzer};
+use crate::layout::PosPair;
+
+/// Options for [`Oxeylyzer::generate_tempered`].
+///
+/// Temperatures are expressed in the same unit as displayed scores, and are spread
+/// geometrically between the minimum and maximum temperature, one per replica.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::tempering::TemperingOptions;
+/// let options = TemperingOptions {
+///     replicas: 4,
+///     ..Default::default()
+/// };
+///
+/// let temperatures = options.temperatures();
+/// assert_eq!(temperatures.len(), 4);
+/// assert_eq!(temperatures[0], options.min_temperature);
+/// assert!((temperatures[3] - options.max_temperature).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemperingOptions {
+    /// Amount of annealing chains, each running at a different temperature.
+    pub replicas: usize,
+    /// Temperature of the coldest chain.
+    pub min_temperature: f64,
+    /// Temperature of the hottest chain.
+    pub max_temperature: f64,
+    /// Amount of rounds. After every round, adjacent chains attempt to exchange their layouts.
+    pub rounds: usize,
+    /// Amount of swaps each chain attempts per round.
+    pub steps_per_round: usize,
+}
+
+impl Default for TemperingOptions {
+    fn default() -> Self {
+        Self {
+            replicas: rayon::current_num_threads().max(2),
+            min_temperature: 0.01,
+            max_temperature: 1.0,
+            rounds: 500,
+            steps_per_round: 1000,
+        }
+    }
+}
+
+impl TemperingOptions {
+    /// Returns the temperature of every replica, from coldest to hottest.
+    pub fn temperatures(&self) -> Vec<f64> {
+        let replicas = self.replicas.max(1);
+
+        if replicas == 1 {
+            return vec![self.min_temperature];
+        }
+
+        let ratio = (self.max_temperature / self.min_temperature).powf(1.0 / (replicas - 1) as f64);
+
+        (0..replicas)
+            .map(|i| self.min_temperature * ratio.powi(i as i32))
+            .collect()
+    }
+}
+
+struct Replica {
+    temperature: f64,
+    layout: FastLayout,
+    cache: LayoutCache,
+    score: i64,
+    best: FastLayout,
+    best_score: i64,
+}
+
+impl Replica {
+    fn new(oxeylyzer: &Oxeylyzer, layout: FastLayout, temperature: f64) -> Self {
+        let cache = oxeylyzer.initialize_cache(&layout);
+        let score = cache.total_score();
+
+        Self {
+            temperature,
+            best: layout.clone(),
+            layout,
+            cache,
+            score,
+            best_score: score,
+        }
+    }
+
+    fn anneal(&mut self, oxeylyzer: &Oxeylyzer, possible_swaps: &[PosPair], steps: usize) {
+        if possible_swaps.is_empty() {
+            return;
+        }
+
+        let mut rng = tls_rng();
+        let scale = oxeylyzer.data.char_total as f64 * 100.0;
+
+        for _ in 0..steps {
+            let swap = &possible_swaps[rng.generate_range(0..possible_swaps.len())];
+
+            let Some(new_score) = oxeylyzer.score_swap_cached(&mut self.layout, swap, &self.cache)
+            else {
+                continue;
+            };
+
+            let delta = (new_score - self.score) as f64 / scale;
+
+            if delta >= 0.0 || rng.generate::<f64>() < (delta / self.temperature).exp() {
+                oxeylyzer.accept_swap(&mut self.layout, swap, &mut self.cache);
+                self.score = new_score;
+
+                if self.score > self.best_score {
+                    self.best_score = self.score;
+                    self.best = self.layout.clone();
+                }
+            }
+        }
+    }
+}
+
+impl Oxeylyzer {
+    /// Generates a layout using parallel tempering. Every replica runs a simulated annealing
+    /// chain at its own temperature on the current rayon thread pool, and after every round
+    /// adjacent chains exchange their layouts according to the Metropolis criterion. The best
+    /// layout found by any replica is polished with a greedy optimization pass and returned.
+    pub fn generate_tempered(
+        &self,
+        based_on: &FastLayout,
+        pins: &[usize],
+        options: &TemperingOptions,
+    ) -> FastLayout {
+        let mut layout = based_on.clone();
+
+        let possible_swaps = std::mem::take(&mut layout.possible_swaps)
+            .iter()
+            .copied()
+            .filter(|&PosPair(a, b)| !pins.contains(&(a as usize)) && !pins.contains(&(b as usize)))
+            .collect::<Vec<_>>();
+
+        let mut replicas = options
+            .temperatures()
+            .into_iter()
+            .map(|t| Replica::new(self, layout.random_with_pins(pins), t))
+            .collect::<Vec<_>>();
+
+        let scale = self.data.char_total as f64 * 100.0;
+        let mut rng = tls_rng();
+
+        for round in 0..options.rounds {
+            replicas
+                .par_iter_mut()
+                .for_each(|r| r.anneal(self, &possible_swaps, options.steps_per_round));
+
+            // alternate between exchanging (0, 1), (2, 3).. and (1, 2), (3, 4)..
+            for i in (round % 2..replicas.len().saturating_sub(1)).step_by(2) {
+                let (cold, hot) = (&replicas[i], &replicas[i + 1]);
+
+                let beta_diff = 1.0 / cold.temperature - 1.0 / hot.temperature;
+                let score_diff = (hot.score - cold.score) as f64 / scale;
+                let exponent = beta_diff * score_diff;
+
+                if exponent >= 0.0 || rng.generate::<f64>() < exponent.exp() {
+                    let (left, right) = replicas.split_at_mut(i + 1);
+                    exchange(&mut left[i], &mut right[0]);
+                }
+            }
+        }
+
+        let mut best = replicas
+            .into_iter()
+            .max_by_key(|r| r.best_score)
+            .map(|r| r.best)
+            .unwrap_or(layout);
+
+        best.possible_swaps = possible_swaps.into();
+
+        self.optimize(best)
+    }
+}
+
+fn exchange(a: &mut Replica, b: &mut Replica) {
+    std::mem::swap(&mut a.layout, &mut b.layout);
+    std::mem::swap(&mut a.cache, &mut b.cache);
+    std::mem::swap(&mut a.score, &mut b.score);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{GEN, QWERTY};
+
+    #[test]
+    fn tempered_beats_qwerty() {
+        let options = TemperingOptions {
+            replicas: 4,
+            rounds: 20,
+            steps_per_round: 200,
+            ..Default::default()
+        };
+        let pins = [0, 1];
+
+        let generated = GEN.generate_tempered(&QWERTY, &pins, &options);
+
+        assert_eq!(generated.char(0), QWERTY.char(0));
+        assert_eq!(generated.char(1), QWERTY.char(1));
+        assert!(
+            GEN.initialize_cache(&generated).total_score()
+                > GEN.initialize_cache(&QWERTY).total_score()
+        );
+    }
+}
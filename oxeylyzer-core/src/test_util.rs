@@ -0,0 +1,52 @@
+//! Fixtures shared by the tests of several modules.
+
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    data::Data, fast_layout::FastLayout, generate::Oxeylyzer, layout::Layout, weights::Config,
+};
+
+/// Qwerty on an ansi board with traditional fingering.
+pub(crate) const QWERTY_DOF: &str = r#"
+    {
+        "name": "Qwerty",
+        "board": "ansi",
+        "layers": {
+            "main": [
+                "q w e r t  y u i o p",
+                "a s d f g  h j k l ;",
+                "z x c v b  n m , . /"
+            ]
+        },
+        "fingering": "traditional"
+    }
+"#;
+
+/// Analyzer for the corpus and weights of the config in the root of the repository.
+pub(crate) static GEN: Lazy<Oxeylyzer> = Lazy::new(|| oxeylyzer_with(|_, _| {}));
+
+/// [`QWERTY_DOF`] as a layout for [`GEN`].
+pub(crate) static QWERTY: Lazy<FastLayout> = Lazy::new(|| GEN.fast_layout(&qwerty(), &[]));
+
+/// The root of the repository, where the config and static files are.
+pub(crate) fn base_path() -> PathBuf {
+    PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."))
+}
+
+/// Parses [`QWERTY_DOF`].
+pub(crate) fn qwerty() -> Layout {
+    serde_json::from_str(QWERTY_DOF).unwrap()
+}
+
+/// Loads the config and corpus [`GEN`] uses, lets `modify` change them and creates an analyzer
+/// from the result.
+pub(crate) fn oxeylyzer_with(modify: impl FnOnce(&mut Config, &mut Data)) -> Oxeylyzer {
+    let base = base_path();
+    let mut config = Config::with_loaded_weights(base.join("config.toml")).unwrap();
+    let mut data = Data::load(base.join(&config.corpus)).unwrap();
+    modify(&mut config, &mut data);
+
+    Oxeylyzer::new(data, config).unwrap()
+}
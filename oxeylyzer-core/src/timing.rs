@@ -0,0 +1,320 @@
+use std::path::{Path, PathBuf};
+
+use ahash::AHashMap as HashMap;
+use libdof::prelude::Finger;
+use serde::Deserialize;
+
+use crate::{
+    OxeylyzerError, OxeylyzerResultExt, Result, fast_layout::FastLayout, generate::Oxeylyzer,
+    trigram_patterns::parse_finger,
+};
+
+/// One side of a measured bigram, which is either a key position or a finger.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum TimingKey {
+    /// Index of a key on the layout, counting row by row from the top left.
+    Position(u8),
+    /// Name of a finger, like `lp` or `ri`.
+    Finger(String),
+}
+
+impl TimingKey {
+    fn parse(s: &str) -> Self {
+        match s.parse::<u8>() {
+            Ok(pos) => TimingKey::Position(pos),
+            Err(_) => TimingKey::Finger(s.to_string()),
+        }
+    }
+}
+
+/// Average time it took to type a bigram from one key or finger to another.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TimingEntry {
+    /// The first key of the bigram.
+    pub from: TimingKey,
+    /// The second key of the bigram.
+    pub to: TimingKey,
+    /// Average time between both keystrokes in milliseconds.
+    pub ms: f64,
+}
+
+/// Metric that measured timings are used for instead of distances between keys.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimingTarget {
+    /// Timings are only used to estimate typing time.
+    #[default]
+    None,
+    /// Timings replace the distance of same finger bigrams in finger speed.
+    Fspeed,
+    /// Timings replace the distance of bigrams on different fingers of the same hand in
+    /// stretches.
+    Stretches,
+}
+
+/// Configuration for measured bigram timings.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::timing::{TimingConfig, TimingTarget};
+/// let timing: TimingConfig = toml::from_str(r#"
+///     path = "./timings.csv"
+///     replaces = "fspeed"
+/// "#).unwrap();
+///
+/// assert_eq!(timing.replaces, TimingTarget::Fspeed);
+/// assert!(timing.table.is_empty());
+/// ```
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct TimingConfig {
+    /// Path to a csv or json file with timings.
+    pub path: PathBuf,
+    /// Metric the timings are used for. Typing time is always estimated.
+    #[serde(default)]
+    pub replaces: TimingTarget,
+    /// The loaded timings, which are empty until loaded with
+    /// [`Config::with_loaded_weights`](crate::weights::Config::with_loaded_weights).
+    #[serde(skip)]
+    pub table: TimingTable,
+}
+
+/// Measured times of bigrams, either per pair of key positions or per pair of fingers. Times
+/// of key positions take precedence over those of fingers.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::timing::TimingTable;
+/// use libdof::prelude::Finger;
+///
+/// let table = TimingTable::from_csv("from,to,ms\n0,1,120\nli,li,180\n").unwrap();
+///
+/// assert_eq!(table.get(0, 1, Finger::LP, Finger::LR), Some(120.0));
+/// assert_eq!(table.get(1, 0, Finger::LR, Finger::LP), None);
+/// assert_eq!(table.get(3, 13, Finger::LI, Finger::LI), Some(180.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimingTable {
+    positions: HashMap<(u8, u8), f64>,
+    fingers: HashMap<(u8, u8), f64>,
+}
+
+impl TimingTable {
+    /// Creates a table from a list of measurements. Both sides of an entry need to be either a
+    /// position or a finger.
+    pub fn from_entries(entries: impl IntoIterator<Item = TimingEntry>) -> Result<Self> {
+        let mut table = Self::default();
+
+        for TimingEntry { from, to, ms } in entries {
+            match (from, to) {
+                (TimingKey::Position(p1), TimingKey::Position(p2)) => {
+                    table.positions.insert((p1, p2), ms);
+                }
+                (TimingKey::Finger(f1), TimingKey::Finger(f2)) => {
+                    let key = (parse_finger(&f1)? as u8, parse_finger(&f2)? as u8);
+                    table.fingers.insert(key, ms);
+                }
+                (from, to) => {
+                    return Err(OxeylyzerError::InvalidTiming(format!(
+                        "{from:?} and {to:?} should both be positions or both be fingers"
+                    )));
+                }
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Parses a csv file with `from`, `to` and `ms` columns. Empty lines, lines starting with
+    /// `#` and a header line are skipped.
+    pub fn from_csv(s: &str) -> Result<Self> {
+        let entries = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+
+                let [from, to, ms] = fields[..] else {
+                    return Some(Err(OxeylyzerError::InvalidTiming(line.to_string())));
+                };
+
+                match ms.parse::<f64>() {
+                    Ok(ms) => Some(Ok(TimingEntry {
+                        from: TimingKey::parse(from),
+                        to: TimingKey::parse(to),
+                        ms,
+                    })),
+                    Err(_) if i == 0 => None,
+                    Err(_) => Some(Err(OxeylyzerError::InvalidTiming(line.to_string()))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::from_entries(entries)
+    }
+
+    /// Parses a json array of [`TimingEntry`] objects.
+    pub fn from_json(s: &str) -> Result<Self> {
+        let entries = serde_json::from_str::<Vec<TimingEntry>>(s).str_context("timing data")?;
+
+        Self::from_entries(entries)
+    }
+
+    /// Loads a table from a file, which is parsed as csv if it has a `.csv` extension and as
+    /// json otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(&path).path_context(&path)?;
+
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("csv") => Self::from_csv(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+
+    /// Whether the table doesn't contain any measurements.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty() && self.fingers.is_empty()
+    }
+
+    /// Returns the time it takes to go from one key to another, if it was measured.
+    pub fn get(&self, p1: u8, p2: u8, f1: Finger, f2: Finger) -> Option<f64> {
+        self.positions
+            .get(&(p1, p2))
+            .or_else(|| self.fingers.get(&(f1 as u8, f2 as u8)))
+            .copied()
+    }
+
+    /// Returns the average time of a pair of keys in both directions, using whichever
+    /// direction was measured if only one of them was.
+    pub fn pair_cost(&self, p1: u8, p2: u8, f1: Finger, f2: Finger) -> Option<f64> {
+        match (self.get(p1, p2, f1, f2), self.get(p2, p1, f2, f1)) {
+            (Some(a), Some(b)) => Some((a + b) / 2.0),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+impl Oxeylyzer {
+    /// Returns the timing table if timings are configured to replace `target`.
+    pub(crate) fn timing_for(&self, target: TimingTarget) -> Option<&TimingTable> {
+        self.timing
+            .as_ref()
+            .filter(|t| t.replaces == target && !t.table.is_empty())
+            .map(|t| &t.table)
+    }
+
+    /// Estimates the average time per bigram in milliseconds, combining bigram frequencies with
+    /// measured timings. Only bigrams that have a measured time are taken into account. Returns
+    /// `None` if no timings are configured or none of them apply to the layout.
+    pub fn typing_time(&self, layout: &FastLayout) -> Option<f64> {
+        let table = &self.timing.as_ref()?.table;
+
+        let mut time = 0.0;
+        let mut total = 0;
+
+        for (p1, (&c1, &f1)) in layout.keys.iter().zip(layout.fingers.iter()).enumerate() {
+            for (p2, (&c2, &f2)) in layout.keys.iter().zip(layout.fingers.iter()).enumerate() {
+                if let Some(ms) = table.get(p1 as u8, p2 as u8, f1, f2) {
+                    let freq = self.data.get_bigram_u([c1, c2]);
+
+                    time += freq as f64 * ms;
+                    total += freq;
+                }
+            }
+        }
+
+        (total > 0).then(|| time / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_util::{base_path, oxeylyzer_with, qwerty},
+        weights::Config,
+    };
+
+    use libdof::prelude::Finger::*;
+
+    #[test]
+    fn parse_tables() {
+        let csv = "# measured on a 60% board\nfrom, to, ms\n\n0, 10, 150.5\nLI, ri, 90\n";
+        let table = TimingTable::from_csv(csv).unwrap();
+
+        assert_eq!(table.get(0, 10, LP, LP), Some(150.5));
+        assert_eq!(table.get(5, 6, LI, RI), Some(90.0));
+        assert_eq!(table.pair_cost(10, 0, LP, LP), Some(150.5));
+
+        let json = r#"[
+            { "from": 0, "to": 10, "ms": 150.5 },
+            { "from": "li", "to": "ri", "ms": 90 }
+        ]"#;
+        assert_eq!(TimingTable::from_json(json).unwrap(), table);
+
+        assert!(TimingTable::from_csv("0,1,100\n0,li,100").is_err());
+        assert!(TimingTable::from_csv("0,1,100\n0,1,fast").is_err());
+        assert!(TimingTable::from_csv("lx,li,100").is_err());
+    }
+
+    #[test]
+    fn path_relative_to_config() {
+        let base = base_path();
+        let dir = std::env::temp_dir().join(format!("oxeylyzer-timing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = std::fs::read_to_string(base.join("config.toml")).unwrap();
+        std::fs::write(
+            dir.join("config.toml"),
+            format!("{config}\n[timing]\npath = \"timings.csv\"\n"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("timings.csv"), "li,li,185\n").unwrap();
+
+        let config = Config::with_loaded_weights(dir.join("config.toml")).unwrap();
+        let timing = config.timing.unwrap();
+        assert_eq!(timing.path, dir.join("timings.csv"));
+        assert_eq!(timing.table.get(3, 13, LI, LI), Some(185.0));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn typing_time_and_fspeed() {
+        let csv = Finger::FINGERS
+            .iter()
+            .flat_map(|f1| Finger::FINGERS.map(|f2| (*f1, f2)))
+            .map(|(f1, f2)| {
+                let ms = if f1 == f2 { 200 } else { 100 };
+                format!(
+                    "{},{},{ms}\n",
+                    f1.to_string().to_lowercase(),
+                    f2.to_string().to_lowercase()
+                )
+            })
+            .collect::<String>();
+
+        let timing = TimingConfig {
+            replaces: TimingTarget::Fspeed,
+            table: TimingTable::from_csv(&csv).unwrap(),
+            ..Default::default()
+        };
+        let oxeylyzer = oxeylyzer_with(|config, _| config.timing = Some(timing));
+        let qwerty = oxeylyzer.fast_layout(&qwerty(), &[]);
+
+        let time = oxeylyzer.typing_time(&qwerty).unwrap();
+        assert!(time > 100.0 && time < 200.0);
+
+        // timings are scaled by finger weights like distances are
+        let finger_weights = &oxeylyzer.weights.finger_weights;
+        for (finger, pairs) in Finger::FINGERS
+            .iter()
+            .zip(qwerty.fspeed_indices.fingers.iter())
+        {
+            let dist = (200.0 * (finger_weights.max() / finger_weights.get(*finger))) as i64;
+            assert!(pairs.iter().all(|pair| pair.dist == dist));
+        }
+    }
+}
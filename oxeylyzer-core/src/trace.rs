@@ -0,0 +1,254 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{OxeylyzerError, OxeylyzerResultExt, Result};
+
+/// Telemetry collected while optimizing a single layout.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::trace::OptimizationTrace;
+/// let trace = OptimizationTrace::default();
+/// assert_eq!(trace.final_score(), 0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptimizationTrace {
+    /// Score of the layout before any swaps were made.
+    pub initial_score: i64,
+    /// Score of the layout after each accepted swap.
+    pub scores: Vec<i64>,
+    /// Amount of swaps that were accepted.
+    pub swaps: usize,
+    /// Whether optimization stopped because it hit the maximum amount of swaps.
+    pub hit_cap: bool,
+    /// Wall time the optimization took.
+    pub duration: Duration,
+}
+
+impl OptimizationTrace {
+    /// Returns the score the layout ended up with.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::trace::OptimizationTrace;
+    /// let trace = OptimizationTrace {
+    ///     initial_score: -10,
+    ///     scores: vec![-8, -5],
+    ///     swaps: 2,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(trace.final_score(), -5);
+    /// ```
+    pub fn final_score(&self) -> i64 {
+        self.scores.last().copied().unwrap_or(self.initial_score)
+    }
+}
+
+/// A single bucket of a [`TraceReport::histogram`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramBin {
+    /// Lower bound of the bucket, inclusive.
+    pub start: f64,
+    /// Upper bound of the bucket, exclusive except for the last bucket.
+    pub end: f64,
+    /// Amount of runs with a final score in this bucket.
+    pub count: usize,
+}
+
+/// Collection of [`OptimizationTrace`]s of a batch of generation jobs, which can be summarized
+/// and exported. Scores are divided by `scale` when reported, which makes them match the scores
+/// shown elsewhere.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::trace::{OptimizationTrace, TraceReport};
+/// let traces = vec![
+///     OptimizationTrace { hit_cap: true, ..Default::default() },
+///     OptimizationTrace::default(),
+/// ];
+/// let report = TraceReport::new(traces, 1.0);
+///
+/// assert_eq!(report.cap_hit_percentage(), 50.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceReport {
+    /// Traces of every job.
+    pub traces: Vec<OptimizationTrace>,
+    /// Value raw scores are divided by when reported.
+    pub scale: f64,
+}
+
+#[derive(Serialize)]
+struct ExportedTrace {
+    initial_score: f64,
+    scores: Vec<f64>,
+    final_score: f64,
+    swaps: usize,
+    hit_cap: bool,
+    wall_time_ms: f64,
+}
+
+impl TraceReport {
+    /// Creates a new report from a list of traces.
+    pub fn new(traces: Vec<OptimizationTrace>, scale: f64) -> Self {
+        Self { traces, scale }
+    }
+
+    fn scaled(&self, score: i64) -> f64 {
+        score as f64 / self.scale
+    }
+
+    /// Returns the scaled final score of every run.
+    pub fn final_scores(&self) -> Vec<f64> {
+        self.traces
+            .iter()
+            .map(|t| self.scaled(t.final_score()))
+            .collect()
+    }
+
+    /// Returns the percentage of runs that stopped because they hit the swap cap.
+    pub fn cap_hit_percentage(&self) -> f64 {
+        if self.traces.is_empty() {
+            return 0.0;
+        }
+
+        let hit = self.traces.iter().filter(|t| t.hit_cap).count();
+
+        hit as f64 / self.traces.len() as f64 * 100.0
+    }
+
+    /// Returns the average amount of accepted swaps per run.
+    pub fn average_swaps(&self) -> f64 {
+        if self.traces.is_empty() {
+            return 0.0;
+        }
+
+        let total = self.traces.iter().map(|t| t.swaps).sum::<usize>();
+
+        total as f64 / self.traces.len() as f64
+    }
+
+    /// Returns the total wall time of all runs combined.
+    pub fn total_duration(&self) -> Duration {
+        self.traces.iter().map(|t| t.duration).sum()
+    }
+
+    /// Groups the final scores of all runs into `bins` equally sized buckets.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::trace::{OptimizationTrace, TraceReport};
+    /// let traces = [-10, -9, -1]
+    ///     .into_iter()
+    ///     .map(|initial_score| OptimizationTrace { initial_score, ..Default::default() })
+    ///     .collect();
+    /// let report = TraceReport::new(traces, 1.0);
+    /// let histogram = report.histogram(3);
+    ///
+    /// assert_eq!(histogram.len(), 3);
+    /// assert_eq!(histogram[0].count, 2);
+    /// assert_eq!(histogram[2].count, 1);
+    /// ```
+    pub fn histogram(&self, bins: usize) -> Vec<HistogramBin> {
+        let scores = self.final_scores();
+
+        if scores.is_empty() || bins == 0 {
+            return Vec::new();
+        }
+
+        let min = scores.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = (max - min) / bins as f64;
+
+        let mut histogram = (0..bins)
+            .map(|i| HistogramBin {
+                start: min + width * i as f64,
+                end: min + width * (i + 1) as f64,
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+
+        for score in scores {
+            let i = match width > 0.0 {
+                true => (((score - min) / width) as usize).min(bins - 1),
+                false => 0,
+            };
+            histogram[i].count += 1;
+        }
+
+        histogram
+    }
+
+    fn exported(&self) -> Vec<ExportedTrace> {
+        self.traces
+            .iter()
+            .map(|t| ExportedTrace {
+                initial_score: self.scaled(t.initial_score),
+                scores: t.scores.iter().map(|&s| self.scaled(s)).collect(),
+                final_score: self.scaled(t.final_score()),
+                swaps: t.swaps,
+                hit_cap: t.hit_cap,
+                wall_time_ms: t.duration.as_secs_f64() * 1000.0,
+            })
+            .collect()
+    }
+
+    /// Formats all traces as CSV, with one row per accepted swap of every run. Swap 0 is the
+    /// score before optimizing.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::trace::{OptimizationTrace, TraceReport};
+    /// let trace = OptimizationTrace {
+    ///     initial_score: -4,
+    ///     scores: vec![-2],
+    ///     swaps: 1,
+    ///     ..Default::default()
+    /// };
+    /// let csv = TraceReport::new(vec![trace], 2.0).to_csv();
+    ///
+    /// assert_eq!(
+    ///     csv,
+    ///     "run,swap,score,swaps,hit_cap,wall_time_ms\n0,0,-2,1,false,0\n0,1,-1,1,false,0\n"
+    /// );
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let mut buf = String::from("run,swap,score,swaps,hit_cap,wall_time_ms\n");
+
+        for (run, trace) in self.exported().into_iter().enumerate() {
+            let scores = std::iter::once(trace.initial_score).chain(trace.scores);
+
+            for (swap, score) in scores.enumerate() {
+                // writing to a string can't fail
+                let _ = writeln!(
+                    &mut buf,
+                    "{run},{swap},{score},{},{},{}",
+                    trace.swaps, trace.hit_cap, trace.wall_time_ms
+                );
+            }
+        }
+
+        buf
+    }
+
+    /// Formats all traces as a JSON array, one object per run.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.exported())
+            .map_err(|_| OxeylyzerError::CouldNotSerializeTrace)
+    }
+
+    /// Saves the report to a file. Uses CSV if the path ends in `.csv`, and JSON otherwise.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => self.to_csv(),
+            _ => self.to_json()?,
+        };
+
+        std::fs::write(path, content).path_context(path)
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
 use libdof::dofinitions::{Finger, Finger::*, Hand, Hand::*};
+use serde::Deserialize;
+
+use crate::{OxeylyzerError, Result};
 
 /// Represents various patterns formed by three consecutive keystrokes (trigrams).
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -29,12 +32,175 @@ pub enum TrigramPattern {
     BadSfb,
     /// Same-Finger Trigram (all three strokes on the same finger).
     Sft,
-    /// Trigram involves a thumb key.
+    /// Trigram alternates hands and involves a thumb key.
+    ThumbAlternate,
+    /// Trigram involves a thumb key and rolls, either with two keys on one hand and one on the
+    /// other, or with all three on one hand in a single direction.
+    ThumbRoll,
+    /// Trigram involves a thumb key and changes direction on one hand.
+    ThumbRedirect,
+    /// Trigram involves a thumb key, but contains a same finger bigram.
     Thumb,
     /// Any other pattern.
     Other,
     /// Invalid trigram.
     Invalid,
+    /// User-defined category, indexing into [`TrigramRules::categories`].
+    Custom(u8),
+}
+
+/// A user-defined trigram category with its own weight.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TrigramCategory {
+    /// Name used to refer to this category in overrides and in stats.
+    pub name: String,
+    /// Weight applied to the frequency of trigrams in this category.
+    pub weight: f64,
+}
+
+/// Changes the category of a single finger combination.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TrigramOverride {
+    /// The three fingers of the trigram, in order, e.g. `["lp", "lr", "lm"]`.
+    pub fingers: [String; 3],
+    /// Name of a built-in category like `redirects` or `bad_sfbs`, or of a custom category.
+    pub category: String,
+}
+
+/// Rules that customize how finger combinations are classified into [`TrigramPattern`]s.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::trigram_patterns::{TrigramPattern, TrigramRules};
+/// let rules: TrigramRules = toml::from_str(r#"
+///     bad_fingers = ["lp", "lr", "rr", "rp"]
+///
+///     [[categories]]
+///     name = "pinky_rolls"
+///     weight = 1.0
+///
+///     [[overrides]]
+///     fingers = ["lp", "lr", "ri"]
+///     category = "pinky_rolls"
+/// "#).unwrap();
+///
+/// let table = rules.pattern_table().unwrap();
+/// assert_eq!(table[0 * 100 + 1 * 10 + 6], TrigramPattern::Custom(0));
+/// ```
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TrigramRules {
+    /// Fingers that make a redirect bad if all three of its keys use one of them. By default
+    /// this is every finger except the index fingers and thumbs.
+    #[serde(default)]
+    pub bad_fingers: Option<Vec<String>>,
+    /// Custom categories, which can be assigned to finger combinations using overrides.
+    #[serde(default)]
+    pub categories: Vec<TrigramCategory>,
+    /// Finger combinations whose category is overridden.
+    #[serde(default)]
+    pub overrides: Vec<TrigramOverride>,
+}
+
+pub(crate) fn parse_finger(name: &str) -> Result<Finger> {
+    match name.to_lowercase().as_str() {
+        "lp" => Ok(LP),
+        "lr" => Ok(LR),
+        "lm" => Ok(LM),
+        "li" => Ok(LI),
+        "lt" => Ok(LT),
+        "rt" => Ok(RT),
+        "ri" => Ok(RI),
+        "rm" => Ok(RM),
+        "rr" => Ok(RR),
+        "rp" => Ok(RP),
+        _ => Err(OxeylyzerError::UnknownFinger(name.to_string())),
+    }
+}
+
+impl TrigramRules {
+    fn category(&self, name: &str) -> Result<TrigramPattern> {
+        use TrigramPattern::*;
+
+        let pattern = match name {
+            "alternates" => Alternate,
+            "alternates_sfs" => AlternateSfs,
+            "inrolls" => Inroll,
+            "outrolls" => Outroll,
+            "onehands" => Onehand,
+            "redirects" => Redirect,
+            "redirects_sfs" => RedirectSfs,
+            "bad_redirects" => BadRedirect,
+            "bad_redirects_sfs" => BadRedirectSfs,
+            "sfbs" => Sfb,
+            "bad_sfbs" => BadSfb,
+            "sfts" => Sft,
+            "thumb_alternates" => ThumbAlternate,
+            "thumb_rolls" => ThumbRoll,
+            "thumb_redirects" => ThumbRedirect,
+            "thumbs" => Thumb,
+            "other" => Other,
+            "invalid" => Invalid,
+            _ => match self.categories.iter().position(|c| c.name == name) {
+                Some(i) => Custom(i as u8),
+                None => return Err(OxeylyzerError::UnknownTrigramCategory(name.to_string())),
+            },
+        };
+
+        Ok(pattern)
+    }
+
+    fn bad_fingers(&self) -> Result<[bool; 10]> {
+        match &self.bad_fingers {
+            Some(names) => {
+                let mut bad = [false; 10];
+                for name in names {
+                    bad[parse_finger(name)? as usize] = true;
+                }
+                Ok(bad)
+            }
+            None => Ok(Finger::FINGERS.map(|f| f.is_bad())),
+        }
+    }
+
+    /// Checks whether all fingers and categories these rules refer to exist.
+    pub fn validate(&self) -> Result<()> {
+        self.pattern_table().map(|_| ())
+    }
+
+    /// Builds the table that maps every combination of three fingers to its pattern, with the
+    /// index being `f1 * 100 + f2 * 10 + f3`.
+    pub fn pattern_table(&self) -> Result<Arc<[TrigramPattern; 1000]>> {
+        if self.categories.len() > u8::MAX as usize + 1 {
+            return Err(OxeylyzerError::TooManyTrigramCategories(
+                u8::MAX as usize + 1,
+            ));
+        }
+
+        let bad = self.bad_fingers()?;
+        let mut combinations = [TrigramPattern::Other; 1000];
+
+        for f1 in Finger::FINGERS {
+            for f2 in Finger::FINGERS {
+                for f3 in Finger::FINGERS {
+                    let index = (f1 as usize) * 100 + (f2 as usize) * 10 + (f3 as usize);
+                    let trigram = Trigram::with_bad_fingers(f1, f2, f3, bad);
+                    combinations[index] = trigram.get_trigram_pattern();
+                }
+            }
+        }
+
+        for TrigramOverride { fingers, category } in &self.overrides {
+            let [f1, f2, f3] = [
+                parse_finger(&fingers[0])?,
+                parse_finger(&fingers[1])?,
+                parse_finger(&fingers[2])?,
+            ];
+            let index = (f1 as usize) * 100 + (f2 as usize) * 10 + (f3 as usize);
+            combinations[index] = self.category(category)?;
+        }
+
+        Ok(Arc::new(combinations))
+    }
 }
 
 trait TrigramFinger {
@@ -73,6 +239,7 @@ pub(crate) struct Trigram {
     h1: Hand,
     h2: Hand,
     h3: Hand,
+    bad: [bool; 10],
 }
 
 impl std::fmt::Display for Trigram {
@@ -82,7 +249,12 @@ impl std::fmt::Display for Trigram {
 }
 
 impl Trigram {
+    #[cfg(test)]
     fn new(f1: Finger, f2: Finger, f3: Finger) -> Self {
+        Self::with_bad_fingers(f1, f2, f3, Finger::FINGERS.map(|f| f.is_bad()))
+    }
+
+    fn with_bad_fingers(f1: Finger, f2: Finger, f3: Finger, bad: [bool; 10]) -> Self {
         Trigram {
             f1,
             f2,
@@ -90,9 +262,14 @@ impl Trigram {
             h1: f1.hand(),
             h2: f2.hand(),
             h3: f3.hand(),
+            bad,
         }
     }
 
+    fn is_bad(&self, f: Finger) -> bool {
+        self.bad[f as usize]
+    }
+
     fn is_thumb(&self) -> bool {
         self.f1.is_thumb() || self.f2.is_thumb() || self.f3.is_thumb()
     }
@@ -159,7 +336,7 @@ impl Trigram {
     }
 
     fn is_bad_redir(&self) -> bool {
-        self.is_redir() && self.f1.is_bad() && self.f2.is_bad() && self.f3.is_bad()
+        self.is_redir() && self.is_bad(self.f1) && self.is_bad(self.f2) && self.is_bad(self.f3)
     }
 
     fn has_sfb(&self) -> bool {
@@ -189,9 +366,23 @@ impl Trigram {
         }
     }
 
+    fn get_thumb(&self) -> TrigramPattern {
+        use TrigramPattern::*;
+
+        if self.has_sfb() {
+            Thumb
+        } else if self.is_alt() {
+            ThumbAlternate
+        } else if self.is_redir() {
+            ThumbRedirect
+        } else {
+            ThumbRoll
+        }
+    }
+
     fn get_trigram_pattern(&self) -> TrigramPattern {
         if self.is_thumb() {
-            TrigramPattern::Thumb
+            self.get_thumb()
         } else if self.is_alt() {
             self.get_alternate()
         } else if self.on_one_hand() {
@@ -206,7 +397,8 @@ impl Trigram {
     }
 }
 
-/// Pre-calculates and returns a mapping of all possible finger combinations to their trigram patterns.
+/// Pre-calculates and returns a mapping of all possible finger combinations to their trigram
+/// patterns, using the default [`TrigramRules`].
 ///
 /// # Examples:
 /// ```
@@ -216,39 +408,18 @@ impl Trigram {
 /// assert_eq!(combinations.len(), 1000);
 /// ```
 pub fn get_trigram_combinations() -> Arc<[TrigramPattern; 1000]> {
-    let mut combinations = [TrigramPattern::Other; 1000];
-
-    for f3 in Finger::FINGERS {
-        for f2 in Finger::FINGERS {
-            for f1 in Finger::FINGERS {
-                let index = (f3 as usize) * 100 + (f2 as usize) * 10 + (f1 as usize);
-                let trigram = Trigram::new(f3, f2, f1);
-                combinations[index] = trigram.get_trigram_pattern();
-            }
-        }
-    }
-
-    Arc::new(combinations)
+    // the default rules don't refer to anything, so they can't be invalid
+    TrigramRules::default()
+        .pattern_table()
+        .expect("default trigram rules are valid")
 }
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
-
     use super::{TrigramPattern::*, *};
-    use crate::{
-        data::Data, fast_layout::FastLayout, generate::Oxeylyzer, layout::Layout, weights::Config,
-    };
+    use crate::{fast_layout::FastLayout, layout::Layout, test_util::GEN};
     use once_cell::sync::Lazy;
 
-    static GEN: Lazy<Oxeylyzer> = Lazy::new(|| {
-        let base = PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
-        let config = Config::with_loaded_weights(base.join("config.toml")).unwrap();
-        let data = Data::load(base.join(&config.corpus)).unwrap();
-
-        Oxeylyzer::new(data, config)
-    });
-
     static DVORAK: Lazy<FastLayout> = Lazy::new(|| {
         let dof_str = r#"
             {
@@ -389,6 +560,78 @@ mod tests {
         assert!(t4.is_bad_redir());
     }
 
+    #[test]
+    fn thumbs() {
+        assert_eq!(
+            Trigram::new(LM, RT, LI).get_trigram_pattern(),
+            ThumbAlternate
+        );
+        assert_eq!(
+            Trigram::new(RT, LM, RT).get_trigram_pattern(),
+            ThumbAlternate
+        );
+        assert_eq!(Trigram::new(LR, LM, RT).get_trigram_pattern(), ThumbRoll);
+        assert_eq!(Trigram::new(LT, RI, RM).get_trigram_pattern(), ThumbRoll);
+        assert_eq!(Trigram::new(LM, LI, LT).get_trigram_pattern(), ThumbRoll);
+        assert_eq!(
+            Trigram::new(LI, LT, LM).get_trigram_pattern(),
+            ThumbRedirect
+        );
+        assert_eq!(Trigram::new(RT, RT, LI).get_trigram_pattern(), Thumb);
+    }
+
+    #[test]
+    fn configured_rules() {
+        let rules = TrigramRules {
+            bad_fingers: Some(vec!["lp".into(), "lr".into()]),
+            categories: vec![TrigramCategory {
+                name: "index_rolls".into(),
+                weight: 2.0,
+            }],
+            overrides: vec![
+                TrigramOverride {
+                    fingers: ["lm".into(), "li".into(), "rp".into()],
+                    category: "index_rolls".into(),
+                },
+                TrigramOverride {
+                    fingers: ["LP".into(), "LR".into(), "LM".into()],
+                    category: "redirects".into(),
+                },
+            ],
+        };
+        let table = rules.pattern_table().unwrap();
+        let index = |f1: Finger, f2: Finger, f3: Finger| {
+            (f1 as usize) * 100 + (f2 as usize) * 10 + (f3 as usize)
+        };
+
+        assert_eq!(table[index(LP, LR, LP)], BadRedirectSfs);
+        assert_eq!(table[index(LR, LP, LM)], Redirect);
+        assert_eq!(table[index(LM, LI, RP)], Custom(0));
+        assert_eq!(table[index(LP, LR, LM)], Redirect);
+        assert_eq!(table[index(RM, RI, LP)], Inroll);
+
+        let unknown_finger = TrigramRules {
+            bad_fingers: Some(vec!["lx".into()]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            unknown_finger.validate(),
+            Err(OxeylyzerError::UnknownFinger(f)) if f == "lx"
+        ));
+
+        let unknown_category = TrigramRules {
+            overrides: vec![TrigramOverride {
+                fingers: ["lm".into(), "li".into(), "rp".into()],
+                category: "index_rolls".into(),
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            unknown_category.validate(),
+            Err(OxeylyzerError::UnknownTrigramCategory(c)) if c == "index_rolls"
+        ));
+    }
+
     #[test]
     fn trigram_combinations() {
         assert_eq!(
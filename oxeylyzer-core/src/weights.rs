@@ -3,7 +3,11 @@ use serde::{Deserialize, Serialize};
 use serde_with::{OneOrMany, serde_as};
 use std::path::{Path, PathBuf};
 
-use crate::{OxeylyzerError, OxeylyzerResultExt, Result};
+use crate::{
+    OxeylyzerError, OxeylyzerResultExt, Result,
+    timing::{TimingConfig, TimingTable},
+    trigram_patterns::TrigramRules,
+};
 
 /// Configuration for penalizing excessive finger usage.
 #[derive(Deserialize, Clone, Debug, Default)]
@@ -22,6 +26,92 @@ pub struct MaxFingerUse {
     pub thumb: f64,
 }
 
+/// Presets that derive the effort of every key from the geometry of the board.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EffortPreset {
+    /// One for every home key, plus the distance to the home key of its finger in key widths.
+    #[default]
+    Distance,
+    /// Like [`Distance`](Self::Distance), but multiplied by how much weaker the finger is than
+    /// the strongest finger according to [`FingerWeights`].
+    FingerStrength,
+}
+
+/// Configuration for the effort it takes to press every key.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::weights::{EffortPreset, KeyEffort};
+/// let key_effort: KeyEffort = toml::from_str("weight = -0.5").unwrap();
+///
+/// assert_eq!(key_effort.preset, EffortPreset::Distance);
+/// assert_eq!(key_effort.grid, None);
+/// ```
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct KeyEffort {
+    /// Penalty multiplied by the effort of every keystroke. Disabled when 0.
+    #[serde(default)]
+    pub weight: f64,
+    /// Preset used to derive key effort from the board when no grid is provided.
+    #[serde(default)]
+    pub preset: EffortPreset,
+    /// Effort of every key, row by row in the same order as the layout. Layouts that don't have
+    /// exactly one key per value use the preset instead, see [`grid_len`](Self::grid_len).
+    #[serde(default)]
+    pub grid: Option<Vec<Vec<f64>>>,
+}
+
+impl KeyEffort {
+    /// Amount of values in the grid, which is the amount of keys a layout needs to have for the
+    /// grid to be used.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::weights::KeyEffort;
+    /// let key_effort: KeyEffort = toml::from_str("grid = [[1.0, 2.0], [3.0]]").unwrap();
+    ///
+    /// assert_eq!(key_effort.grid_len(), Some(3));
+    /// assert_eq!(KeyEffort::default().grid_len(), None);
+    /// ```
+    pub fn grid_len(&self) -> Option<usize> {
+        self.grid
+            .as_ref()
+            .map(|rows| rows.iter().map(Vec::len).sum())
+    }
+}
+
+/// Ratios that same finger skipgrams are weighted with compared to same finger bigrams, for
+/// every distance. Distances that aren't configured fall back to a default.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::weights::SkipgramRatios;
+/// let ratios: SkipgramRatios = toml::from_str("dsfb2 = 0.05").unwrap();
+///
+/// assert_eq!(ratios.resolve([0.2, 0.04, 0.008]), [0.2, 0.05, 0.008]);
+/// ```
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SkipgramRatios {
+    /// Ratio of skipgrams with one key in between.
+    pub dsfb: Option<f64>,
+    /// Ratio of skipgrams with two keys in between.
+    pub dsfb2: Option<f64>,
+    /// Ratio of skipgrams with three keys in between.
+    pub dsfb3: Option<f64>,
+}
+
+impl SkipgramRatios {
+    /// Returns the ratio of every distance, using `fallback` for those that aren't configured.
+    pub fn resolve(&self, fallback: [f64; 3]) -> [f64; 3] {
+        [
+            self.dsfb.unwrap_or(fallback[0]),
+            self.dsfb2.unwrap_or(fallback[1]),
+            self.dsfb3.unwrap_or(fallback[2]),
+        ]
+    }
+}
+
 #[derive(Deserialize, Clone, Debug, Default)]
 /// Holds weights used for calculating various layout penalties and rewards.
 ///
@@ -41,6 +131,14 @@ pub struct Weights {
     pub stretches: f64,
     /// Penalty for pinky-ring bigrams.
     pub pinky_ring_bigrams: f64,
+    /// Ratios of same finger skipgrams compared to sfbs in finger speed. By default every
+    /// distance is `sfs / sfbs` raised to the power of the distance.
+    #[serde(default)]
+    pub skipgram_ratios: SkipgramRatios,
+    /// Ratios of skipgrams compared to bigrams in stretches. By default these are the same as
+    /// the finger speed ratios.
+    #[serde(default)]
+    pub stretch_skipgram_ratios: SkipgramRatios,
     /// Reward for inward rolls.
     pub inrolls: f64,
     /// Reward for outward rolls.
@@ -59,12 +157,73 @@ pub struct Weights {
     pub bad_redirects: f64,
     /// Penalty for uncomfortable redirects with same-finger skip.
     pub bad_redirects_sfs: f64,
+    /// Reward for alternating hands with a thumb key. Disabled by default.
+    #[serde(default)]
+    pub thumb_alternates: f64,
+    /// Reward for rolls involving a thumb key. Disabled by default.
+    #[serde(default)]
+    pub thumb_rolls: f64,
+    /// Penalty for redirects involving a thumb key. Disabled by default.
+    #[serde(default)]
+    pub thumb_redirects: f64,
+    /// Reward for one-handed quadgrams that roll in a single direction. Only used when
+    /// quadgrams are enabled.
+    #[serde(default)]
+    pub quadgram_rolls: f64,
+    /// Reward for quadgrams that alternate hands on every stroke. Only used when quadgrams are
+    /// enabled.
+    #[serde(default)]
+    pub chained_alternates: f64,
+    /// Penalty for one-handed quadgrams that change direction twice. Only used when quadgrams
+    /// are enabled.
+    #[serde(default)]
+    pub double_redirects: f64,
+    /// Penalty for the distance fingers travel. Disabled by default.
+    #[serde(default)]
+    pub finger_travel: f64,
+    /// Effort it takes to press every key. Disabled by default.
+    #[serde(default)]
+    pub key_effort: KeyEffort,
+    /// Penalty for the difference in usage between the left and right hand. Disabled by
+    /// default.
+    #[serde(default)]
+    pub hand_balance: f64,
     /// Maximum usage thresholds for fingers.
     pub max_finger_use: MaxFingerUse,
     /// Specific weights for each finger.
     pub finger_weights: FingerWeights,
 }
 
+impl Weights {
+    /// Ratios that same finger skipgrams at distance 1, 2 and 3 are weighted with compared to
+    /// sfbs in finger speed.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::weights::Config;
+    /// let mut weights = Config::with_defaults().weights;
+    /// weights.sfbs = -8.0;
+    /// weights.sfs = -2.0;
+    ///
+    /// assert_eq!(weights.dsfb_ratios(), [0.25, 0.0625, 0.015625]);
+    ///
+    /// weights.skipgram_ratios.dsfb3 = Some(0.0);
+    /// assert_eq!(weights.dsfb_ratios(), [0.25, 0.0625, 0.0]);
+    /// ```
+    pub fn dsfb_ratios(&self) -> [f64; 3] {
+        let ratio = self.sfs / self.sfbs;
+
+        self.skipgram_ratios
+            .resolve([ratio, ratio.powi(2), ratio.powi(3)])
+    }
+
+    /// Ratios that skipgrams at distance 1, 2 and 3 are weighted with compared to bigrams in
+    /// stretches.
+    pub fn stretch_dsfb_ratios(&self) -> [f64; 3] {
+        self.stretch_skipgram_ratios.resolve(self.dsfb_ratios())
+    }
+}
+
 #[derive(Deserialize, Clone, Debug, Default)]
 /// Max finger usage thresholds scaled for internal calculations.
 ///
@@ -88,6 +247,23 @@ pub struct AnalyzerMaxFingerUse {
     pub thumb: i64,
 }
 
+#[derive(Deserialize, Clone, Debug, Default)]
+/// Key effort configuration scaled for internal calculations.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::weights::AnalyzerKeyEffort;
+/// let analyzer = AnalyzerKeyEffort::default();
+/// ```
+pub struct AnalyzerKeyEffort {
+    /// Scaled effort penalty.
+    pub weight: i64,
+    /// Preset used when there is no grid.
+    pub preset: EffortPreset,
+    /// Flattened effort of every key.
+    pub grid: Option<Box<[f64]>>,
+}
+
 #[derive(Deserialize, Clone, Debug, Default)]
 /// Analyzer weights scaled for internal integer calculations.
 ///
@@ -107,6 +283,8 @@ pub struct AnalyzerWeights {
     pub stretches: i64,
     /// Scaled pinky-ring bigram penalty.
     pub pinky_ring_bigrams: i64,
+    /// Ratios of same finger skipgrams at distance 1, 2 and 3 compared to sfbs.
+    pub dsfb_ratios: [f64; 3],
     /// Scaled inroll reward.
     pub inrolls: i64,
     /// Scaled outroll reward.
@@ -125,6 +303,24 @@ pub struct AnalyzerWeights {
     pub bad_redirects: i64,
     /// Scaled bad redirects with SFS penalty.
     pub bad_redirects_sfs: i64,
+    /// Scaled thumb alternates reward.
+    pub thumb_alternates: i64,
+    /// Scaled thumb rolls reward.
+    pub thumb_rolls: i64,
+    /// Scaled thumb redirects penalty.
+    pub thumb_redirects: i64,
+    /// Scaled quadgram rolls reward.
+    pub quadgram_rolls: i64,
+    /// Scaled chained alternates reward.
+    pub chained_alternates: i64,
+    /// Scaled double redirects penalty.
+    pub double_redirects: i64,
+    /// Scaled finger travel penalty.
+    pub finger_travel: i64,
+    /// Key effort configuration.
+    pub key_effort: AnalyzerKeyEffort,
+    /// Scaled hand balance penalty.
+    pub hand_balance: i64,
     /// Specific weights for each finger.
     pub finger_weights: FingerWeights,
     /// Max finger usage thresholds.
@@ -134,6 +330,7 @@ pub struct AnalyzerWeights {
 impl From<Weights> for AnalyzerWeights {
     fn from(weights: Weights) -> Self {
         let scale = |float| (float * 100.0) as i64;
+        let dsfb_ratios = weights.dsfb_ratios();
 
         let max_finger_use = AnalyzerMaxFingerUse {
             penalty: scale(weights.max_finger_use.penalty),
@@ -144,12 +341,22 @@ impl From<Weights> for AnalyzerWeights {
             thumb: weights.max_finger_use.thumb as i64,
         };
 
+        let key_effort = AnalyzerKeyEffort {
+            weight: scale(weights.key_effort.weight),
+            preset: weights.key_effort.preset,
+            grid: weights
+                .key_effort
+                .grid
+                .map(|rows| rows.into_iter().flatten().collect()),
+        };
+
         Self {
             lateral_penalty: scale(weights.lateral_penalty),
             sfbs: scale(weights.sfbs),
             sfs: scale(weights.sfs),
             stretches: scale(weights.stretches),
             pinky_ring_bigrams: scale(weights.pinky_ring_bigrams),
+            dsfb_ratios,
             inrolls: scale(weights.inrolls),
             outrolls: scale(weights.outrolls),
             onehands: scale(weights.onehands),
@@ -159,6 +366,15 @@ impl From<Weights> for AnalyzerWeights {
             redirects_sfs: scale(weights.redirects_sfs),
             bad_redirects: scale(weights.bad_redirects),
             bad_redirects_sfs: scale(weights.bad_redirects_sfs),
+            thumb_alternates: scale(weights.thumb_alternates),
+            thumb_rolls: scale(weights.thumb_rolls),
+            thumb_redirects: scale(weights.thumb_redirects),
+            quadgram_rolls: scale(weights.quadgram_rolls),
+            chained_alternates: scale(weights.chained_alternates),
+            double_redirects: scale(weights.double_redirects),
+            finger_travel: scale(weights.finger_travel),
+            key_effort,
+            hand_balance: scale(weights.hand_balance),
             finger_weights: weights.finger_weights,
             max_finger_use,
         }
@@ -184,10 +400,23 @@ pub struct Config {
     pub corpus_configs: PathBuf,
     /// Scaling factor for trigram precision.
     pub trigram_precision: usize,
+    /// Amount of most frequent quadgrams used for scoring. 0 disables quadgram analysis.
+    #[serde(default)]
+    pub quadgram_precision: usize,
     /// Max number of threads/cores to use.
     pub max_cores: usize,
     /// Configured weights for the generator.
     pub weights: Weights,
+    /// Rules for classifying trigram patterns.
+    #[serde(default)]
+    pub trigram_rules: TrigramRules,
+    /// Thresholds and hand measurements used to find scissors, lsbs and stretches.
+    #[serde(default)]
+    pub geometry: Geometry,
+    /// Measured bigram timings, used to estimate typing time and optionally to replace finger
+    /// speed or stretch distances.
+    #[serde(default)]
+    pub timing: Option<TimingConfig>,
 }
 
 impl Config {
@@ -201,7 +430,18 @@ impl Config {
     pub fn with_loaded_weights<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(&path).path_context(&path)?;
 
-        toml::from_str::<Self>(&content).path_context(path)
+        let mut config = toml::from_str::<Self>(&content).path_context(&path)?;
+        config.trigram_rules.validate()?;
+        config.weights.finger_weights.validate()?;
+
+        // timings are relative to the config, like the sources of corpus configs
+        if let Some(timing) = config.timing.as_mut() {
+            let dir = path.as_ref().parent().unwrap_or(Path::new("./"));
+            timing.path = dir.join(&timing.path);
+            timing.table = TimingTable::load(&timing.path)?;
+        }
+
+        Ok(config)
     }
 
     /// Creates a configuration with default values.
@@ -217,6 +457,7 @@ impl Config {
             layouts: vec![PathBuf::from("./static/layouts/english")],
             corpus_configs: PathBuf::from("./static/corpus_configs/**/"),
             trigram_precision: 100000,
+            quadgram_precision: 0,
             max_cores: 128,
             weights: Weights {
                 lateral_penalty: 1.3,
@@ -224,6 +465,8 @@ impl Config {
                 sfs: -1.0,
                 stretches: -0.3,
                 pinky_ring_bigrams: -0.0,
+                skipgram_ratios: SkipgramRatios::default(),
+                stretch_skipgram_ratios: SkipgramRatios::default(),
                 inrolls: 1.6,
                 outrolls: 1.3,
                 onehands: 0.8,
@@ -233,6 +476,15 @@ impl Config {
                 redirects_sfs: -2.75,
                 bad_redirects: -4.0,
                 bad_redirects_sfs: -6.0,
+                thumb_alternates: 0.0,
+                thumb_rolls: 0.0,
+                thumb_redirects: 0.0,
+                quadgram_rolls: 0.0,
+                chained_alternates: 0.0,
+                double_redirects: 0.0,
+                finger_travel: 0.0,
+                key_effort: KeyEffort::default(),
+                hand_balance: 0.0,
                 finger_weights: FingerWeights {
                     lp: 1.4,
                     lr: 3.6,
@@ -254,6 +506,9 @@ impl Config {
                     thumb: 22.0,
                 },
             },
+            trigram_rules: TrigramRules::default(),
+            geometry: Geometry::default(),
+            timing: None,
         }
     }
 
@@ -268,6 +523,17 @@ impl Config {
         self.trigram_precision
     }
 
+    /// Gets the quadgram precision. Quadgrams aren't used for scoring if this is 0.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::weights::Config;
+    /// assert_eq!(Config::with_defaults().quadgram_precision(), 0);
+    /// ```
+    pub fn quadgram_precision(&self) -> usize {
+        self.quadgram_precision
+    }
+
     /// Retrieves the name of the configured corpus.
     ///
     /// # Examples:
@@ -343,6 +609,30 @@ impl FingerWeights {
         }
     }
 
+    /// Checks that every finger weight is positive, as key effort and finger speed are divided
+    /// by them.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::weights::FingerWeights;
+    /// assert!(FingerWeights::default().validate().is_ok());
+    ///
+    /// let weights = FingerWeights { lt: 0.0, ..Default::default() };
+    /// assert!(weights.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        match Finger::FINGERS
+            .into_iter()
+            .find(|&f| !(self.get(f) > 0.0 && self.get(f).is_finite()))
+        {
+            Some(f) => Err(OxeylyzerError::InvalidFingerWeight(
+                f.to_string().to_lowercase(),
+                self.get(f),
+            )),
+            None => Ok(()),
+        }
+    }
+
     #[inline]
     /// Computes the maximum finger weight.
     ///
@@ -376,3 +666,150 @@ impl Default for FingerWeights {
         }
     }
 }
+
+/// How far adjacent fingers of the same hand overlap horizontally, in key widths. Negative
+/// values mean there's a gap between them, like between the index finger and thumb.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::weights::FingerOverlap;
+/// use libdof::prelude::Finger;
+///
+/// let overlap = FingerOverlap::default();
+/// assert_eq!(overlap.get(Finger::LP, Finger::LR), 0.8);
+/// assert_eq!(overlap.get(Finger::RR, Finger::RP), 0.8);
+/// assert_eq!(overlap.get(Finger::LP, Finger::LM), 0.0);
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FingerOverlap {
+    /// Overlap between the pinky and ring finger.
+    pub pinky_ring: f64,
+    /// Overlap between the ring and middle finger.
+    pub ring_middle: f64,
+    /// Overlap between the middle and index finger.
+    pub middle_index: f64,
+    /// Overlap between the index finger and thumb.
+    pub index_thumb: f64,
+}
+
+impl FingerOverlap {
+    /// Gets the overlap between two fingers, which is 0 if they aren't adjacent fingers on the
+    /// same hand.
+    pub fn get(&self, f1: Finger, f2: Finger) -> f64 {
+        use Finger::*;
+
+        match (f1, f2) {
+            (LP, LR) | (LR, LP) | (RR, RP) | (RP, RR) => self.pinky_ring,
+            (LR, LM) | (LM, LR) | (RM, RR) | (RR, RM) => self.ring_middle,
+            (LM, LI) | (LI, LM) | (RI, RM) | (RM, RI) => self.middle_index,
+            (LI, LT) | (LT, LI) | (RT, RI) | (RI, RT) => self.index_thumb,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Default for FingerOverlap {
+    fn default() -> Self {
+        Self {
+            pinky_ring: 0.8,
+            ring_middle: 0.4,
+            middle_index: 0.1,
+            index_thumb: -2.5,
+        }
+    }
+}
+
+/// How far the tip of every finger reaches relative to the others, in key heights. Keys are
+/// shifted down by the length of the finger pressing them, so longer fingers have higher values
+/// and reach the upper rows more easily.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::weights::FingerLengths;
+/// use libdof::prelude::Finger;
+///
+/// let lengths = FingerLengths::default();
+/// assert_eq!(lengths.get(Finger::LM), lengths.get(Finger::RM));
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FingerLengths {
+    /// Length of the pinkies.
+    pub pinky: f64,
+    /// Length of the ring fingers.
+    pub ring: f64,
+    /// Length of the middle fingers.
+    pub middle: f64,
+    /// Length of the index fingers.
+    pub index: f64,
+    /// Length of the thumbs.
+    pub thumb: f64,
+}
+
+impl FingerLengths {
+    /// Gets the length of a specific finger.
+    pub fn get(&self, f: Finger) -> f64 {
+        use Finger::*;
+
+        match f {
+            LP | RP => self.pinky,
+            LR | RR => self.ring,
+            LM | RM => self.middle,
+            LI | RI => self.index,
+            LT | RT => self.thumb,
+        }
+    }
+}
+
+impl Default for FingerLengths {
+    fn default() -> Self {
+        Self {
+            pinky: -0.15,
+            ring: 0.35,
+            middle: 0.25,
+            index: -0.3,
+            thumb: -1.8,
+        }
+    }
+}
+
+/// Thresholds and hand measurements used to decide which key pairs count as scissors, lateral
+/// stretch bigrams and stretches. Every value that isn't configured keeps its default.
+///
+/// # Examples:
+/// ```
+/// # use oxeylyzer_core::weights::Geometry;
+/// let geometry: Geometry = toml::from_str("scissor_dy = 1.5").unwrap();
+///
+/// assert_eq!(geometry.scissor_dy, 1.5);
+/// assert_eq!(geometry.lsb_dx, Geometry::default().lsb_dx);
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Geometry {
+    /// Vertical distance two keys on adjacent fingers need to exceed to form a scissor.
+    pub scissor_dy: f64,
+    /// Horizontal distance between a middle and index finger key from which it's a lateral
+    /// stretch bigram.
+    pub lsb_dx: f64,
+    /// Distance two keys on the same hand can be apart for every finger between them before
+    /// it counts as a stretch.
+    pub stretch_per_finger: f64,
+    /// Horizontal overlap between adjacent fingers.
+    pub finger_overlap: FingerOverlap,
+    /// Length of every finger.
+    pub finger_lengths: FingerLengths,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            scissor_dy: 1.9,
+            lsb_dx: 1.5,
+            stretch_per_finger: 1.35,
+            finger_overlap: FingerOverlap::default(),
+            finger_lengths: FingerLengths::default(),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use libdof::prelude::Finger;
+
+use crate::fast_layout::FastLayout;
+use crate::generate::Oxeylyzer;
+
+/// Word level statistics of a layout. Only words of at least two letters are taken into
+/// account, and percentages are relative to all of those words in the corpus. Everything is 0
+/// if the corpus was created without collecting words.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordStats {
+    /// Percentage of words that are typed entirely with one hand.
+    pub one_hand: f64,
+    /// Percentage of words that contain at least one same finger bigram.
+    pub with_sfb: f64,
+    /// Average amount of times the typing hand switches within a word.
+    pub hand_switches: f64,
+    /// Percentage of words containing a character that isn't on the layout.
+    pub invalid: f64,
+}
+
+/// How a single word is typed on a layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordAnalysis {
+    /// The word itself.
+    pub word: String,
+    /// Frequency of the word as a percentage of all words in the corpus.
+    pub freq: f64,
+    /// Amount of same finger bigrams in the word.
+    pub sfbs: usize,
+    /// Amount of times the typing hand switches within the word.
+    pub hand_switches: usize,
+}
+
+impl WordAnalysis {
+    /// Whether the word is typed entirely with one hand.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::words::WordAnalysis;
+    /// let analysis = WordAnalysis {
+    ///     word: "were".to_string(),
+    ///     freq: 0.3,
+    ///     sfbs: 0,
+    ///     hand_switches: 0,
+    /// };
+    /// assert!(analysis.is_one_hand());
+    /// ```
+    pub fn is_one_hand(&self) -> bool {
+        self.hand_switches == 0
+    }
+}
+
+impl Oxeylyzer {
+    /// Returns the fingers used for every character of a word, or `None` if any of them isn't
+    /// on the layout.
+    fn word_fingers(&self, layout: &FastLayout, word: &[u8]) -> Option<Vec<Finger>> {
+        word.iter()
+            .map(|&c| layout.char_to_finger.get(c as usize).copied().flatten())
+            .collect()
+    }
+
+    /// Counts the same finger bigrams and hand switches of a word, or returns `None` if it
+    /// contains a character that isn't on the layout.
+    fn word_counts(&self, layout: &FastLayout, word: &[u8]) -> Option<(usize, usize)> {
+        let fingers = self.word_fingers(layout, word)?;
+
+        let (sfbs, hand_switches) =
+            word.windows(2)
+                .zip(fingers.windows(2))
+                .fold((0, 0), |(sfbs, switches), (c, f)| {
+                    let sfb = c[0] != c[1] && f[0] == f[1];
+                    let switch = f[0].hand() != f[1].hand();
+
+                    (sfbs + sfb as usize, switches + switch as usize)
+                });
+
+        Some((sfbs, hand_switches))
+    }
+
+    /// Analyzes how a single word would be typed on a layout. Returns `None` if the word
+    /// contains a character that isn't on the layout.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use oxeylyzer_core::{data::Data, generate::Oxeylyzer, layout::Layout, weights::Config};
+    /// # let stronk = serde_json::from_str::<Layout>(include_str!(
+    /// #     concat!(std::env!("CARGO_MANIFEST_DIR"), "/static/layouts/gust.dof")
+    /// # )).unwrap();
+    /// let data = Data::load(concat!(
+    ///     std::env!("CARGO_MANIFEST_DIR"),
+    ///     "/../static/language_data/english.json"
+    /// )).unwrap();
+    /// let oxeylyzer = Oxeylyzer::new(data, Config::with_defaults()).unwrap();
+    /// let fast_layout = oxeylyzer.fast_layout(&stronk /* <-- Layout */, &[]);
+    ///
+    /// let analysis = oxeylyzer.analyze_word(&fast_layout, "layout").unwrap();
+    /// assert_eq!(analysis.word, "layout");
+    /// ```
+    pub fn analyze_word(&self, layout: &FastLayout, word: &str) -> Option<WordAnalysis> {
+        let u = word
+            .chars()
+            .map(|c| self.mapping.get_u(c))
+            .collect::<Vec<_>>();
+        let (sfbs, hand_switches) = self.word_counts(layout, &u)?;
+
+        let freq = self
+            .data
+            .words()
+            .iter()
+            .find(|(w, _)| **w == *u)
+            .map(|&(_, f)| f as f64 / self.data.word_total as f64 * 100.0)
+            .unwrap_or_default();
+
+        Some(WordAnalysis {
+            word: word.to_string(),
+            freq,
+            sfbs,
+            hand_switches,
+        })
+    }
+
+    /// Computes word level statistics for a layout.
+    pub fn word_stats(&self, layout: &FastLayout) -> WordStats {
+        let mut total = 0;
+        let mut one_hand = 0;
+        let mut with_sfb = 0;
+        let mut hand_switches = 0;
+        let mut invalid = 0;
+
+        for (word, freq) in self.data.words().iter().filter(|(w, _)| w.len() >= 2) {
+            total += freq;
+
+            match self.word_counts(layout, word) {
+                Some((sfbs, switches)) => {
+                    one_hand += if switches == 0 { *freq } else { 0 };
+                    with_sfb += if sfbs > 0 { *freq } else { 0 };
+                    hand_switches += switches as i64 * freq;
+                }
+                None => invalid += freq,
+            }
+        }
+
+        if total == 0 {
+            return WordStats::default();
+        }
+
+        let percent = |v: i64| v as f64 / total as f64 * 100.0;
+
+        WordStats {
+            one_hand: percent(one_hand),
+            with_sfb: percent(with_sfb),
+            hand_switches: hand_switches as f64 / (total - invalid).max(1) as f64,
+            invalid: percent(invalid),
+        }
+    }
+
+    /// Returns the `n` most frequent words that contain at least one same finger bigram on
+    /// the layout.
+    pub fn worst_words(&self, layout: &FastLayout, n: usize) -> Vec<WordAnalysis> {
+        self.data
+            .words()
+            .iter()
+            .filter_map(|(word, freq)| {
+                let (sfbs, hand_switches) = self.word_counts(layout, word)?;
+
+                (sfbs > 0).then(|| WordAnalysis {
+                    word: word.iter().map(|&u| self.mapping.get_c(u)).collect(),
+                    freq: *freq as f64 / self.data.word_total as f64 * 100.0,
+                    sfbs,
+                    hand_switches,
+                })
+            })
+            .take(n)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        REPLACEMENT_CHAR,
+        corpus_cleaner::CorpusCleaner,
+        data::{CollectionOptions, Data},
+        test_util::{oxeylyzer_with, qwerty},
+    };
+
+    use once_cell::sync::Lazy;
+
+    static GEN: Lazy<Oxeylyzer> = Lazy::new(|| {
+        oxeylyzer_with(|_, data| {
+            let words = [("the", 60), ("were", 20), ("ed", 10), ("a", 10)];
+            data.word_total = 100;
+            data.words = words
+                .into_iter()
+                .map(|(w, f)| (w.to_string(), f as f64))
+                .collect();
+        })
+    });
+
+    static QWERTY: Lazy<FastLayout> = Lazy::new(|| GEN.fast_layout(&qwerty(), &[]));
+
+    #[test]
+    fn qwerty_word_stats() {
+        let stats = GEN.word_stats(&QWERTY);
+
+        // single letter words are ignored, so 'were' and 'ed' make up a third of all words
+        assert!((stats.one_hand - 100.0 / 3.0).abs() < 1e-9);
+        assert!((stats.with_sfb - 100.0 / 9.0).abs() < 1e-9);
+        assert!((stats.hand_switches - 2.0 * 60.0 / 90.0).abs() < 1e-9);
+        assert_eq!(stats.invalid, 0.0);
+
+        let worst = GEN.worst_words(&QWERTY, 10);
+        assert_eq!(worst.len(), 1);
+        assert_eq!(worst[0].word, "ed");
+        assert_eq!(worst[0].freq, 10.0);
+        assert_eq!(worst[0].sfbs, 1);
+        assert!(worst[0].is_one_hand());
+    }
+
+    #[test]
+    fn collect_words() {
+        let base = std::path::PathBuf::from(concat!(std::env!("CARGO_MANIFEST_DIR"), "/.."));
+        let cleaner = CorpusCleaner::builder()
+            .with_chars("abcdefghijklmnopqrstuvwxyz ".chars())
+            .build();
+        let options = CollectionOptions {
+            words: Some(100),
+            ..Default::default()
+        };
+
+        let data = Data::from_paths_with_options(
+            &[base.join("static/text/monkeyracer")],
+            "monkeyracer",
+            &cleaner,
+            options,
+        )
+        .unwrap();
+
+        assert_eq!(data.words.len(), 100);
+        assert!(data.word_total > 0 && data.word_total < data.char_total);
+        assert!(data.words.keys().all(|w| {
+            !w.is_empty()
+                && w.chars()
+                    .all(|c| c.is_ascii_lowercase() && c != REPLACEMENT_CHAR)
+        }));
+
+        let (most_common, _) = data
+            .words
+            .iter()
+            .max_by(|(_, f1), (_, f2)| f1.total_cmp(f2))
+            .unwrap();
+        assert_eq!(most_common, "the");
+    }
+}
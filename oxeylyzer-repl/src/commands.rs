@@ -1,3 +1,4 @@
+use oxeylyzer_core::data::CollectionOptions;
 use sexp::Sexp;
 
 use crate::repl::*;
@@ -84,8 +85,19 @@ impl Repl {
             Analyze(a) => self.analyze(&a.name_or_nr),
             Compare(c) => self.compare(&c.name1, &c.name2),
             Swap(s) => self.swap(&s.name, &s.swaps),
-            Rank(_) => self.rank(),
-            Generate(i) => self.generate(&i.name, i.count, i.pins),
+            Rank(r) => match r.corpora {
+                Some(corpora) => self.rank_corpora(&corpora, r.aggregate),
+                None => self.rank(),
+            },
+            Generate(i) => match i.corpora {
+                Some(corpora) => {
+                    self.generate_corpora(&i.name, i.count, i.pins, &corpora, i.aggregate)
+                }
+                None => self.generate(&i.name, i.count, i.pins, i.trace),
+            },
+            Temper(t) => self.temper(&t.name, t.replicas, t.rounds, t.pins),
+            Solve(s) => self.solve(&s.name, s.pins, s.time),
+            Trace(t) => self.trace(t.path),
             Save(s) => self.save(s.n, s.name),
             Sfbs(s) => self.sfbs(&s.name, s.count),
             Fspeed(s) => self.fspeed(&s.name, s.count),
@@ -93,10 +105,23 @@ impl Repl {
             Scissors(s) => self.scissors(&s.name, s.count),
             Lsbs(s) => self.lsbs(&s.name, s.count),
             Pinkyring(s) => self.pinky_ring(&s.name, s.count),
+            Words(w) => self.words(&w.name, w.count),
             Language(l) => self.language(l.language),
             Include(l) => self.include(&l.languages),
             Languages(_) => self.languages(),
-            Load(l) => self.load(l.language, l.all, l.raw),
+            Load(l) => {
+                let options = CollectionOptions {
+                    quadgrams: l.quadgrams,
+                    words: l.words,
+                };
+                let format = match l.keylog {
+                    true => CorpusFormat::Keylog {
+                        discard_corrections: l.discard_corrections,
+                    },
+                    false => CorpusFormat::Text,
+                };
+                self.load(l.language, l.all, l.raw, l.source, format, options)
+            }
             Ngram(n) => self.ngram(&n.ngram),
             Reload(_) => self.reload(),
             Quit(_) => return Ok(ReplStatus::Quit),
@@ -4,8 +4,11 @@ use std::fmt::Write;
 
 use itertools::{EitherOrBoth, Itertools};
 use oxeylyzer_core::fast_layout::*;
-use oxeylyzer_core::generate::LayoutStats;
+use oxeylyzer_core::generate::{LayoutStats, MAX_SWAPS};
+use oxeylyzer_core::layout::Layout;
+use oxeylyzer_core::multi_corpus::MultiOxeylyzer;
 use oxeylyzer_core::rayon::iter::ParallelIterator;
+use oxeylyzer_core::trace::TraceReport;
 use oxeylyzer_core::{analyzer_data::AnalyzerData, generate::Oxeylyzer};
 
 use ansi_rgb::{Colorable, rgb};
@@ -36,10 +39,11 @@ pub fn generate_n_with_pins(
     amount: usize,
     based_on: FastLayout,
     pins: &[usize],
-) -> Result<ReplResponse> {
+    trace: bool,
+) -> Result<(ReplResponse, Option<TraceReport>)> {
     if amount == 0 {
         println!("Optimizing 0 variants took: 0 seconds");
-        return Ok(ReplResponse::Nothing);
+        return Ok((ReplResponse::Nothing, None));
     }
 
     let fmt_score = |base| (base as f64) / (layout_gen.data.char_total as f64) / 100.0;
@@ -53,11 +57,28 @@ pub fn generate_n_with_pins(
         .expect("Couldn't initialize the progress bar template")
         .progress_chars("=>-"));
 
-    let mut layouts = layout_gen
-        .generate_n_with_pins_iter(amount, &based_on, pins)
-        .map(|l| (layout_gen.score(&l), l))
-        .progress_with(pb)
-        .collect::<Vec<_>>();
+    let (mut layouts, report) = match trace {
+        true => {
+            let (layouts, traces): (Vec<_>, Vec<_>) = layout_gen
+                .generate_n_with_pins_traced_iter(amount, &based_on, pins)
+                .map(|(l, t)| ((layout_gen.score(&l), l), t))
+                .progress_with(pb)
+                .unzip();
+
+            let scale = layout_gen.data.char_total as f64 * 100.0;
+
+            (layouts, Some(TraceReport::new(traces, scale)))
+        }
+        false => {
+            let layouts = layout_gen
+                .generate_n_with_pins_iter(amount, &based_on, pins)
+                .map(|l| (layout_gen.score(&l), l))
+                .progress_with(pb)
+                .collect::<Vec<_>>();
+
+            (layouts, None)
+        }
+    };
 
     println!(
         "Optimizing {} variants took: {} seconds",
@@ -81,19 +102,108 @@ pub fn generate_n_with_pins(
         .map(|(_, layout)| layout)
         .collect::<Vec<_>>();
 
-    Ok(ReplResponse::multiple_layouts(&layouts, buf))
+    Ok((ReplResponse::multiple_layouts(&layouts, buf), report))
 }
 
-fn format_fspeed(finger_speed: &[f64]) -> String {
-    let f = |v| format!("{:.3}", v * 10.0);
+pub fn format_trace_report(report: &TraceReport) -> Result<String> {
+    const BINS: usize = 10;
+    const BAR_WIDTH: usize = 40;
+
+    let mut buf = String::new();
+
+    writeln!(
+        &mut buf,
+        "{} runs, {:.1}% hit the cap of {MAX_SWAPS} swaps, {:.1} swaps on average, {:.2}s total",
+        report.traces.len(),
+        report.cap_hit_percentage(),
+        report.average_swaps(),
+        report.total_duration().as_secs_f64(),
+    )?;
+    writeln!(&mut buf, "Final scores:")?;
+
+    let histogram = report.histogram(BINS);
+    let max_count = histogram.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+
+    for bin in histogram {
+        let bar = "#".repeat(bin.count * BAR_WIDTH / max_count);
+        writeln!(
+            &mut buf,
+            "{:>9.3} .. {:>9.3} | {bar: <BAR_WIDTH$} {}",
+            bin.start, bin.end, bin.count
+        )?;
+    }
+
+    Ok(buf)
+}
+
+pub fn generate_n_multi_corpus(
+    layout_gen: &Oxeylyzer,
+    multi: &MultiOxeylyzer,
+    amount: usize,
+    based_on: Layout,
+    pins: &[usize],
+) -> Result<ReplResponse> {
+    if amount == 0 {
+        println!("Optimizing 0 variants took: 0 seconds");
+        return Ok(ReplResponse::Nothing);
+    }
+
+    let mut buf = String::new();
+
+    let start = std::time::Instant::now();
+
+    let pb = ProgressBar::new(amount as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{wide_bar:.white/white}] [eta: {eta:>3}] - {per_sec:>11} {pos:>6}/{len}")
+        .expect("Couldn't initialize the progress bar template")
+        .progress_chars("=>-"));
+
+    let mut layouts = multi
+        .generate_n_with_pins_iter(amount, &based_on, pins)
+        .progress_with(pb)
+        .collect::<Vec<_>>();
+
+    println!(
+        "Optimizing {} variants for {} took: {} seconds",
+        amount,
+        multi.names().join(", "),
+        start.elapsed().as_secs()
+    );
+
+    layouts.sort_by(|(_, s1), (_, s2)| s2.aggregate.total_cmp(&s1.aggregate));
+
+    for (i, (layout, score)) in layouts.iter().enumerate().take(10) {
+        let printable = heatmap_string(&layout_gen.fast_layout(layout, &[]), &layout_gen.data);
+        let per_corpus = multi
+            .names()
+            .iter()
+            .zip(&score.scores)
+            .map(|(name, s)| format!("{name}: {s:.5}"))
+            .join(", ");
+
+        writeln!(
+            &mut buf,
+            "#{i}, {}: {:.5}, mean: {:.5}, worst: {:.5}\n{per_corpus}\n{printable}",
+            multi.aggregate, score.aggregate, score.mean, score.worst,
+        )?;
+    }
+
+    let layouts = layouts.into_iter().map(|(layout, _)| layout).collect();
+
+    Ok(ReplResponse::MultipleLayouts {
+        layouts,
+        printable: buf,
+    })
+}
 
+fn format_per_finger(values: &[f64], f: impl Fn(f64) -> String) -> String {
     let mut left_hand = Vec::new();
-    for v in finger_speed.iter().take(5) {
+    for &v in values.iter().take(5) {
         left_hand.push(f(v))
     }
 
     let mut right_hand = Vec::new();
-    for v in finger_speed.iter().rev().take(5) {
+    for &v in values.iter().rev().take(5) {
         right_hand.push(f(v))
     }
 
@@ -104,6 +214,22 @@ fn format_fspeed(finger_speed: &[f64]) -> String {
     format!("{legend}{left_hand}{right_hand}")
 }
 
+fn format_fspeed(finger_speed: &[f64]) -> String {
+    format_per_finger(finger_speed, |v| format!("{:.3}", v * 10.0))
+}
+
+fn format_fspeed_per_distance(fspeed_per_distance: &[f64; 4]) -> String {
+    ["Sfb", "Dsfb", "Dsfb2", "Dsfb3"]
+        .iter()
+        .zip(fspeed_per_distance)
+        .map(|(name, v)| format!("{name} {v:.3}"))
+        .join(", ")
+}
+
+fn format_travel(finger_travel: &[f64]) -> String {
+    format_per_finger(finger_travel, |v| format!("{v:.3}"))
+}
+
 pub fn get_print_layout_stats(stats: &LayoutStats, data: &AnalyzerData) -> Result<String> {
     let fmt_score = |base| (base as f64) / (data.char_total as f64) / 100.0;
 
@@ -113,19 +239,64 @@ pub fn get_print_layout_stats(stats: &LayoutStats, data: &AnalyzerData) -> Resul
         &mut buf,
         concat!(
             "Sfb:  {:.3}%\nDsfb: {:.3}%\n\nFinger Speed: {:.3}\n",
-            "{}\nStretches: {:.3}%\nScissors: {:.3}%\nLsbs: {:.3}%\n",
+            "{}Per Distance: {}\n\nFinger Travel: {:.3}\n{}\nKey Effort: {:.3}\n",
+            "Stretches: {:.3}%\nScissors: {:.3}%\nLsbs: {:.3}%\n",
             "Pinky Ring Bigrams: {:.3}%\n",
         ),
         stats.sfb,
         stats.dsfb,
         stats.fspeed,
         format_fspeed(&stats.finger_speed),
+        format_fspeed_per_distance(&stats.fspeed_per_distance),
+        stats.travel,
+        format_travel(&stats.finger_travel),
+        stats.effort,
         stats.stretches,
         stats.scissors,
         stats.lsbs,
         stats.pinky_ring,
     )?;
 
+    let [left, right] = stats.hand_usage;
+    let [top, home, bottom, thumb] = stats.row_usage;
+
+    write!(
+        &mut buf,
+        "Hand Balance: {left:.2}% left, {right:.2}% right\n\
+			Top Row: {top:.3}%\n\
+			Home Row: {home:.3}%\n\
+			Bottom Row: {bottom:.3}%\n",
+    )?;
+
+    if thumb > 0.0 {
+        writeln!(&mut buf, "Thumb Row: {thumb:.3}%")?;
+    }
+
+    if let Some(time) = stats.typing_time {
+        writeln!(&mut buf, "Estimated Typing Time: {time:.1}ms per bigram")?;
+    }
+
+    writeln!(&mut buf)?;
+
+    let b = &stats.bigram_stats;
+    let fmt_rolls = |rolls: &[f64; 3]| rolls.map(|r| format!("{r:.3}%")).join(", ");
+
+    writeln!(
+        &mut buf,
+        "Bigram Inrolls: {:.3}% ({})\n\
+			Bigram Outrolls: {:.3}% ({})\n\
+			Adjacent Finger Rolls: {:.3}%\n\
+			Same Hand Bigrams: {:.3}%\n\
+			Cross Hand Bigrams: {:.3}%\n",
+        b.total_inrolls(),
+        fmt_rolls(&b.inrolls),
+        b.total_outrolls(),
+        fmt_rolls(&b.outrolls),
+        b.adjacent_rolls(),
+        b.same_hand,
+        b.cross_hand,
+    )?;
+
     let t = &stats.trigram_stats;
 
     writeln!(
@@ -143,8 +314,7 @@ pub fn get_print_layout_stats(stats: &LayoutStats, data: &AnalyzerData) -> Resul
 			Bad Redirects Sfs: {:.3}%\n\
 			Total Redirects: {:.3}%\n\n\
 			Bad Sfbs: {:.3}%\n\
-			Sft: {:.3}%\n\
-			Score: {:.3}",
+			Sft: {:.3}%",
         t.inrolls,
         t.outrolls,
         (t.inrolls + t.outrolls),
@@ -159,9 +329,37 @@ pub fn get_print_layout_stats(stats: &LayoutStats, data: &AnalyzerData) -> Resul
         (t.redirects + t.redirects_sfs + t.bad_redirects + t.bad_redirects_sfs),
         t.bad_sfbs,
         t.sfts,
-        fmt_score(stats.score),
     )?;
 
+    if t.thumb_alternates + t.thumb_rolls + t.thumb_redirects + t.thumbs > 0.0 {
+        writeln!(
+            &mut buf,
+            "\nThumb Alternates: {:.3}%\n\
+			Thumb Rolls: {:.3}%\n\
+			Thumb Redirects: {:.3}%\n\
+			Thumb Sfbs: {:.3}%",
+            t.thumb_alternates, t.thumb_rolls, t.thumb_redirects, t.thumbs,
+        )?;
+    }
+
+    for (name, freq) in &t.custom {
+        writeln!(&mut buf, "{name}: {freq:.3}%")?;
+    }
+
+    let q = &stats.quadgram_stats;
+
+    if q.rolls + q.chained_alternates + q.double_redirects + q.other > 0.0 {
+        writeln!(
+            &mut buf,
+            "\nQuadgram Rolls: {:.3}%\n\
+			Chained Alternates: {:.3}%\n\
+			Double Redirects: {:.3}%\n",
+            q.rolls, q.chained_alternates, q.double_redirects,
+        )?;
+    }
+
+    writeln!(&mut buf, "Score: {:.3}", fmt_score(stats.score))?;
+
     Ok(buf)
 }
 
@@ -208,10 +406,17 @@ pub fn get_print_compare_stats(
             "Sfb:                {: <11} Sfb:                {:.3}%\n",
             "Dsfb:               {: <11} Dsfb:               {:.3}%\n",
             "Finger Speed:       {: <11} Finger Speed:       {:.3}\n",
+            "Finger Travel:      {: <11} Finger Travel:      {:.3}\n",
+            "Key Effort:         {: <11} Key Effort:         {:.3}\n",
             "Stretches:          {: <11} Stretches:          {:.3}\n",
             "Scissors:           {: <11} Scissors:           {:.3}%\n",
             "Lsbs:               {: <11} Lsbs:               {:.3}%\n",
             "Pinky Ring Bigrams: {: <11} Pinky Ring Bigrams: {:.3}%\n\n",
+            "Left Hand:          {: <11} Left Hand:          {:.2}%\n",
+            "Right Hand:         {: <11} Right Hand:         {:.2}%\n",
+            "Top Row:            {: <11} Top Row:            {:.3}%\n",
+            "Home Row:           {: <11} Home Row:           {:.3}%\n",
+            "Bottom Row:         {: <11} Bottom Row:         {:.3}%\n\n",
             "Inrolls:            {: <11} Inrolls:            {:.2}%\n",
             "Outrolls:           {: <11} Outrolls:           {:.2}%\n",
             "Total Rolls:        {: <11} Total Rolls:        {:.2}%\n",
@@ -234,6 +439,10 @@ pub fn get_print_compare_stats(
         s2.dsfb,
         format!("{:.3}", s1.fspeed),
         s2.fspeed,
+        format!("{:.3}", s1.travel),
+        s2.travel,
+        format!("{:.3}", s1.effort),
+        s2.effort,
         format!("{:.3}", s1.stretches),
         s2.stretches,
         format!("{:.3}%", s1.scissors),
@@ -242,6 +451,16 @@ pub fn get_print_compare_stats(
         s2.lsbs,
         format!("{:.3}%", s1.pinky_ring),
         s2.pinky_ring,
+        format!("{:.2}%", s1.hand_usage[0]),
+        s2.hand_usage[0],
+        format!("{:.2}%", s1.hand_usage[1]),
+        s2.hand_usage[1],
+        format!("{:.3}%", s1.row_usage[0]),
+        s2.row_usage[0],
+        format!("{:.3}%", s1.row_usage[1]),
+        s2.row_usage[1],
+        format!("{:.3}%", s1.row_usage[2]),
+        s2.row_usage[2],
         format!("{:.2}%", ts1.inrolls),
         ts2.inrolls,
         format!("{:.2}%", ts1.outrolls),
@@ -20,7 +20,13 @@ xflags::xflags! {
             repeated swaps: String
         }
         /// Rank all layouts for the currently specified language. A higher score is better.
-        cmd rank list {}
+        cmd rank list {
+            /// Scores against several corpora instead, `--corpora english,dutch:2` gives dutch a
+            /// weight of 2. Shows the score per corpus, the mean and the worst score.
+            optional --corpora corpora: String
+            /// How to combine scores of multiple corpora: min, mean or weighted. Mean by default.
+            optional --aggregate aggregate: String
+        }
         /// Improves the the given layout. Optionally, you can provide a list of pinned characters
         /// to keep in place during optimization.
         cmd generate gen g improve i optimize {
@@ -28,6 +34,40 @@ xflags::xflags! {
             optional count: usize
             /// Sets pinned characters on the layout to optimize, `-p abc` pins `abc`.
             optional -p, --pins pins: String
+            /// Collects telemetry of every run, which can be inspected with `trace` afterwards.
+            optional -t, --trace
+            /// Optimizes for several corpora at once, `--corpora english,dutch:2` gives dutch a
+            /// weight of 2.
+            optional --corpora corpora: String
+            /// How to combine scores of multiple corpora: min, mean or weighted. Mean by default.
+            optional --aggregate aggregate: String
+        }
+        /// Improves the given layout using parallel tempering: one annealing chain per replica, each
+        /// at a different temperature, which periodically exchange layouts. Shows the best layout
+        /// found across all replicas.
+        cmd temper anneal {
+            required name: String
+            /// Amount of replicas. Defaults to the amount of threads that are available.
+            optional -r, --replicas replicas: usize
+            /// Amount of rounds after which replicas attempt to exchange layouts. 500 by default.
+            optional --rounds rounds: usize
+            /// Sets pinned characters on the layout to optimize, `-p abc` pins `abc`.
+            optional -p, --pins pins: String
+        }
+        /// Finds the optimal arrangement of all keys that are not pinned, using branch and bound.
+        /// Only feasible for about 12 free keys or fewer.
+        cmd solve {
+            required name: String
+            /// Sets pinned characters on the layout to optimize, `-p abc` pins `abc`.
+            optional -p, --pins pins: String
+            /// Time budget in seconds. If the search doesn't complete in time, the best layout
+            /// found so far is shown instead. 60 by default.
+            optional -t, --time seconds: u64
+        }
+        /// Summarizes the telemetry of the last `generate --trace` run. Optionally exports all
+        /// runs to a file, as CSV if the path ends in `.csv` and as JSON otherwise.
+        cmd trace {
+            optional path: PathBuf
         }
         /// Saves the nth layout that was generated. Optionally, you can provide a name as `-n <name>`.
         cmd save s {
@@ -59,6 +99,12 @@ xflags::xflags! {
             required name: String
             optional -c, --count count: usize
         }
+        /// Shows word statistics and the n most common words that contain an sfb on a layout. 10
+        /// by default. Requires a corpus loaded with `--words`.
+        cmd words w {
+            required name: String
+            optional -c, --count count: usize
+        }
         /// Shows the top n stretches on a layout. 10 by default.
         cmd stretches {
             required name: String
@@ -82,10 +128,26 @@ xflags::xflags! {
             /// If set, processes the corpus as-is without cleaning it.
             optional -r, --raw
             /// If set, processes all corpora found in ./static/text where the folder name is the
-            /// language name.
+            /// language name. Can't be combined with `--source` or `--keylog`.
             optional -a, --all
+            /// If set, also collects this many of the most frequent quadgrams. Quadgrams are only
+            /// used for scoring when `quadgram_precision` in the config is above 0.
+            optional -q, --quadgrams count: usize
+            /// If set, also collects this many of the most common words, which are used by the
+            /// `words` command.
+            optional -w, --words count: usize
+            /// Reads the corpus from this file or directory instead of the sources in the corpus
+            /// config. Gzip and zstd compressed files are decompressed on the fly, and `-` reads
+            /// the corpus from stdin until it is closed.
+            optional -s, --source source: PathBuf
+            /// Treats the sources as keystroke logs in JSONL format instead of plain text, which
+            /// counts backspace and other corrections as they were typed.
+            optional -k, --keylog
+            /// When loading keystroke logs, drops keystrokes that were erased with backspace along
+            /// with the backspaces themselves.
+            optional --discard-corrections
         }
-        /// Gives information about a certain ngram. for 2 letter ones, skipgram info will be provided as well.
+        /// Gives information about a certain ngram of up to 4 letters. for 2 letter ones, skipgram info will be provided as well.
         cmd ngram n occ freq {
             required ngram: String
         }
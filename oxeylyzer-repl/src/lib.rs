@@ -1,5 +1,4 @@
 pub mod commands;
-pub mod corpus_transposition;
 pub mod display;
 pub mod flags;
 pub mod repl;
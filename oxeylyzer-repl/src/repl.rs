@@ -6,14 +6,21 @@ use std::io::Write as _;
 use std::path::{Path, PathBuf};
 
 use itertools::Itertools;
+use oxeylyzer_core::analyzer_data::AnalyzerData;
+use oxeylyzer_core::code_corpus::CodeOptions;
 use oxeylyzer_core::corpus_cleaner::CorpusCleaner;
-use oxeylyzer_core::data::Data;
+use oxeylyzer_core::corpus_config::{CorpusConfig, CorpusSource};
+use oxeylyzer_core::data::{CollectionOptions, Data};
+use oxeylyzer_core::keylog::{KeyEvent, load_events, parse_events};
 use oxeylyzer_core::{OxeylyzerError, OxeylyzerResultExt};
 use oxeylyzer_core::{
     fast_layout::*,
     generate::Oxeylyzer,
     layout::{Layout, PosPair},
+    multi_corpus::{Aggregate, MultiOxeylyzer},
     rayon,
+    tempering::TemperingOptions,
+    trace::TraceReport,
     weights::Config,
 };
 use rustyline::DefaultEditor;
@@ -24,7 +31,6 @@ use serde_json::Serializer;
 use serde_json::ser::PrettyFormatter;
 use thiserror::Error;
 
-use crate::corpus_transposition::CorpusConfig;
 use crate::display::*;
 
 pub const EXIT_MESSAGE: &str = "Exiting analyzer...";
@@ -38,6 +44,17 @@ fn get_subcommand(cmd: &str) -> String {
         .collect::<String>()
 }
 
+/// Reads the key events of the keystroke log of every source, where `-` reads a log from stdin.
+fn read_key_events(sources: &[CorpusSource]) -> oxeylyzer_core::Result<Vec<Vec<KeyEvent>>> {
+    sources
+        .iter()
+        .map(|source| match source.path.as_path() {
+            p if p == Path::new("-") => parse_events(std::io::stdin().lock()),
+            p => load_events(p),
+        })
+        .collect()
+}
+
 #[derive(Debug, Error)]
 pub enum ReplError {
     #[error("Layout '{0}' not found. It might exist, but it's not currently loaded.")]
@@ -50,8 +67,10 @@ pub enum ReplError {
     ShlexError,
     #[error("Index '{0}' is out of bounds after generating {1} layouts")]
     IndexOutOfBounds(usize, usize),
-    #[error("Invalid ngram length, found length {0}. Allowed lengths: 1, 2, 3")]
+    #[error("Invalid ngram length, found length {0}. Allowed lengths: 1, 2, 3, 4")]
     InvalidNgramLength(usize),
+    #[error("No word data for '{0}'. Load it with `load {0} --words <count>` first")]
+    NoWordData(String),
     #[error(
         "Failed to parse lisp expression: {err_message}\n{line}\n{}",
         std::iter::repeat_n(" " , idx.saturating_sub(2)).chain(["^"]).collect::<String>()
@@ -69,12 +88,12 @@ pub enum ReplError {
     MissingLanguageFlag,
     #[error("Could not serialize layout:\n{}\n", .0.formatted_string())]
     CouldNotSerializeLayout(Box<FastLayout>),
+    #[error("No telemetry available, run `generate <layout> --trace` first")]
+    NoTrace,
+    #[error("Invalid corpus weight in '{0}', expected `<corpus>:<weight>` like `english:2`")]
+    InvalidCorpusWeight(String),
     #[error("Could not find corpus config for corpus '{0}'")]
     CouldNotFindCorpusConfig(String),
-    #[error(
-        "Shift key can only be a single char, found '{}' with length {}", .0, .0.chars().count()
-    )]
-    WrongShiftKeyLength(String),
     #[error(
         "{}\n{} '{}'",
         "Failed to get corpus path: the process is ./path/corpus.json -> ./path/<new_lang>.json,",
@@ -86,6 +105,12 @@ pub enum ReplError {
         "Could not get file name for corpus config file '{}'. Is it even a file?", .0.display()
     )]
     NoCorpusConfigFileName(PathBuf),
+    #[error("A single `--source` can't be loaded for `--all` corpora at once")]
+    SourceWithAll,
+    #[error(
+        "`--keylog` can't be combined with `--all`, key logs are loaded one language at a time"
+    )]
+    KeylogWithAll,
     #[error(
         "Attempting to execute command '{}' when '{}' does not return a layout",
         .0, get_subcommand(&.0)
@@ -104,6 +129,15 @@ pub enum ReplError {
 
 pub type Result<T> = std::result::Result<T, ReplError>;
 
+/// How the sources of a corpus are read by `load`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusFormat {
+    /// Plain text, which may be compressed.
+    Text,
+    /// Keystroke logs in JSONL format.
+    Keylog { discard_corrections: bool },
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ReplStatus {
     Continue,
@@ -153,6 +187,7 @@ pub struct Repl {
     saved: HashMap<String, Layout>,
     temp_generated: Vec<Layout>,
     temp_command_layouts: HashMap<String, Layout>,
+    last_trace: Option<TraceReport>,
     thread_pool: rayon::ThreadPool,
     corpus_configs: PathBuf,
     language_data: PathBuf,
@@ -166,8 +201,8 @@ impl Repl {
         let base = PathBuf::from(BASE_PATH);
 
         let config = Config::with_loaded_weights(base.join(config_name))?;
-        let data = Data::load(base.join(&config.corpus)).unwrap();
-        let language = data.name.clone();
+        let data = AnalyzerData::load(base.join(&config.corpus), &config.weights).unwrap();
+        let language = data.name().to_string();
 
         let corpus_configs = config.corpus_configs.clone();
         let language_data = config
@@ -189,9 +224,11 @@ impl Repl {
                     .inspect_err(|e| println!("Error loading layout at '{}': {e}", p.display()))
             })
             .flat_map(|h| h.into_iter())
-            .collect();
+            .collect::<HashMap<_, _>>();
+
+        warn_effort_grid(config.weights.key_effort.grid_len(), saved.values());
 
-        let layout_gen = Oxeylyzer::new(data, config);
+        let layout_gen = Oxeylyzer::with_analyzer_data(data, config)?;
 
         Ok(Self {
             saved,
@@ -199,6 +236,7 @@ impl Repl {
             layout_gen,
             temp_generated: Vec::new(),
             temp_command_layouts: HashMap::new(),
+            last_trace: None,
             thread_pool,
             corpus_configs,
             language_data,
@@ -329,6 +367,68 @@ impl Repl {
         Ok(ReplResponse::no_layout(buf))
     }
 
+    fn multi_corpus(&self, corpora: &str, aggregate: Option<String>) -> Result<MultiOxeylyzer> {
+        let config = Config::with_loaded_weights(PathBuf::from(BASE_PATH).join("config.toml"))?;
+        let aggregate = match aggregate {
+            Some(a) => a.parse::<Aggregate>()?,
+            None => Aggregate::default(),
+        };
+
+        let corpora = corpora
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(|corpus| {
+                let (name, weight) = match corpus.split_once(':') {
+                    Some((name, weight)) => match weight.parse::<f64>() {
+                        Ok(weight) => (name, weight),
+                        Err(_) => return Err(ReplError::InvalidCorpusWeight(corpus.to_string())),
+                    },
+                    None => (corpus, 1.0),
+                };
+
+                let corpus_path = PathBuf::from(BASE_PATH)
+                    .join(&self.language_data)
+                    .join(name)
+                    .with_extension("json");
+
+                let data = AnalyzerData::load(corpus_path, &config.weights)?;
+
+                Ok((Oxeylyzer::with_analyzer_data(data, config.clone())?, weight))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(MultiOxeylyzer::new(corpora, aggregate)?)
+    }
+
+    pub fn rank_corpora(&self, corpora: &str, aggregate: Option<String>) -> Result<ReplResponse> {
+        let mut buf = String::new();
+        let multi = self.multi_corpus(corpora, aggregate)?;
+
+        let header = multi.names().iter().map(|n| format!("{n: >10}")).join(" ");
+        writeln!(
+            &mut buf,
+            "{: <15} {header} {: >10} {: >10} {: >10}",
+            "", "mean", "worst", multi.aggregate
+        )?;
+
+        self.saved
+            .iter()
+            .map(|(n, l)| (n, multi.score(l)))
+            .sorted_by(|(_, a), (_, b)| a.aggregate.total_cmp(&b.aggregate))
+            .map(|(n, s)| {
+                let scores = s.scores.iter().map(|v| format!("{v: >10.3}")).join(" ");
+                writeln!(
+                    &mut buf,
+                    "{n: <15} {scores} {: >10.3} {: >10.3} {: >10.3}",
+                    s.mean, s.worst, s.aggregate
+                )
+            })
+            .try_for_each(|e| e)?;
+
+        Ok(ReplResponse::no_layout(buf))
+    }
+
     pub fn pin_positions(&self, layout: &FastLayout, pin_chars: String) -> Vec<usize> {
         let m = HashSet::<char>::from_iter(pin_chars.chars());
 
@@ -346,6 +446,7 @@ impl Repl {
         name: &str,
         count: Option<usize>,
         pin_chars: Option<String>,
+        trace: bool,
     ) -> Result<ReplResponse> {
         let layout = self.layout(name)?.clone();
 
@@ -355,9 +456,14 @@ impl Repl {
             None => vec![],
         };
 
-        let response = self
+        let (response, report) = self
             .thread_pool
-            .install(|| generate_n_with_pins(&self.layout_gen, count, layout, &pins))?;
+            .install(|| generate_n_with_pins(&self.layout_gen, count, layout, &pins, trace))?;
+
+        if let Some(report) = report {
+            println!("{}", format_trace_report(&report)?);
+            self.last_trace = Some(report);
+        }
 
         use ReplResponse as RR;
 
@@ -370,6 +476,140 @@ impl Repl {
         }
     }
 
+    pub fn generate_corpora(
+        &mut self,
+        name: &str,
+        count: Option<usize>,
+        pin_chars: Option<String>,
+        corpora: &str,
+        aggregate: Option<String>,
+    ) -> Result<ReplResponse> {
+        let layout = self.layout(name)?;
+        let multi = self.multi_corpus(corpora, aggregate)?;
+
+        let count = count.unwrap_or(2500);
+        let pins = match pin_chars {
+            Some(chars) => self.pin_positions(&layout, chars),
+            None => vec![],
+        };
+
+        let response = self.thread_pool.install(|| {
+            generate_n_multi_corpus(&self.layout_gen, &multi, count, layout.into(), &pins)
+        })?;
+
+        use ReplResponse as RR;
+
+        match response {
+            RR::MultipleLayouts { layouts, printable } => {
+                self.temp_generated = layouts.clone();
+                Ok(RR::MultipleLayouts { layouts, printable })
+            }
+            response => Ok(response),
+        }
+    }
+
+    pub fn temper(
+        &mut self,
+        name: &str,
+        replicas: Option<usize>,
+        rounds: Option<usize>,
+        pin_chars: Option<String>,
+    ) -> Result<ReplResponse> {
+        let layout = self.layout(name)?;
+
+        let pins = match pin_chars {
+            Some(chars) => self.pin_positions(&layout, chars),
+            None => vec![],
+        };
+
+        let start = std::time::Instant::now();
+
+        let generated = self.thread_pool.install(|| {
+            let default = TemperingOptions::default();
+            let options = TemperingOptions {
+                replicas: replicas.unwrap_or(default.replicas),
+                rounds: rounds.unwrap_or(default.rounds),
+                ..default
+            };
+
+            self.layout_gen.generate_tempered(&layout, &pins, &options)
+        });
+
+        println!(
+            "Parallel tempering took: {} seconds",
+            start.elapsed().as_secs()
+        );
+
+        let printable = self.analyze_layout(&generated)?;
+
+        self.temp_generated = vec![generated.clone().into()];
+
+        Ok(ReplResponse::single_layout(generated, printable))
+    }
+
+    pub fn solve(
+        &mut self,
+        name: &str,
+        pin_chars: Option<String>,
+        seconds: Option<u64>,
+    ) -> Result<ReplResponse> {
+        let layout = self.layout(name)?;
+
+        let pins = match pin_chars {
+            Some(chars) => self.pin_positions(&layout, chars),
+            None => vec![],
+        };
+        let time_budget = std::time::Duration::from_secs(seconds.unwrap_or(60));
+
+        println!(
+            "Solving {} free keys with a time budget of {}s...",
+            layout.keys.len() - pins.len(),
+            time_budget.as_secs()
+        );
+
+        let result = self
+            .thread_pool
+            .install(|| self.layout_gen.solve(&layout, &pins, time_budget));
+
+        let mut buf = String::new();
+
+        match result.proven {
+            true => writeln!(
+                &mut buf,
+                "Proved optimal in {:.2}s, visiting {} nodes\n",
+                result.duration.as_secs_f64(),
+                result.nodes
+            )?,
+            false => writeln!(
+                &mut buf,
+                "Ran out of time after visiting {} nodes, this is the best layout found so far\n",
+                result.nodes
+            )?,
+        }
+        write!(&mut buf, "{}", self.analyze_layout(&result.layout)?)?;
+
+        self.temp_generated = vec![result.layout.clone().into()];
+
+        Ok(ReplResponse::single_layout(result.layout, buf))
+    }
+
+    pub fn trace(&self, path: Option<PathBuf>) -> Result<ReplResponse> {
+        let report = self.last_trace.as_ref().ok_or(ReplError::NoTrace)?;
+
+        match path {
+            Some(path) => {
+                report.save(&path)?;
+                println!(
+                    "Exported {} runs to '{}'",
+                    report.traces.len(),
+                    path.display()
+                );
+                Ok(ReplResponse::Nothing)
+            }
+            None => Ok(ReplResponse::no_layout(format_trace_report(report)?)),
+        }
+    }
+
     fn placeholder_name(&self, layout: &FastLayout) -> Result<String> {
         for i in 1..1000usize {
             let new_name = layout
@@ -618,6 +858,36 @@ impl Repl {
         Ok(ReplResponse::no_layout(buf))
     }
 
+    pub fn words(&self, name: &str, top_n: Option<usize>) -> Result<ReplResponse> {
+        let mut buf = String::new();
+        let layout = self.layout(name)?;
+
+        if self.layout_gen.data.word_total == 0 {
+            return Err(ReplError::NoWordData(self.layout_gen.language.clone()));
+        }
+
+        let stats = self.layout_gen.word_stats(&layout);
+
+        writeln!(
+            &mut buf,
+            "One Hand Words: {:.3}%
+Words With Sfbs: {:.3}%
+Hand Switches Per Word: {:.3}
+",
+            stats.one_hand, stats.with_sfb, stats.hand_switches
+        )?;
+
+        let worst = self.layout_gen.worst_words(&layout, top_n.unwrap_or(10));
+
+        writeln!(&mut buf, "top {} words with sfbs for {name}:", worst.len())?;
+
+        for w in worst {
+            writeln!(&mut buf, "{}: {:.3}% ({} sfbs)", w.word, w.freq, w.sfbs)?;
+        }
+
+        Ok(ReplResponse::no_layout(buf))
+    }
+
     pub fn fspeed(&self, name: &str, top_n: Option<usize>) -> Result<ReplResponse> {
         let mut buf = String::new();
         let layout = self.layout(name)?;
@@ -726,6 +996,10 @@ impl Repl {
 
                 Ok::<_, ReplError>(layouts)
             })
+            .inspect(|layouts| {
+                let grid = self.layout_gen.weights.key_effort.grid.as_ref();
+                warn_effort_grid(grid.map(|g| g.len()), layouts.values());
+            })
             .flatten()
             .map(|(name, l)| {
                 self.saved.insert(name, l.clone());
@@ -761,28 +1035,97 @@ impl Repl {
         Ok(ReplResponse::no_layout(buf))
     }
 
-    fn load_one_with_cleaner<P: AsRef<Path>>(
+    fn load_one_with_cleaner(
         &mut self,
         language: &str,
         cleaner: CorpusCleaner,
-        corpus_paths: &[P],
+        sources: &[CorpusSource],
+        code: Option<CodeOptions>,
+        format: CorpusFormat,
+        options: CollectionOptions,
     ) -> Result<()> {
         let language_data_path = PathBuf::from(BASE_PATH).join(&self.language_data);
 
-        match Data::from_paths(corpus_paths, language, &cleaner) {
-            Ok(data) => match data.save(language_data_path) {
-                Ok(_) => println!("Saved data for {language}!"),
-                Err(e) => println!("Failed to save data for {language}: {e}"),
+        if code.is_some() && matches!(format, CorpusFormat::Keylog { .. }) {
+            println!(
+                "Ignoring the [code] table of {language}, key logs already contain indentation as it was typed"
+            );
+        }
+
+        let data = match (format, sources) {
+            (
+                CorpusFormat::Keylog {
+                    discard_corrections,
+                },
+                _,
+            ) => read_key_events(sources).and_then(|logs| {
+                Data::from_key_logs_with_report(
+                    sources,
+                    &logs,
+                    language,
+                    &cleaner,
+                    options,
+                    discard_corrections,
+                )
+            }),
+            (CorpusFormat::Text, [source]) if source.path == Path::new("-") => match &code {
+                Some(code) => std::io::read_to_string(std::io::stdin().lock())
+                    .path_context("stdin")
+                    .and_then(|text| {
+                        let text = code.process_text(text);
+                        Data::from_reader_with_report(text.as_bytes(), language, &cleaner, options)
+                    }),
+                None => Data::from_reader_with_report(
+                    std::io::stdin().lock(),
+                    language,
+                    &cleaner,
+                    options,
+                ),
             },
+            (CorpusFormat::Text, _) => match &code {
+                Some(code) => {
+                    Data::from_code_with_report(sources, language, &cleaner, code, options)
+                }
+                None => Data::from_sources_with_report(sources, language, &cleaner, options),
+            },
+        };
+
+        match data {
+            Ok((data, report)) => {
+                println!("{report}");
+
+                match data.save(&language_data_path) {
+                    Ok(_) => println!("Saved data for {language}!"),
+                    Err(e) => println!("Failed to save data for {language}: {e}"),
+                }
+                if let Err(e) = report.save(&language_data_path) {
+                    println!("Failed to save corpus report for {language}: {e}");
+                }
+            }
             Err(e) => println!("Couldn't convert language: {e}"),
         };
 
         Ok(())
     }
 
-    pub fn load(&mut self, language: String, all: bool, raw: bool) -> Result<ReplResponse> {
+    pub fn load(
+        &mut self,
+        language: String,
+        all: bool,
+        raw: bool,
+        source: Option<PathBuf>,
+        format: CorpusFormat,
+        options: CollectionOptions,
+    ) -> Result<ReplResponse> {
         let corpus_configs = PathBuf::from(BASE_PATH).join(&self.corpus_configs);
 
+        if all && source.is_some() {
+            return Err(ReplError::SourceWithAll);
+        }
+        if all && matches!(format, CorpusFormat::Keylog { .. }) {
+            return Err(ReplError::KeylogWithAll);
+        }
+
         match (all, raw) {
             (true, true) => {
                 glob::glob(&corpus_configs.to_string_lossy())
@@ -791,6 +1134,7 @@ impl Repl {
                     .map(|path| {
                         let config = CorpusConfig::load(&path)?;
                         let sources = config.sources().to_vec();
+                        let code = config.code().cloned();
                         let cleaner = CorpusCleaner::raw();
                         let language = path
                             .file_stem()
@@ -799,7 +1143,9 @@ impl Repl {
 
                         println!("loading raw data for language: {language}...");
 
-                        self.load_one_with_cleaner(&language, cleaner, &sources)
+                        self.load_one_with_cleaner(
+                            &language, cleaner, &sources, code, format, options,
+                        )
                     })
                     .for_each(|res| {
                         let _ = res.inspect_err(|e| eprintln!("{e}"));
@@ -812,7 +1158,8 @@ impl Repl {
                     .map(|path| {
                         let config = CorpusConfig::load(&path)?;
                         let sources = config.sources().to_vec();
-                        let cleaner = config.into();
+                        let code = config.code().cloned();
+                        let cleaner = CorpusCleaner::try_from(config)?;
                         let language = path
                             .file_stem()
                             .ok_or_else(|| ReplError::NoCorpusConfigFileName(path.clone()))?
@@ -820,7 +1167,9 @@ impl Repl {
 
                         println!("loading data for language: {language}...");
 
-                        self.load_one_with_cleaner(&language, cleaner, &sources)
+                        self.load_one_with_cleaner(
+                            &language, cleaner, &sources, code, format, options,
+                        )
                     })
                     .for_each(|res| {
                         let _ = res.inspect_err(|e| eprintln!("{e}"));
@@ -838,12 +1187,13 @@ impl Repl {
                     .ok_or_else(|| ReplError::CouldNotFindCorpusConfig(language.clone()))?;
 
                 let config = CorpusConfig::load(config_path)?;
-                let sources = config.sources();
+                let sources = source.map_or_else(|| config.sources().to_vec(), |s| vec![s.into()]);
+                let code = config.code().cloned();
                 let cleaner = CorpusCleaner::raw();
 
                 println!("loading raw data for language: {language}...");
 
-                self.load_one_with_cleaner(&language, cleaner, sources)?;
+                self.load_one_with_cleaner(&language, cleaner, &sources, code, format, options)?;
             }
             (false, false) => {
                 let config_path = glob::glob(&corpus_configs.to_string_lossy())
@@ -857,12 +1207,13 @@ impl Repl {
                     .ok_or_else(|| ReplError::CouldNotFindCorpusConfig(language.clone()))?;
 
                 let config = CorpusConfig::load(config_path)?;
-                let sources = config.sources().to_vec();
-                let cleaner = CorpusCleaner::from(config);
+                let sources = source.map_or_else(|| config.sources().to_vec(), |s| vec![s.into()]);
+                let code = config.code().cloned();
+                let cleaner = CorpusCleaner::try_from(config)?;
 
                 println!("loading data for {language}...");
 
-                self.load_one_with_cleaner(&language, cleaner, &sources)?;
+                self.load_one_with_cleaner(&language, cleaner, &sources, code, format, options)?;
                 self.language(Some(language))?;
             }
         };
@@ -924,6 +1275,24 @@ impl Repl {
                     (occ as f64) / (data.trigram_total as f64) * 100.0
                 )?
             }
+            4 if data.quadgram_total == 0 => writeln!(
+                &mut buf,
+                "No quadgram data for this language. Load it with `load <language> --quadgrams <count>`"
+            )?,
+            4 => {
+                let quadgram: [char; 4] = ngram.chars().collect::<Vec<char>>().try_into().unwrap();
+                let q = quadgram.map(|c| data.mapping.get_u(c));
+                let &(_, occ) = data
+                    .gen_quadgrams()
+                    .iter()
+                    .find(|&&(qf, _)| qf == q)
+                    .unwrap_or(&(q, 0));
+                writeln!(
+                    &mut buf,
+                    "{ngram}: {:.3}%",
+                    (occ as f64) / (data.quadgram_total as f64) * 100.0
+                )?
+            }
             n => return Err(ReplError::InvalidNgramLength(n)),
         };
 
@@ -943,7 +1312,7 @@ impl Repl {
             .join(language)
             .with_extension("json");
 
-        let data = Data::load(corpus_path)?;
+        let data = AnalyzerData::load(corpus_path, &config.weights)?;
 
         let saved = config
             .layouts
@@ -954,9 +1323,11 @@ impl Repl {
             })
             .flat_map(|h| h.into_iter())
             .chain(std::mem::take(&mut self.saved))
-            .collect();
+            .collect::<HashMap<_, _>>();
+
+        warn_effort_grid(config.weights.key_effort.grid_len(), saved.values());
 
-        let generator = Oxeylyzer::new(data, config);
+        let generator = Oxeylyzer::with_analyzer_data(data, config)?;
 
         self.language_data = language_data;
         self.corpus_configs = corpus_configs;
@@ -978,6 +1349,27 @@ impl Repl {
     }
 }
 
+/// Warns about layouts that don't have a key for every value of the key effort grid, as their key
+/// effort is derived from the preset instead.
+fn warn_effort_grid<'a>(grid_len: Option<usize>, layouts: impl IntoIterator<Item = &'a Layout>) {
+    let Some(grid_len) = grid_len else {
+        return;
+    };
+
+    let names = layouts
+        .into_iter()
+        .filter(|l| l.keys.len() != grid_len)
+        .map(|l| &l.name)
+        .sorted()
+        .join(", ");
+
+    if !names.is_empty() {
+        println!(
+            "The key effort grid has {grid_len} values, so the key effort preset is used for: {names}"
+        );
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub fn load_layouts<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Layout>> {
     let base = PathBuf::from(BASE_PATH).join(&path);